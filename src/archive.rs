@@ -0,0 +1,317 @@
+//! Compressed files and archives.
+
+use crate::Entry;
+use std::{
+    io::{self, Cursor, Read},
+    path::{Path, PathBuf},
+};
+
+// How many levels of archives nested inside archives are expanded.
+const MAX_DEPTH: usize = 4;
+// The maximum decompressed size of a single file, to guard against decompression bombs.
+const MAX_SIZE: u64 = 256 * 1024 * 1024;
+// The maximum decompressed size of all files in an archive, including nested archives.
+const MAX_TOTAL_SIZE: u64 = 1024 * 1024 * 1024;
+// The maximum number of files in an archive, including the files in nested archives.
+const MAX_ENTRIES: usize = 10_000;
+
+// What is left of the limits on expanding an archive.
+struct Budget {
+    // The number of bytes that may still be decompressed.
+    bytes: u64,
+    // The number of files that may still be read.
+    entries: usize,
+}
+
+impl Budget {
+    fn new() -> Self {
+        Budget {
+            bytes: MAX_TOTAL_SIZE,
+            entries: MAX_ENTRIES,
+        }
+    }
+
+    // Counts a file read from an archive.
+    fn take_entry(&mut self) -> io::Result<()> {
+        if self.entries == 0 {
+            return Err(invalid_data("archive contains too many files"));
+        }
+
+        self.entries -= 1;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Compression {
+    Bzip2,
+    Gzip,
+    Xz,
+    Zstd,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Kind {
+    // A compressed file, and the name of the file it contains.
+    Compressed(Compression, String),
+    Tar,
+    Zip,
+}
+
+/// Returns true if the file at `path` is a compressed file or an archive, based on its extension.
+pub fn is_archive(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(kind)
+        .is_some()
+}
+
+/// Returns the files in the compressed file or archive at `path`, whose contents are `bytes`.
+/// Compressed files and archives inside it are expanded as well.
+pub fn entries(path: &Path, bytes: Vec<u8>) -> io::Result<Vec<Entry>> {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("");
+    let mut entries = vec![];
+
+    expand(name, None, bytes, 0, &mut Budget::new(), &mut entries)?;
+
+    Ok(entries)
+}
+
+fn expand(
+    name: &str,
+    path: Option<String>,
+    bytes: Vec<u8>,
+    depth: usize,
+    budget: &mut Budget,
+    entries: &mut Vec<Entry>,
+) -> io::Result<()> {
+    let kind = if depth < MAX_DEPTH { kind(name) } else { None };
+
+    match kind {
+        None => entries.push(Entry {
+            path,
+            name: PathBuf::from(name),
+            bytes,
+        }),
+        Some(Kind::Compressed(compression, inner)) => {
+            let bytes = decompress(compression, &bytes, budget)?;
+            expand(&inner, path, bytes, depth + 1, budget, entries)?;
+        }
+        Some(Kind::Tar) => {
+            let mut archive = tar::Archive::new(&bytes[..]);
+
+            for entry in archive.entries()? {
+                let entry = entry?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+
+                budget.take_entry()?;
+                let inner = entry.path()?.to_string_lossy().into_owned();
+                let bytes = read_limited(entry, budget)?;
+                let path = Some(join(&path, &inner));
+                expand(&inner, path, bytes, depth + 1, budget, entries)?;
+            }
+        }
+        Some(Kind::Zip) => {
+            let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+
+            for i in 0..archive.len() {
+                let file = archive.by_index(i)?;
+                if !file.is_file() {
+                    continue;
+                }
+
+                budget.take_entry()?;
+                let inner = file.name().to_string();
+                let bytes = read_limited(file, budget)?;
+                let path = Some(join(&path, &inner));
+                expand(&inner, path, bytes, depth + 1, budget, entries)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Returns the kind of archive of the file `name`, if any.
+fn kind(name: &str) -> Option<Kind> {
+    let lower = name.to_ascii_lowercase();
+    let stem = |ext: &str| name[..name.len() - ext.len()].to_string();
+
+    let compressed = [
+        (".gz", Compression::Gzip, ""),
+        (".tgz", Compression::Gzip, ".tar"),
+        (".xz", Compression::Xz, ""),
+        (".txz", Compression::Xz, ".tar"),
+        (".bz2", Compression::Bzip2, ""),
+        (".tbz", Compression::Bzip2, ".tar"),
+        (".tbz2", Compression::Bzip2, ".tar"),
+        (".zst", Compression::Zstd, ""),
+        (".tzst", Compression::Zstd, ".tar"),
+    ];
+
+    for (ext, compression, inner_ext) in compressed.iter() {
+        if lower.ends_with(ext) {
+            return Some(Kind::Compressed(*compression, stem(ext) + inner_ext));
+        }
+    }
+
+    if lower.ends_with(".tar") {
+        Some(Kind::Tar)
+    } else if lower.ends_with(".zip") {
+        Some(Kind::Zip)
+    } else {
+        None
+    }
+}
+
+fn decompress(compression: Compression, bytes: &[u8], budget: &mut Budget) -> io::Result<Vec<u8>> {
+    match compression {
+        Compression::Bzip2 => read_limited(bzip2::read::MultiBzDecoder::new(bytes), budget),
+        Compression::Gzip => read_limited(flate2::read::MultiGzDecoder::new(bytes), budget),
+        Compression::Xz => read_limited(xz2::read::XzDecoder::new_multi_decoder(bytes), budget),
+        Compression::Zstd => read_limited(zstd::Decoder::new(bytes)?, budget),
+    }
+}
+
+// Reads a decompressed file, taking its size from `budget`.
+fn read_limited<R: Read>(reader: R, budget: &mut Budget) -> io::Result<Vec<u8>> {
+    let limit = MAX_SIZE.min(budget.bytes);
+    let mut bytes = vec![];
+    reader.take(limit + 1).read_to_end(&mut bytes)?;
+
+    let len = bytes.len() as u64;
+    if len > MAX_SIZE {
+        return Err(invalid_data("decompressed file is too large"));
+    }
+    if len > budget.bytes {
+        return Err(invalid_data("decompressed archive is too large"));
+    }
+
+    budget.bytes -= len;
+    Ok(bytes)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn join(path: &Option<String>, inner: &str) -> String {
+    match path {
+        Some(path) => format!("{}!/{}", path, inner),
+        None => inner.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::io::Write;
+
+    fn compress(compression: Compression, bytes: &[u8]) -> Vec<u8> {
+        match compression {
+            Compression::Bzip2 => {
+                let mut encoder =
+                    bzip2::write::BzEncoder::new(vec![], bzip2::Compression::default());
+                encoder.write_all(bytes).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+                encoder.write_all(bytes).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Xz => {
+                let mut encoder = xz2::write::XzEncoder::new(vec![], 6);
+                encoder.write_all(bytes).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Zstd => zstd::encode_all(bytes, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn archive_kinds() {
+        assert_eq!(
+            kind("Notes.md.GZ"),
+            Some(Kind::Compressed(Compression::Gzip, "Notes.md".to_string()))
+        );
+        assert_eq!(
+            kind("docs.tgz"),
+            Some(Kind::Compressed(Compression::Gzip, "docs.tar".to_string()))
+        );
+        assert_eq!(kind("docs.tar"), Some(Kind::Tar));
+        assert_eq!(kind("docs.zip"), Some(Kind::Zip));
+        assert_eq!(kind("docs.md"), None);
+        assert!(is_archive(Path::new("dir/release.txt.zst")));
+        assert!(!is_archive(Path::new("dir.zip/release.txt")));
+    }
+
+    #[test]
+    fn decompress_files() {
+        let text = b"See https://example.com/.\n";
+
+        for (ext, compression) in &[
+            ("bz2", Compression::Bzip2),
+            ("gz", Compression::Gzip),
+            ("xz", Compression::Xz),
+            ("zst", Compression::Zstd),
+        ] {
+            let path = PathBuf::from(format!("notes.txt.{}", ext));
+
+            assert_eq!(
+                entries(&path, compress(*compression, text)).unwrap(),
+                vec![Entry {
+                    path: None,
+                    name: PathBuf::from("notes.txt"),
+                    bytes: text.to_vec(),
+                }]
+            );
+        }
+
+        assert!(entries(Path::new("notes.txt.gz"), text.to_vec()).is_err());
+    }
+
+    #[test]
+    fn budget() {
+        let mut builder = tar::Builder::new(vec![]);
+        for name in &["a.md", "b.md", "c.md"] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(100);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, &[b'x'; 100][..])
+                .unwrap();
+        }
+        let tar = builder.into_inner().unwrap();
+
+        let expand_with = |bytes: u64, entries: usize| {
+            let mut budget = Budget { bytes, entries };
+            expand("docs.tar", None, tar.clone(), 0, &mut budget, &mut vec![])
+        };
+
+        assert!(expand_with(300, 3).is_ok());
+        assert_eq!(
+            expand_with(300, 2).unwrap_err().to_string(),
+            "archive contains too many files"
+        );
+        assert_eq!(
+            expand_with(299, 3).unwrap_err().to_string(),
+            "decompressed archive is too large"
+        );
+
+        // Bytes decompressed from a compressed archive count too.
+        let mut budget = Budget {
+            bytes: 1000,
+            entries: 3,
+        };
+        let compressed = compress(Compression::Gzip, &tar);
+        assert!(expand("docs.tar.gz", None, compressed, 0, &mut budget, &mut vec![]).is_err());
+    }
+}
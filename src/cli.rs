@@ -3,8 +3,12 @@
 use clap::{
     clap_app, crate_authors, crate_description, crate_version, AppSettings, Arg, ArgMatches, Values,
 };
-use loch::Config;
-use std::str::FromStr;
+use encoding_rs::Encoding;
+use loch::{
+    Auth, Config, HostConfig, IpVersion, Language, MethodStrategy, Placeholder, RedirectPolicy,
+    Resolve, Scheme, StatusCodes, Timeouts, TlsVersion,
+};
+use std::{env, path::PathBuf, str::FromStr};
 
 // Split an input string by valid delimiters (spaces and commas).
 fn split_input(input: Values) -> Vec<String> {
@@ -21,6 +25,53 @@ fn split_input(input: Values) -> Vec<String> {
         })
 }
 
+// Split a host setting, given as HOST=VALUE.
+fn split_host(setting: &str) -> Result<(&str, &str), String> {
+    match setting.find('=') {
+        Some(i) if i > 0 => Ok((&setting[..i], &setting[i + 1..])),
+        _ => Err(format!("'{}': expected HOST=VALUE", setting)),
+    }
+}
+
+// Split a header, given as NAME: VALUE.
+fn split_header(header: &str) -> Result<(&str, &str), String> {
+    match header.find(':') {
+        Some(i) if i > 0 => Ok((header[..i].trim(), header[i + 1..].trim())),
+        _ => Err(format!("'{}': expected NAME: VALUE", header)),
+    }
+}
+
+// Split status codes, given as CODES for all hosts or as HOST=CODES.
+fn split_status(setting: &str) -> Result<(Option<&str>, StatusCodes), String> {
+    let (host, codes) = match setting.find('=') {
+        Some(_) => split_host(setting).map(|(host, codes)| (Some(host), codes))?,
+        None => (None, setting),
+    };
+
+    Ok((host, StatusCodes::from_str(codes)?))
+}
+
+// Split timeouts, given as TIMEOUTS for all hosts or as HOST=TIMEOUTS.
+fn split_timeouts(setting: &str) -> Result<(Option<&str>, Timeouts), String> {
+    let (host, timeouts) = match setting.find('=') {
+        Some(_) => split_host(setting).map(|(host, timeouts)| (Some(host), timeouts))?,
+        None => (None, setting),
+    };
+
+    Ok((host, Timeouts::from_str(timeouts)?))
+}
+
+// Returns the settings of `host` in `hosts`, adding them if needed.
+fn host_entry<'h>(hosts: &'h mut Vec<HostConfig>, host: &str) -> &'h mut HostConfig {
+    match hosts.iter().position(|entry| entry.host == host) {
+        Some(i) => &mut hosts[i],
+        None => {
+            hosts.push(HostConfig::new(host));
+            hosts.last_mut().unwrap()
+        }
+    }
+}
+
 pub struct Cli<'a> {
     matches: ArgMatches<'a>,
 }
@@ -32,12 +83,21 @@ impl<'a> Cli<'a> {
             (author: crate_authors!())
             (about: crate_description!())
 
+            (@arg check_intra_doc_links: --("check-intra-doc-links")
+                "Validate intra-doc links in Rust doc comments, such as [`Config`], against the \
+                 items declared in the Rust files being checked. Implies --comments-only for Rust")
+            (@arg dry_run: --("dry-run") requires[fix]
+                "With --fix, print the changes as a diff instead of writing them")
             (@arg exclude_paths: -e --("exclude-paths") [PATHS] +takes_value ...
                 "File or directory paths not to check. \
                  Example: --exclude-paths README.md *.rs")
             (@arg exclude_urls: -E --("exclude-urls") [URLS] +takes_value ...
                 "URL patterns not to check. The '*' wild card can be used with single quotes. \
                  Example: --exclude-urls sub.example.com '*.org' '*.test.com' example.com/page")
+            (@arg fix: --fix
+                "Rewrite links in place: links that moved permanently (301 or 308) are replaced \
+                 with the URL they lead to, and http:// links with https:// if that works too. \
+                 Only the links themselves are changed")
             (@arg follow: -L --follow
                 "Follow symbolic links")
             (@arg no_check: --("no-check")
@@ -53,12 +113,241 @@ impl<'a> Cli<'a> {
                  The --exclude-paths option can be used in conjunction with --no-ignore, e.g. to \
                  reapply filtering for hidden files and directories. \
                  Example: --no-ignore --exclude-paths .*")
+            (@arg notebook_code_comments: --("notebook-code-comments")
+                "Also search comments in the code cells of Jupyter notebooks. Only Markdown cells \
+                 are searched by default")
+            (@arg search_archives: --("search-archives")
+                "Search inside compressed files (.gz, .xz, .bz2, .zst) and archives (.zip, .tar, \
+                 .tar.gz, ...). URLs are reported with the path inside the archive, e.g. \
+                 docs.zip!/docs/intro.md:12. Requires the archive feature")
+            (@arg soft_404: --("soft-404")
+                "Report pages that are answered with a good status code but look missing as \
+                 suspected soft 404s: pages titled e.g. 'Page not found', and pages that are the \
+                 same as the page a host serves for a path that doesn't exist")
+            (@arg structured_data: --("structured-data")
+                "Only search the string values of JSON, YAML and TOML files, and show the key path \
+                 of each URL, e.g. package.repository")
             (@arg verbose: -v --verbose
                 "Wordy, prolix, long-winded")
 
             (@arg input: ...
                 "The input files and/or directories to be checked")
         )
+        .arg(
+            Arg::from_usage(
+                "--comments-only=[LANGS] 'Only search source files in these languages for URLs in \
+                 comments and doc comments. Searches comments for all supported languages if no \
+                 languages are given. Supported: c, go, js, python, rust, shell, toml, yaml. \
+                 Example: --comments-only=rust,python'",
+            )
+            .min_values(0)
+            .use_delimiter(true)
+            .require_equals(true)
+            .validator(|v| Language::from_str(&v).map(|_| ())),
+        )
+        .arg(
+            Arg::from_usage(
+                "--check-placeholders=[KINDS] 'Check placeholder URLs of these kinds instead of \
+                 skipping them. Checks all of them if no kinds are given. Supported: template \
+                 (e.g. https://{{ host }}/ or https://${DOMAIN}/), local (localhost and private \
+                 IP addresses), example (example.com and other reserved domains). \
+                 Example: --check-placeholders=local'",
+            )
+            .min_values(0)
+            .use_delimiter(true)
+            .require_equals(true)
+            .validator(|v| Placeholder::from_str(&v).map(|_| ())),
+        )
+        .arg(
+            Arg::from_usage(
+                "--accept-status [CODES] 'Count these status codes as good, in addition to 200 to \
+                 399. Given as CODES for all hosts, or HOST=CODES for one host. Can be repeated. \
+                 Example: --accept-status *.linkedin.com=403,999'",
+            )
+            .multiple(true)
+            .number_of_values(1)
+            .validator(|v| split_status(&v).map(|_| ())),
+        )
+        .arg(
+            Arg::from_usage(
+                "--basic-auth [HOST=USER:VAR] 'Use basic authentication for a host, with the \
+                 password in an environment variable. Can be repeated. \
+                 Example: --basic-auth wiki.corp.com=alice:WIKI_PASSWORD'",
+            )
+            .multiple(true)
+            .number_of_values(1)
+            .validator(|v| match split_host(&v)?.1.find(':') {
+                Some(i) if i > 0 => Ok(()),
+                _ => Err(format!("'{}': expected HOST=USER:VAR", v)),
+            }),
+        )
+        .arg(
+            Arg::from_usage(
+                "--bearer-token [HOST=VAR] 'Send a bearer token to a host, read from an \
+                 environment variable. Can be repeated. \
+                 Example: --bearer-token api.github.com=GITHUB_TOKEN'",
+            )
+            .multiple(true)
+            .number_of_values(1)
+            .validator(|v| split_host(&v).map(|_| ())),
+        )
+        .arg(Arg::from_usage(
+            "--ca-bundle [FILE] 'Verify TLS certificates with this bundle of CA certificates, \
+             e.g. one including the root CA of an intercepting proxy. Default: the system bundle'",
+        ))
+        .arg(Arg::from_usage(
+            "--client-cert [FILE] 'Authenticate with this client certificate, in PEM format'",
+        ))
+        .arg(Arg::from_usage(
+            "--client-key [FILE] 'The private key of the client certificate, if it isn't in the \
+             certificate file'",
+        ))
+        .arg(
+            Arg::from_usage(
+                "--cookie-jar [HOST=FILE] 'Send cookies to a host from a cookie jar in the \
+                 Netscape format, as written by curl. Can be repeated. \
+                 Example: --cookie-jar wiki.corp.com=cookies.txt'",
+            )
+            .multiple(true)
+            .number_of_values(1)
+            .validator(|v| split_host(&v).map(|_| ())),
+        )
+        .arg(
+            Arg::from_usage(
+                "--encoding [ENCODING] 'Read all files with this text encoding instead of \
+                 detecting it. Files are detected as UTF-8, UTF-16 or Latin-1 by default. \
+                 Example: --encoding utf-16le'",
+            )
+            .validator(|v| match Encoding::for_label(v.trim().as_bytes()) {
+                Some(_) => Ok(()),
+                None => Err(format!("'{}': unknown text encoding", v)),
+            }),
+        )
+        .arg(
+            Arg::from_usage(
+                "-H --header [HOST=HEADER] 'Send a header to a host. ${VAR} in the header is \
+                 replaced by the environment variable VAR. A host starting with *. matches its \
+                 subdomains, and the same goes for the other host options. Can be repeated. \
+                 Example: --header \"*.corp.com=X-Token: ${CORP_TOKEN}\"'",
+            )
+            .multiple(true)
+            .number_of_values(1)
+            .validator(|v| split_header(split_host(&v)?.1).map(|_| ())),
+        )
+        .arg(Arg::from_usage(
+            "--hosts-file [FILE] 'Connect to the addresses of host names in a file in the format \
+             of /etc/hosts instead of resolving the names. Overridden by --resolve'",
+        ))
+        .arg(
+            Arg::from_usage(
+                "--insecure [HOST] 'Don't verify the TLS certificates of a host, e.g. one with a \
+                 self-signed certificate. Can be repeated. Example: --insecure dev.corp.com'",
+            )
+            .multiple(true)
+            .number_of_values(1),
+        )
+        .arg(
+            Arg::from_usage(
+                "--ip-version [VERSION] 'Only connect over this IP version: 4 or 6. Both are used \
+                 by default'",
+            )
+            .validator(|v| IpVersion::from_str(&v).map(|_| ())),
+        )
+        .arg(
+            Arg::from_usage(
+                "--max-body-size [BYTES] 'Stop downloading response bodies larger than this. \
+                 Bodies are only read to detect soft 404s. Default: 1048576'",
+            )
+            .validator(|v| {
+                usize::from_str(&v)
+                    .map(|_| ())
+                    .map_err(|e| format!("'{}': {}", v, e))
+            }),
+        )
+        .arg(
+            Arg::from_usage(
+                "--max-redirects [NUM] 'Report URLs that redirect more than this many times as \
+                 bad. Default: 10'",
+            )
+            .validator(|v| {
+                usize::from_str(&v)
+                    .map(|_| ())
+                    .map_err(|e| format!("'{}': {}", v, e))
+            }),
+        )
+        .arg(
+            Arg::from_usage(
+                "--method [STRATEGY] 'The HTTP methods to request URLs with: head-then-get (send \
+                 GET only if HEAD fails), head or get. GET requests only ask for the first byte \
+                 of the body, unless detecting soft 404s. Default: head-then-get'",
+            )
+            .validator(|v| MethodStrategy::from_str(&v).map(|_| ())),
+        )
+        .arg(
+            Arg::from_usage(
+                "--min-tls [VERSION] 'The minimum TLS version to connect with: 1.0, 1.1, 1.2 or \
+                 1.3'",
+            )
+            .validator(|v| TlsVersion::from_str(&v).map(|_| ())),
+        )
+        .arg(
+            Arg::from_usage(
+                "--netrc=[FILE] 'Read credentials for hosts from a netrc file, ~/.netrc if no \
+                 file is given. Each host is only sent its own credentials'",
+            )
+            .min_values(0)
+            .require_equals(true),
+        )
+        .arg(
+            Arg::from_usage(
+                "--no-proxy [HOSTS] 'Connect to these hosts directly rather than through the \
+                 proxy. A leading dot matches subdomains only, and * matches all hosts. \
+                 Example: --no-proxy localhost,.corp.com'",
+            )
+            .use_delimiter(true),
+        )
+        .arg(Arg::from_usage(
+            "--proxy [URL] 'Connect through this proxy. Default: the http_proxy and https_proxy \
+             environment variables. Example: --proxy http://proxy.corp.com:3128'",
+        ))
+        .arg(
+            Arg::from_usage(
+                "--redirects [POLICY] 'What to do when a URL redirects: follow (and warn about \
+                 permanent 301 and 308 redirects), warn (about all redirects) or forbid (report \
+                 the URL as bad). Default: follow'",
+            )
+            .validator(|v| RedirectPolicy::from_str(&v).map(|_| ())),
+        )
+        .arg(
+            Arg::from_usage(
+                "--resolve [HOST:PORT:ADDR] 'Connect to these addresses for a host name and port \
+                 instead of resolving the name, as with curl. Can be repeated. \
+                 Example: --resolve docs.example.com:443:10.0.0.5'",
+            )
+            .multiple(true)
+            .number_of_values(1)
+            .validator(|v| Resolve::from_str(&v).map(|_| ())),
+        )
+        .arg(
+            Arg::from_usage(
+                "--schemes=[SCHEMES] 'Also find URLs with these schemes, and validate them offline \
+                 instead of checking them. Finds all of them if no schemes are given. Supported: \
+                 mailto, tel, data. Example: --schemes=mailto,tel'",
+            )
+            .min_values(0)
+            .use_delimiter(true)
+            .require_equals(true)
+            .validator(|v| Scheme::from_str(&v).map(|_| ())),
+        )
+        .arg(
+            Arg::from_usage(
+                "--soft-404-pattern [REGEX] 'Also report pages matching this case-insensitive \
+                 regular expression as suspected soft 404s. Implies --soft-404. Can be repeated. \
+                 Example: --soft-404-pattern \"we couldn.t find that page\"'",
+            )
+            .multiple(true)
+            .number_of_values(1),
+        )
         .arg(
             Arg::from_usage(
                 "-t --timeout [SECS] 'Set the timeout for requests, in seconds. Not set by \
@@ -70,6 +359,27 @@ impl<'a> Cli<'a> {
                     .map_err(|e| format!("'{}': {}", v, e))
             }),
         )
+        .arg(
+            Arg::from_usage(
+                "--timeouts [TIMEOUTS] 'Set the timeouts of the phases of requests, in \
+                 milliseconds: dns, connect, first-byte and total. Given as TIMEOUTS for all \
+                 hosts, or HOST=TIMEOUTS for one host. Can be repeated. \
+                 Example: --timeouts connect:500,total:5000'",
+            )
+            .multiple(true)
+            .number_of_values(1)
+            .validator(|v| split_timeouts(&v).map(|_| ())),
+        )
+        .arg(
+            Arg::from_usage(
+                "--warn-status [CODES] 'Report these status codes as warnings rather than as bad. \
+                 Given as CODES for all hosts, or HOST=CODES for one host. Can be repeated. \
+                 Example: --warn-status 429,503'",
+            )
+            .multiple(true)
+            .number_of_values(1)
+            .validator(|v| split_status(&v).map(|_| ())),
+        )
         .global_setting(AppSettings::ColoredHelp)
         .get_matches();
 
@@ -85,10 +395,124 @@ impl<'a> Cli<'a> {
         }
     }
 
+    // Returns the settings of the hosts given with --header, --bearer-token, --basic-auth,
+    // --cookie-jar, --insecure, and --accept-status, --warn-status and --timeouts with a host.
+    fn hosts(&self) -> Vec<HostConfig> {
+        let mut hosts = vec![];
+        let values = |name| self.matches.values_of(name).into_iter().flatten();
+
+        // Safe unwraps: the values were validated.
+        for setting in values("header") {
+            let (host, header) = split_host(setting).unwrap();
+            let (name, value) = split_header(header).unwrap();
+            host_entry(&mut hosts, host)
+                .headers
+                .push((name.to_string(), value.to_string()));
+        }
+        for setting in values("bearer-token") {
+            let (host, token_env) = split_host(setting).unwrap();
+            host_entry(&mut hosts, host).auth = Some(Auth::Bearer {
+                token_env: token_env.to_string(),
+            });
+        }
+        for setting in values("basic-auth") {
+            let (host, credentials) = split_host(setting).unwrap();
+            let i = credentials.find(':').unwrap();
+            host_entry(&mut hosts, host).auth = Some(Auth::Basic {
+                username: credentials[..i].to_string(),
+                password_env: credentials[i + 1..].to_string(),
+            });
+        }
+        for setting in values("cookie-jar") {
+            let (host, path) = split_host(setting).unwrap();
+            host_entry(&mut hosts, host).cookie_jar = Some(PathBuf::from(path));
+        }
+        for host in values("insecure") {
+            host_entry(&mut hosts, host).insecure = true;
+        }
+        for setting in values("accept-status") {
+            if let (Some(host), codes) = split_status(setting).unwrap() {
+                host_entry(&mut hosts, host).accept_status = codes;
+            }
+        }
+        for setting in values("warn-status") {
+            if let (Some(host), codes) = split_status(setting).unwrap() {
+                host_entry(&mut hosts, host).warn_status = codes;
+            }
+        }
+        for setting in values("timeouts") {
+            if let (Some(host), timeouts) = split_timeouts(setting).unwrap() {
+                let entry = host_entry(&mut hosts, host);
+                entry.timeouts = timeouts.or(entry.timeouts);
+            }
+        }
+
+        hosts
+    }
+
+    // Returns the status codes given for all hosts with `option`, either --accept-status or
+    // --warn-status.
+    fn status_codes(&self, option: &str) -> StatusCodes {
+        let codes = self
+            .matches
+            .values_of(option)
+            .into_iter()
+            .flatten()
+            .filter_map(|setting| match split_status(setting).unwrap() {
+                (None, codes) => Some(codes.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        StatusCodes::from_str(&codes.join(",")).unwrap()
+    }
+
+    // Returns the timeouts given for all hosts with --timeouts. Later timeouts of a phase override
+    // earlier ones.
+    fn timeouts(&self) -> Timeouts {
+        self.matches
+            .values_of("timeouts")
+            .into_iter()
+            .flatten()
+            .fold(Timeouts::default(), |all, setting| {
+                match split_timeouts(setting).unwrap() {
+                    (None, timeouts) => timeouts.or(all),
+                    _ => all,
+                }
+            })
+    }
+
     pub fn to_config(&self) -> Config {
         Config {
+            accept_status: self.status_codes("accept-status"),
             // Not for interactive use. Verbose already displays all URLs.
             all_urls: false,
+            ca_bundle: self.matches.value_of("ca-bundle").map(PathBuf::from),
+            check_intra_doc_links: self.matches.is_present("check_intra_doc_links"),
+            check_placeholders: if self.matches.is_present("check-placeholders") {
+                match self.matches.values_of("check-placeholders") {
+                    Some(values) if values.len() > 0 => {
+                        values.map(|s| Placeholder::from_str(s).unwrap()).collect()
+                    }
+                    _ => Placeholder::ALL.to_vec(),
+                }
+            } else {
+                vec![]
+            },
+            client_cert: self.matches.value_of("client-cert").map(PathBuf::from),
+            client_key: self.matches.value_of("client-key").map(PathBuf::from),
+            comments_only: if self.matches.is_present("comments-only") {
+                match self.matches.values_of("comments-only") {
+                    Some(values) if values.len() > 0 => {
+                        values.map(|s| Language::from_str(s).unwrap()).collect()
+                    }
+                    _ => Language::ALL.to_vec(),
+                }
+            } else {
+                vec![]
+            },
+            dry_run: self.matches.is_present("dry_run"),
+            encoding: self.matches.value_of("encoding").map(|s| s.to_string()),
             exclude_paths: match self.matches.values_of("exclude_paths") {
                 Some(values) => values.map(|s| s.to_string()).collect(),
                 None => vec![],
@@ -97,20 +521,88 @@ impl<'a> Cli<'a> {
                 Some(values) => split_input(values),
                 None => vec![],
             },
+            fix: self.matches.is_present("fix"),
             follow: self.matches.is_present("follow"),
+            hosts: self.hosts(),
+            hosts_file: self.matches.value_of("hosts-file").map(PathBuf::from),
+            ip_version: self
+                .matches
+                .value_of("ip-version")
+                .map(|version| IpVersion::from_str(version).unwrap()),
             // Not for interactive use. Verbose already displays all files.
             list_files: false,
+            max_body_size: self
+                .matches
+                .value_of("max-body-size")
+                .map(|size| usize::from_str(size).unwrap()),
+            max_redirects: self
+                .matches
+                .value_of("max-redirects")
+                .map(|num| usize::from_str(num).unwrap()),
+            method: self
+                .matches
+                .value_of("method")
+                .map_or(Default::default(), |method| {
+                    MethodStrategy::from_str(method).unwrap()
+                }),
+            min_tls_version: self
+                .matches
+                .value_of("min-tls")
+                .map(|version| TlsVersion::from_str(version).unwrap()),
+            netrc: if self.matches.is_present("netrc") {
+                match self.matches.value_of("netrc") {
+                    Some(path) => Some(PathBuf::from(path)),
+                    None => env::var_os("HOME").map(|home| PathBuf::from(home).join(".netrc")),
+                }
+            } else {
+                None
+            },
             no_check: self.matches.is_present("no_check"),
             no_color: self.matches.is_present("no_color"),
             no_ignore: self.matches.is_present("no_ignore"),
             no_http: self.matches.is_present("no_http"),
+            no_proxy: match self.matches.values_of("no-proxy") {
+                Some(values) => values.map(|s| s.to_string()).collect(),
+                None => vec![],
+            },
+            notebook_code_comments: self.matches.is_present("notebook_code_comments"),
+            proxy: self.matches.value_of("proxy").map(|s| s.to_string()),
+            redirects: self
+                .matches
+                .value_of("redirects")
+                .map_or(Default::default(), |policy| {
+                    RedirectPolicy::from_str(policy).unwrap()
+                }),
+            resolve: match self.matches.values_of("resolve") {
+                Some(values) => values.map(|s| Resolve::from_str(s).unwrap()).collect(),
+                None => vec![],
+            },
+            schemes: if self.matches.is_present("schemes") {
+                match self.matches.values_of("schemes") {
+                    Some(values) if values.len() > 0 => {
+                        values.map(|s| Scheme::from_str(s).unwrap()).collect()
+                    }
+                    _ => Scheme::ALL.to_vec(),
+                }
+            } else {
+                vec![]
+            },
+            search_archives: self.matches.is_present("search_archives"),
             // Not for interactive use. Output can be sent to /dev/null if undesired.
             silent: false,
+            soft_404: self.matches.is_present("soft_404"),
+            soft_404_patterns: match self.matches.values_of("soft-404-pattern") {
+                Some(values) => values.map(|s| s.to_string()).collect(),
+                None => vec![],
+            },
+            structured_data: self.matches.is_present("structured_data"),
             timeout: self
                 .matches
                 .value_of("timeout")
                 .map(|time| u64::from_str(time).unwrap()),
+            timeouts: self.timeouts(),
             verbose: self.matches.is_present("verbose"),
+            warn_status: self.status_codes("warn-status"),
         }
     }
 }
@@ -1,22 +1,76 @@
 //! Config struct.
 
+use crate::{
+    HostConfig, IpVersion, Language, MethodStrategy, Placeholder, RedirectPolicy, Resolve, Scheme,
+    StatusCodes, Timeouts, TlsVersion,
+};
+use std::path::{Path, PathBuf};
+
 /// Struct containing configuration parameters for loch.
 #[derive(Default)]
 pub struct Config {
+    /// Status codes to count as good for all URLs, in addition to 200 to 399. Status codes for
+    /// particular hosts can be set with `HostConfig::accept_status`.
+    pub accept_status: StatusCodes,
     // TODO: implement and test.
     /// Return all `FileUrl`s, including the ones that resolved successfully.
     pub all_urls: bool,
+    /// A bundle of CA certificates to verify TLS certificates with, e.g. including the private root
+    /// CA of an intercepting proxy. The system's bundle is used if not set.
+    pub ca_bundle: Option<PathBuf>,
+    /// Validate intra-doc links in Rust doc comments against the items declared in the Rust files
+    /// being checked. Unresolved links are reported as bad. Implies `comments_only` for Rust.
+    pub check_intra_doc_links: bool,
+    /// Kinds of placeholder URLs, such as templated URLs and URLs of local hosts, to check rather
+    /// than skip. All placeholder URLs are skipped by default.
+    pub check_placeholders: Vec<Placeholder>,
+    /// A client certificate to authenticate with, in PEM format.
+    pub client_cert: Option<PathBuf>,
+    /// The private key of the client certificate, if it isn't in the certificate file.
+    pub client_key: Option<PathBuf>,
+    /// Languages whose source files are only searched for URLs in comments and doc comments.
+    pub comments_only: Vec<Language>,
+    /// With `fix`, print the changes as a diff instead of writing them. Implies `fix`.
+    pub dry_run: bool,
+    /// The text encoding of all files, e.g. `utf-16le` or `latin1`. Detected for each file if not
+    /// set.
+    pub encoding: Option<String>,
     // TODO: test.
     /// A list of file patterns to exclude.
     pub exclude_paths: Vec<String>,
     // TODO: implement and test
     /// A list of URL patterns to exclude.
     pub exclude_urls: Vec<String>,
+    /// Rewrite URLs that have a replacement in the files they were found in: URLs that moved
+    /// permanently, and `http://` URLs whose `https://` equivalent is good too.
+    pub fix: bool,
     // TODO: test.
     /// Follow symbolic links.
     pub follow: bool,
+    /// Request settings for particular hosts, such as headers and credentials, which are only sent
+    /// to those hosts.
+    pub hosts: Vec<HostConfig>,
+    /// A file mapping host names to addresses in the format of `/etc/hosts`, to connect to
+    /// instead of resolving the names, for all ports. Overridden by `resolve`.
+    pub hosts_file: Option<PathBuf>,
+    /// Only connect over this IP version, e.g. to check that a site works over IPv6. Both are
+    /// used by default.
+    pub ip_version: Option<IpVersion>,
     /// List all files visited, populating them into the `Info` struct.
     pub list_files: bool,
+    /// The largest response body to download, in bytes. Bodies are only kept to detect soft 404s,
+    /// and the transfer is stopped when they get larger. 1 MiB if not set.
+    pub max_body_size: Option<usize>,
+    /// The number of redirects to follow before reporting a URL as bad. 10 if not set.
+    pub max_redirects: Option<usize>,
+    /// The HTTP methods to request URLs with. By default, a HEAD request is sent first, and a GET
+    /// request only if that fails. GET requests only ask for the first byte of the body with a
+    /// `Range` header, unless detecting soft 404s.
+    pub method: MethodStrategy,
+    /// The minimum version of TLS to connect with.
+    pub min_tls_version: Option<TlsVersion>,
+    /// A netrc file to read credentials from. Each host is only sent its own credentials.
+    pub netrc: Option<PathBuf>,
     /// Disable URL checking.
     pub no_check: bool,
     /// Disable color output.
@@ -26,21 +80,114 @@ pub struct Config {
     // TODO: test.
     /// Process files and directories that are ignored by default.
     pub no_ignore: bool,
+    /// Hosts to connect to directly rather than through the proxy. A leading dot matches
+    /// subdomains only, and `*` matches all hosts.
+    pub no_proxy: Vec<String>,
+    /// Also search comments in the code cells of Jupyter notebooks. Only Markdown cells are
+    /// searched by default.
+    pub notebook_code_comments: bool,
+    /// The proxy to connect through, e.g. `http://proxy.corp.com:3128`. If not set, curl uses the
+    /// `http_proxy`, `https_proxy` and `no_proxy` environment variables.
+    pub proxy: Option<String>,
+    /// What to do when a URL redirects. Redirects are followed by default, and permanent ones
+    /// reported as warnings with the URL they lead to.
+    pub redirects: RedirectPolicy,
+    /// Addresses to connect to for host names and ports instead of resolving them, e.g. to check
+    /// a site on a staging server before it goes live, as with curl's `--resolve`.
+    pub resolve: Vec<Resolve>,
+    /// URL schemes other than HTTP to find, such as `mailto:`. Their URLs are validated offline
+    /// instead of being checked.
+    pub schemes: Vec<Scheme>,
+    /// Search inside compressed files (gzip, xz, bzip2 and zstd) and archives (zip and tar),
+    /// reporting the path of each file inside the archive. Requires the `archive` feature.
+    pub search_archives: bool,
     /// Silence standard, non-`verbose` output.
     pub silent: bool,
-    /// Timeout in seconds, if set.
+    /// Report pages that are answered with a good status code but look missing as suspected soft
+    /// 404s: pages titled e.g. "Page not found", and pages that are the same as the page a host
+    /// serves for a path that doesn't exist.
+    pub soft_404: bool,
+    /// Case-insensitive regular expressions matched against pages to detect soft 404s, e.g.
+    /// `We couldn't find that page`. Implies `soft_404`.
+    pub soft_404_patterns: Vec<String>,
+    /// Only search the string values of JSON, YAML and TOML files, recording the key path of each
+    /// URL found.
+    pub structured_data: bool,
+    /// Total timeout of each request in seconds, if set. Overridden by the total timeout of
+    /// `timeouts`.
     pub timeout: Option<u64>,
+    /// Timeouts of the DNS resolution, connection, first byte and whole of each request, with
+    /// millisecond precision. Timeouts for particular hosts can be set with
+    /// `HostConfig::timeouts`.
+    pub timeouts: Timeouts,
     /// Display more information, such as every file name and URL processed.
     pub verbose: bool,
+    /// Status codes to report as warnings rather than as bad for all URLs, e.g. 429 when rate
+    /// limited. Status codes for particular hosts can be set with `HostConfig::warn_status`.
+    pub warn_status: StatusCodes,
 }
 
 impl Config {
+    /// Set accept_status.
+    pub fn accept_status(mut self, codes: StatusCodes) -> Self {
+        self.accept_status = codes;
+        self
+    }
+
     /// Set all_urls=true.
     pub fn all_urls(mut self) -> Self {
         self.all_urls = true;
         self
     }
 
+    /// Set ca_bundle.
+    pub fn ca_bundle(mut self, ca_bundle: &Path) -> Self {
+        self.ca_bundle = Some(ca_bundle.to_owned());
+        self
+    }
+
+    /// Set check_intra_doc_links=true.
+    pub fn check_intra_doc_links(mut self) -> Self {
+        self.check_intra_doc_links = true;
+        self
+    }
+
+    /// Set check_placeholders.
+    pub fn check_placeholders(mut self, placeholders: &[Placeholder]) -> Self {
+        self.check_placeholders = placeholders.to_vec();
+        self
+    }
+
+    /// Set client_cert.
+    pub fn client_cert(mut self, client_cert: &Path) -> Self {
+        self.client_cert = Some(client_cert.to_owned());
+        self
+    }
+
+    /// Set client_key.
+    pub fn client_key(mut self, client_key: &Path) -> Self {
+        self.client_key = Some(client_key.to_owned());
+        self
+    }
+
+    /// Set comments_only.
+    pub fn comments_only(mut self, languages: &[Language]) -> Self {
+        self.comments_only = languages.to_vec();
+        self
+    }
+
+    /// Set dry_run=true.
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// Set encoding.
+    pub fn encoding(mut self, encoding: &str) -> Self {
+        self.encoding = Some(encoding.to_string());
+        self
+    }
+
     /// Set exclude_paths.
     pub fn exclude_paths(mut self, exclude_paths: &[&str]) -> Self {
         self.exclude_paths = exclude_paths.iter().map(|s| s.to_string()).collect();
@@ -53,18 +200,72 @@ impl Config {
         self
     }
 
+    /// Set fix=true.
+    pub fn fix(mut self) -> Self {
+        self.fix = true;
+        self
+    }
+
     /// Set follow=true.
     pub fn follow(mut self) -> Self {
         self.follow = true;
         self
     }
 
+    /// Add the request settings of a host.
+    pub fn host(mut self, host: HostConfig) -> Self {
+        self.hosts.push(host);
+        self
+    }
+
+    /// Set hosts_file.
+    pub fn hosts_file(mut self, hosts_file: &Path) -> Self {
+        self.hosts_file = Some(hosts_file.to_owned());
+        self
+    }
+
+    /// Set ip_version.
+    pub fn ip_version(mut self, ip_version: IpVersion) -> Self {
+        self.ip_version = Some(ip_version);
+        self
+    }
+
     /// Set list_files=true.
     pub fn list_files(mut self) -> Self {
         self.list_files = true;
         self
     }
 
+    /// Set max_body_size.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = Some(max_body_size);
+        self
+    }
+
+    /// Set max_redirects.
+    pub fn max_redirects(mut self, max_redirects: usize) -> Self {
+        self.max_redirects = Some(max_redirects);
+        self
+    }
+
+    /// Set method.
+    pub fn method(mut self, method: MethodStrategy) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Set min_tls_version.
+    pub fn min_tls_version(mut self, min_tls_version: TlsVersion) -> Self {
+        self.min_tls_version = Some(min_tls_version);
+        self
+    }
+
+    /// Set netrc.
+    pub fn netrc(mut self, netrc: &Path) -> Self {
+        self.netrc = Some(netrc.to_owned());
+        self
+    }
+
     /// Set no_check=true.
     pub fn no_check(mut self) -> Self {
         self.no_check = true;
@@ -89,21 +290,93 @@ impl Config {
         self
     }
 
+    /// Set no_proxy.
+    pub fn no_proxy(mut self, no_proxy: &[&str]) -> Self {
+        self.no_proxy = no_proxy.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    /// Set notebook_code_comments=true.
+    pub fn notebook_code_comments(mut self) -> Self {
+        self.notebook_code_comments = true;
+        self
+    }
+
+    /// Set proxy.
+    pub fn proxy(mut self, proxy: &str) -> Self {
+        self.proxy = Some(proxy.to_string());
+        self
+    }
+
+    /// Set redirects.
+    pub fn redirects(mut self, redirects: RedirectPolicy) -> Self {
+        self.redirects = redirects;
+        self
+    }
+
+    /// Add the addresses to connect to for a host name and port.
+    pub fn resolve(mut self, resolve: Resolve) -> Self {
+        self.resolve.push(resolve);
+        self
+    }
+
+    /// Set schemes.
+    pub fn schemes(mut self, schemes: &[Scheme]) -> Self {
+        self.schemes = schemes.to_vec();
+        self
+    }
+
+    /// Set search_archives=true.
+    pub fn search_archives(mut self) -> Self {
+        self.search_archives = true;
+        self
+    }
+
     /// Set silent=true.
     pub fn silent(mut self) -> Self {
         self.silent = true;
         self
     }
 
+    /// Set soft_404=true.
+    pub fn soft_404(mut self) -> Self {
+        self.soft_404 = true;
+        self
+    }
+
+    /// Set soft_404_patterns.
+    pub fn soft_404_patterns(mut self, patterns: &[&str]) -> Self {
+        self.soft_404_patterns = patterns.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    /// Set structured_data=true.
+    pub fn structured_data(mut self) -> Self {
+        self.structured_data = true;
+        self
+    }
+
     /// Set timeout.
     pub fn timeout(mut self, timeout: u64) -> Self {
         self.timeout = Some(timeout);
         self
     }
 
+    /// Set timeouts.
+    pub fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
     /// Set verbose=true.
     pub fn verbose(mut self) -> Self {
         self.verbose = true;
         self
     }
+
+    /// Set warn_status.
+    pub fn warn_status(mut self, codes: StatusCodes) -> Self {
+        self.warn_status = codes;
+        self
+    }
 }
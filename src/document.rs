@@ -0,0 +1,90 @@
+//! Links in PDF and Office documents.
+
+use crate::{
+    encoding::Skipped,
+    get_exclusion,
+    parse::{self, LineIndex},
+    FileUrl, SearchOptions,
+};
+use std::{io, ops::Range, path::Path, result};
+
+#[cfg(feature = "office")]
+use crate::office;
+#[cfg(feature = "pdf")]
+use crate::pdf;
+
+/// A part of a document, such as a page or a paragraph.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Part {
+    /// The 1-based page or slide containing the part.
+    pub page: Option<usize>,
+    /// The 1-based paragraph, within the document or the slide.
+    pub paragraph: Option<usize>,
+    /// For spreadsheets, the sheet and cell, e.g. `Sheet1!B7`.
+    pub key_path: Option<String>,
+    /// The visible text of the part.
+    pub text: String,
+    /// Links attached to the part, such as hyperlinks and link annotations, with the byte range of
+    /// the linked text if it is known.
+    pub links: Vec<(String, Option<Range<usize>>)>,
+}
+
+/// Returns the parts of the document `bytes`, if `filepath` is a supported document type.
+pub fn parts(filepath: &Path, bytes: &[u8]) -> Option<io::Result<Vec<Part>>> {
+    let ext = filepath.extension()?.to_str()?.to_lowercase();
+
+    match ext.as_str() {
+        #[cfg(feature = "pdf")]
+        "pdf" => Some(pdf::parts(bytes)),
+        #[cfg(feature = "office")]
+        "docx" | "pptx" | "xlsx" => Some(office::parts(bytes, &ext)),
+        _ => None,
+    }
+}
+
+/// Gets the URLs in the document `bytes`, if `filepath` is a supported document type. URLs are
+/// found in the text of each part, and in its links. The `line` and `column` of links whose
+/// position is unknown, such as PDF link annotations, are 0.
+pub fn file_urls(
+    filepath: &Path,
+    bytes: &[u8],
+    options: &SearchOptions,
+) -> Option<result::Result<Vec<FileUrl>, Skipped>> {
+    let parts = match parts(filepath, bytes)? {
+        Ok(parts) => parts,
+        Err(_) => return Some(Err(Skipped::Undecodable)),
+    };
+
+    let mut file_urls = vec![];
+
+    for part in parts {
+        let lines = LineIndex::new(&part.text);
+        let mut part_urls = vec![];
+
+        let found = parse::find_urls(&part.text, options.no_http, false, options.schemes)
+            .into_iter()
+            .map(|(url, range)| (url, Some(range)));
+
+        for (url, range) in part.links.iter().cloned().chain(found) {
+            let (position, span) = match range {
+                Some(range) => (lines.position(range.start), range),
+                None => ((0, 0), 0..0),
+            };
+
+            part_urls.push(FileUrl {
+                page: part.page,
+                paragraph: part.paragraph,
+                key_path: part.key_path.clone(),
+                excluded: get_exclusion(&url, options),
+                ..FileUrl::new(&url, filepath, position, span)
+            });
+        }
+
+        // Hyperlinks often show their own URL.
+        part_urls.sort();
+        part_urls.dedup_by(|a, b| a.url == b.url && a.line == b.line && a.column == b.column);
+        file_urls.append(&mut part_urls);
+    }
+
+    Some(Ok(file_urls))
+}
@@ -0,0 +1,274 @@
+//! Text encoding detection.
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use lazy_static::lazy_static;
+use regex::bytes::Regex;
+use std::{result, str};
+
+// How many bytes to look at when sniffing for UTF-16 or an HTML meta charset.
+const SNIFF_LEN: usize = 1024;
+
+/// The reason a file was not decoded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Skipped {
+    /// The file appears to be binary.
+    Binary,
+    /// The file appears to be text, but could not be decoded.
+    Undecodable,
+}
+
+/// Decodes the contents of a file. If `encoding` is not given, it is detected from, in order, a
+/// byte order mark, the distribution of null bytes (UTF-16 without a BOM), UTF-8 validity and an
+/// HTML `<meta charset>`, falling back to Windows-1252 (a superset of Latin-1) if the file looks
+/// like text.
+pub fn decode(
+    bytes: &[u8],
+    encoding: Option<&'static Encoding>,
+) -> result::Result<String, Skipped> {
+    decode_detected(bytes, encoding).map(|decoded| decoded.text)
+}
+
+/// A decoded file, with the encoding it was decoded from.
+#[derive(Debug)]
+pub struct Decoded {
+    /// The text of the file.
+    pub text: String,
+    /// The encoding of the file.
+    pub encoding: &'static Encoding,
+    /// The length in bytes of the file's byte order mark, or 0 if it has none.
+    pub bom_len: usize,
+}
+
+/// Decodes the contents of a file like `decode`, also returning the encoding used.
+pub fn decode_detected(
+    bytes: &[u8],
+    encoding: Option<&'static Encoding>,
+) -> result::Result<Decoded, Skipped> {
+    if let Some(encoding) = encoding {
+        let (encoding, bom_len) = Encoding::for_bom(bytes)
+            .filter(|(bom, _)| *bom == encoding)
+            .unwrap_or((encoding, 0));
+        return decode_with(encoding, bytes, bom_len);
+    }
+
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        return decode_with(encoding, bytes, bom_len);
+    }
+
+    if let Some(encoding) = sniff_utf16(bytes) {
+        if let Ok(decoded) = decode_with(encoding, bytes, 0) {
+            return Ok(decoded);
+        }
+    }
+
+    if bytes.contains(&0) {
+        return Err(Skipped::Binary);
+    }
+
+    if let Ok(text) = str::from_utf8(bytes) {
+        return Ok(Decoded {
+            text: text.to_string(),
+            encoding: UTF_8,
+            bom_len: 0,
+        });
+    }
+
+    if let Some(encoding) = meta_charset(bytes) {
+        return decode_with(encoding, bytes, 0);
+    }
+
+    // Control characters are rare in Latin-1 text, but common in mis-detected files.
+    let controls = bytes.iter().filter(|&&b| is_control(b)).count();
+    if controls * 100 > bytes.len() {
+        return Err(Skipped::Undecodable);
+    }
+
+    decode_with(WINDOWS_1252, bytes, 0)
+}
+
+/// Encodes `text` with `encoding`, without a byte order mark. Returns `None` if the encoding
+/// can't represent some of its characters.
+pub fn encode(text: &str, encoding: &'static Encoding) -> Option<Vec<u8>> {
+    // UTF-16 is decode-only in `encoding_rs`.
+    if encoding == UTF_16LE {
+        return Some(text.encode_utf16().flat_map(u16::to_le_bytes).collect());
+    }
+    if encoding == UTF_16BE {
+        return Some(text.encode_utf16().flat_map(u16::to_be_bytes).collect());
+    }
+
+    let (bytes, _, unmappable) = encoding.encode(text);
+    Some(bytes.into_owned()).filter(|_| !unmappable)
+}
+
+/// Returns the encoding with the given label, e.g. `utf-16le` or `latin1`.
+pub fn for_label(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes())
+}
+
+// Decodes `bytes` after a byte order mark of `bom_len` bytes.
+fn decode_with(
+    encoding: &'static Encoding,
+    bytes: &[u8],
+    bom_len: usize,
+) -> result::Result<Decoded, Skipped> {
+    encoding
+        .decode_without_bom_handling_and_without_replacement(&bytes[bom_len..])
+        .map(|text| Decoded {
+            text: text.into_owned(),
+            encoding,
+            bom_len,
+        })
+        .ok_or(Skipped::Undecodable)
+}
+
+// Detects UTF-16 without a BOM, where mostly-ASCII text has a null byte in every other position.
+fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(SNIFF_LEN) & !1];
+    let pairs = sample.len() / 2;
+    if pairs == 0 {
+        return None;
+    }
+
+    let even = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+    let odd = sample
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .filter(|&&b| b == 0)
+        .count();
+
+    if odd * 10 >= pairs * 3 && even * 10 <= odd {
+        Some(UTF_16LE)
+    } else if even * 10 >= pairs * 3 && odd * 10 <= even {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+// Returns the encoding declared by an HTML `<meta>` tag near the start of the file.
+fn meta_charset(bytes: &[u8]) -> Option<&'static Encoding> {
+    lazy_static! {
+        static ref META: Regex =
+            Regex::new(r#"(?i-u)<meta\s[^>]*charset\s*=\s*["']?\s*([a-z0-9_:.-]+)"#).unwrap();
+    }
+
+    let sample = &bytes[..bytes.len().min(SNIFF_LEN)];
+    let label = META.captures(sample)?.get(1)?.as_bytes();
+
+    Encoding::for_label(label)
+}
+
+fn is_control(b: u8) -> bool {
+    match b {
+        b'\t' | b'\n' | b'\r' | 0x0c | 0x1b => false,
+        0x00..=0x1f | 0x7f => true,
+        // Undefined in Windows-1252.
+        0x81 | 0x8d | 0x8f | 0x90 | 0x9d => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(|c| c.to_le_bytes()).collect()
+    }
+
+    fn utf16be(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(|c| c.to_be_bytes()).collect()
+    }
+
+    #[test]
+    fn detect_encoding() {
+        let text = "See https://example.com/caf\u{e9}.";
+
+        assert_eq!(decode(text.as_bytes(), None), Ok(text.to_string()));
+
+        // Byte order marks.
+        let bytes = [&b"\xef\xbb\xbf"[..], text.as_bytes()].concat();
+        assert_eq!(decode(&bytes, None), Ok(text.to_string()));
+        let bytes = [&b"\xff\xfe"[..], &utf16le(text)].concat();
+        assert_eq!(decode(&bytes, None), Ok(text.to_string()));
+        let bytes = [&b"\xfe\xff"[..], &utf16be(text)].concat();
+        assert_eq!(decode(&bytes, None), Ok(text.to_string()));
+
+        // UTF-16 without a BOM.
+        assert_eq!(decode(&utf16le(text), None), Ok(text.to_string()));
+        assert_eq!(decode(&utf16be(text), None), Ok(text.to_string()));
+
+        // Latin-1.
+        assert_eq!(
+            decode(b"See https://example.com/caf\xe9.", None),
+            Ok(text.to_string())
+        );
+
+        // HTML meta charset.
+        let html = b"<html><head><meta http-equiv=\"Content-Type\" \
+                     content=\"text/html; charset=koi8-r\"></head>\xf0\xd2\xc9</html>";
+        assert_eq!(
+            decode(html, None),
+            Ok("<html><head><meta http-equiv=\"Content-Type\" \
+                content=\"text/html; charset=koi8-r\"></head>\u{41f}\u{440}\u{438}</html>"
+                .to_string())
+        );
+    }
+
+    #[test]
+    fn forced_encoding() {
+        let text = "https://example.com/\u{e9}";
+
+        let bytes = [&b"\xff\xfe"[..], &utf16le(text)].concat();
+        assert_eq!(decode(&bytes, for_label("utf-16le")), Ok(text.to_string()));
+        assert_eq!(
+            decode(b"https://example.com/\xe9", for_label("latin1")),
+            Ok(text.to_string())
+        );
+        assert_eq!(
+            decode(b"https://example.com/\xe9", for_label("utf-8")),
+            Err(Skipped::Undecodable)
+        );
+        assert_eq!(for_label("no-such-encoding"), None);
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = "See https://example.com/caf\u{e9}.\r\n";
+
+        for bytes in [
+            text.as_bytes().to_vec(),
+            [&b"\xef\xbb\xbf"[..], text.as_bytes()].concat(),
+            [&b"\xff\xfe"[..], &utf16le(text)].concat(),
+            utf16be(text),
+            b"See https://example.com/caf\xe9.\r\n".to_vec(),
+        ]
+        .iter()
+        {
+            let decoded = decode_detected(bytes, None).unwrap();
+            let encoded = encode(&decoded.text, decoded.encoding).unwrap();
+            assert_eq!(&bytes[decoded.bom_len..], &encoded[..]);
+        }
+
+        assert_eq!(encode("caf\u{e9} \u{41f}", WINDOWS_1252), None);
+    }
+
+    #[test]
+    fn skip_files() {
+        assert_eq!(
+            decode(b"\x7fELF\x02\x01\x01\x00\x00\x00", None),
+            Err(Skipped::Binary)
+        );
+        assert_eq!(
+            decode(b"\x01\x02\x03\x04\x05 https://example.com/\xe9", None),
+            Err(Skipped::Undecodable)
+        );
+        assert_eq!(
+            decode(b"\xef\xbb\xbfhttps://example.com/\xe9", None),
+            Err(Skipped::Undecodable)
+        );
+    }
+}
@@ -12,10 +12,20 @@ pub enum Error {
     Curl(curl::Error),
     /// An ignore error.
     Ignore(ignore::Error),
+    /// An unknown text encoding label.
+    InvalidEncoding(String),
+    /// A hosts file that can't be parsed, with the path and the reason.
+    InvalidHostsFile(String),
     /// An invalid URL exclusion pattern.
     InvalidPattern(String),
+    /// An invalid regular expression.
+    InvalidRegex(String),
     /// An io error.
     Io(io::Error),
+    /// An environment variable that a secret is read from is not set.
+    MissingEnvVar(String),
+    /// An option that requires a cargo feature this build doesn't have, with the feature.
+    MissingFeature(String),
 }
 
 impl fmt::Display for Error {
@@ -25,8 +35,13 @@ impl fmt::Display for Error {
         match *self {
             Curl(ref e) => write!(f, "{}", e),
             Ignore(ref e) => write!(f, "{}", e),
+            InvalidEncoding(ref label) => write!(f, "Unknown text encoding: {}", label),
+            InvalidHostsFile(ref reason) => write!(f, "Invalid hosts file: {}", reason),
             InvalidPattern(ref pattern) => write!(f, "Invalid URL exclusion pattern: {}", pattern),
+            InvalidRegex(ref regex) => write!(f, "Invalid regular expression: {}", regex),
             Io(ref e) => write!(f, "{}", e),
+            MissingEnvVar(ref name) => write!(f, "Environment variable not set: {}", name),
+            MissingFeature(ref reason) => write!(f, "Missing cargo feature: {}", reason),
         }
     }
 }
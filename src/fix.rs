@@ -0,0 +1,148 @@
+//! Rewriting URLs in the files they were found in, with their replacements.
+
+use crate::{encoding, error::Result, util, FileUrl, COLOR_INFO, COLOR_PARAM, COLOR_WARN};
+use encoding_rs::Encoding;
+use std::{collections::BTreeMap, fs, io::Write, path::Path};
+use termcolor::StandardStream;
+
+/// Rewrites the URLs in `file_urls` that have a replacement, in the files they were found in. Only
+/// the bytes of each URL change, so the encoding and line endings of files are kept. With
+/// `dry_run`, the changes are printed as a diff instead of written. Returns the number of URLs
+/// rewritten.
+pub fn fix_files(
+    file_urls: &[FileUrl],
+    encoding: Option<&'static Encoding>,
+    dry_run: bool,
+    silent: bool,
+    stdout: &mut StandardStream,
+) -> Result<u64> {
+    let mut files: BTreeMap<&Path, Vec<&FileUrl>> = BTreeMap::new();
+
+    for file_url in file_urls
+        .iter()
+        .filter(|file_url| file_url.replacement.is_some())
+    {
+        // URLs in archives and documents can't be rewritten in place.
+        if file_url.entry.is_some() || file_url.page.is_some() || file_url.paragraph.is_some() {
+            continue;
+        }
+
+        files.entry(&file_url.filepath).or_default().push(file_url);
+    }
+
+    let mut num_fixed_urls = 0;
+
+    for (path, mut file_urls) in files {
+        file_urls.sort_by_key(|file_url| file_url.span);
+        file_urls.dedup_by_key(|file_url| file_url.span);
+
+        num_fixed_urls += fix_file(path, &file_urls, encoding, dry_run, silent, stdout)?;
+    }
+
+    Ok(num_fixed_urls)
+}
+
+// Rewrites the URLs in the file at `path`, given sorted by span, and returns how many were
+// rewritten.
+fn fix_file(
+    path: &Path,
+    file_urls: &[&FileUrl],
+    encoding: Option<&'static Encoding>,
+    dry_run: bool,
+    silent: bool,
+    stdout: &mut StandardStream,
+) -> Result<u64> {
+    let bytes = fs::read(path)?;
+    let decoded = match encoding::decode_detected(&bytes, encoding) {
+        Ok(decoded) => decoded,
+        Err(_) => return Ok(0),
+    };
+    let text = &decoded.text;
+
+    // Only rewrite files that encode back to the same bytes, e.g. not files with invalid UTF-16.
+    let bom = &bytes[..decoded.bom_len];
+    if encoding::encode(text, decoded.encoding).as_deref() != Some(&bytes[bom.len()..]) {
+        if !silent {
+            let warning = format!(
+                "Warning: could not keep the encoding of {}. Not fixing.\n",
+                path.display()
+            );
+            util::set_and_unset_color(stdout, &warning, &COLOR_WARN)?;
+        }
+
+        return Ok(0);
+    }
+
+    let mut fixed = String::with_capacity(text.len());
+    let mut end = 0;
+    let mut num_fixed_urls = 0;
+
+    for file_url in file_urls {
+        let (start, url_end) = file_url.span;
+
+        // Only replace the exact text of a URL, e.g. not a URL wrapped across lines or escaped in
+        // a string.
+        if start < end || text.get(start..url_end) != Some(&file_url.url) {
+            continue;
+        }
+
+        fixed.push_str(&text[end..start]);
+        fixed.push_str(file_url.replacement.as_ref().unwrap());
+        end = url_end;
+        num_fixed_urls += 1;
+    }
+
+    fixed.push_str(&text[end..]);
+
+    if num_fixed_urls == 0 {
+        return Ok(0);
+    }
+
+    // Replacements are ASCII, or the URL upgraded to `https://`, so this shouldn't fail.
+    let fixed_bytes = match encoding::encode(&fixed, decoded.encoding) {
+        Some(fixed_bytes) => [bom, &fixed_bytes].concat(),
+        None => return Ok(0),
+    };
+
+    if dry_run {
+        if !silent {
+            print_diff(path, text, &fixed, stdout)?;
+        }
+    } else {
+        fs::write(path, fixed_bytes)?;
+
+        if !silent {
+            util::set_and_unset_color(stdout, "Fixed", &COLOR_INFO)?;
+            writeln!(
+                stdout,
+                " {} {} in {}",
+                num_fixed_urls,
+                if num_fixed_urls == 1 { "URL" } else { "URLs" },
+                path.display()
+            )?;
+        }
+    }
+
+    Ok(num_fixed_urls)
+}
+
+// Prints the lines of `text` that differ in `fixed` as a unified diff. Replacing URLs doesn't add
+// or remove lines.
+fn print_diff(path: &Path, text: &str, fixed: &str, stdout: &mut StandardStream) -> Result<()> {
+    util::set_and_unset_color(
+        stdout,
+        &format!("--- {0}\n+++ {0}\n", path.display()),
+        &COLOR_INFO,
+    )?;
+
+    for (i, (line, fixed_line)) in text.split('\n').zip(fixed.split('\n')).enumerate() {
+        if line != fixed_line {
+            util::set_and_unset_color(stdout, &format!("@@ -{0} +{0} @@", i + 1), &COLOR_PARAM)?;
+            writeln!(stdout)?;
+            writeln!(stdout, "-{}", line.trim_end_matches('\r'))?;
+            writeln!(stdout, "+{}", fixed_line.trim_end_matches('\r'))?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,527 @@
+//! Request settings for particular hosts, such as headers, credentials and cookies.
+
+use crate::{Error, Result, StatusCodes, Timeouts};
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Request settings for the URLs of a host. Settings are only sent to matching hosts, including
+/// when a URL redirects to another host, so that secrets don't leak to other domains.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct HostConfig {
+    /// The host the settings apply to, e.g. `github.com`. A leading `*.`, as in `*.corp.com`,
+    /// matches all subdomains of a domain instead.
+    pub host: String,
+    /// Extra headers to send, by name and value. `${NAME}` in a value is replaced by the
+    /// environment variable `NAME`, so that secrets needn't be written in configuration.
+    pub headers: Vec<(String, String)>,
+    /// Credentials to send.
+    pub auth: Option<Auth>,
+    /// A cookie jar to send cookies from, in the Netscape format written by curl and browser
+    /// extensions.
+    pub cookie_jar: Option<PathBuf>,
+    /// Don't verify the TLS certificate of the host, e.g. if it is self-signed.
+    pub insecure: bool,
+    /// Status codes of the host's URLs to count as good, e.g. 403 from a site that turns bots
+    /// away.
+    pub accept_status: StatusCodes,
+    /// Status codes of the host's URLs to report as warnings rather than as bad, e.g. 429 when
+    /// rate limited.
+    pub warn_status: StatusCodes,
+    /// Timeouts for the host's URLs, e.g. a longer total timeout for a slow site. Timeouts not set
+    /// are taken from `Config::timeouts`.
+    pub timeouts: Timeouts,
+}
+
+impl HostConfig {
+    /// Returns empty settings for `host`.
+    pub fn new(host: &str) -> Self {
+        HostConfig {
+            host: host.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Add a header.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Set auth.
+    pub fn auth(mut self, auth: Auth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Set cookie_jar.
+    pub fn cookie_jar(mut self, cookie_jar: &Path) -> Self {
+        self.cookie_jar = Some(cookie_jar.to_owned());
+        self
+    }
+
+    /// Set insecure=true.
+    pub fn insecure(mut self) -> Self {
+        self.insecure = true;
+        self
+    }
+
+    /// Set accept_status.
+    pub fn accept_status(mut self, codes: StatusCodes) -> Self {
+        self.accept_status = codes;
+        self
+    }
+
+    /// Set warn_status.
+    pub fn warn_status(mut self, codes: StatusCodes) -> Self {
+        self.warn_status = codes;
+        self
+    }
+
+    /// Set timeouts.
+    pub fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Returns true if the settings apply to `host`.
+    pub fn matches(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_lowercase();
+        let pattern = self.host.to_lowercase();
+
+        match pattern.strip_prefix("*.") {
+            Some(domain) => {
+                host.len() > domain.len()
+                    && host.ends_with(domain)
+                    && host[..host.len() - domain.len()].ends_with('.')
+            }
+            None => host == pattern,
+        }
+    }
+}
+
+/// Credentials for a host. Secrets are read from environment variables.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Auth {
+    /// Bearer authentication, with the token in the given environment variable.
+    Bearer {
+        /// The environment variable containing the token.
+        token_env: String,
+    },
+    /// Basic authentication, with the password in the given environment variable.
+    Basic {
+        /// The username.
+        username: String,
+        /// The environment variable containing the password.
+        password_env: String,
+    },
+}
+
+/// The settings of a host, with secrets and cookies read.
+#[derive(Debug)]
+pub struct HostSettings {
+    host: HostConfig,
+    // Headers in the form `Name: value`.
+    headers: Vec<String>,
+    cookies: Vec<Cookie>,
+}
+
+// A cookie from a cookie jar.
+#[derive(Debug, Eq, PartialEq)]
+struct Cookie {
+    domain: String,
+    include_subdomains: bool,
+    path: String,
+    secure: bool,
+    name: String,
+    value: String,
+}
+
+/// Reads the secrets and cookies of `hosts`, and the credentials in the netrc file `netrc`, if
+/// given.
+pub fn read(hosts: &[HostConfig], netrc: Option<&Path>) -> Result<Vec<HostSettings>> {
+    let mut host_settings = vec![];
+
+    for host in hosts {
+        let mut headers = vec![];
+
+        for (name, value) in host.headers.iter() {
+            headers.push(format!("{}: {}", name, expand_env(value)?));
+        }
+
+        match host.auth {
+            Some(Auth::Bearer { ref token_env }) => {
+                headers.push(format!("Authorization: Bearer {}", env_var(token_env)?));
+            }
+            Some(Auth::Basic {
+                ref username,
+                ref password_env,
+            }) => {
+                headers.push(basic_auth(username, &env_var(password_env)?));
+            }
+            None => (),
+        }
+
+        let cookies = match host.cookie_jar {
+            Some(ref path) => parse_cookie_jar(&read_file(path)?),
+            None => vec![],
+        };
+
+        host_settings.push(HostSettings {
+            host: host.clone(),
+            headers,
+            cookies,
+        });
+    }
+
+    if let Some(netrc) = netrc {
+        for (machine, login, password) in parse_netrc(&read_file(netrc)?) {
+            host_settings.push(HostSettings {
+                host: HostConfig::new(&machine),
+                headers: vec![basic_auth(&login, &password)],
+                cookies: vec![],
+            });
+        }
+    }
+
+    Ok(host_settings)
+}
+
+/// Returns the headers to send with a request to `url`, in the form `Name: value`.
+pub fn headers(host_settings: &[HostSettings], url: &str) -> Vec<String> {
+    let url = match ::url::Url::parse(url) {
+        Ok(url) => url,
+        Err(_) => return vec![],
+    };
+    let host = match url.host_str() {
+        Some(host) => host,
+        None => return vec![],
+    };
+
+    let mut headers = vec![];
+    let mut cookies = vec![];
+
+    for settings in host_settings
+        .iter()
+        .filter(|settings| settings.host.matches(host))
+    {
+        headers.extend(settings.headers.iter().cloned());
+
+        for cookie in settings.cookies.iter() {
+            if cookie.matches(host, url.path(), url.scheme() == "https") {
+                cookies.push(format!("{}={}", cookie.name, cookie.value));
+            }
+        }
+    }
+
+    if !cookies.is_empty() {
+        headers.push(format!("Cookie: {}", cookies.join("; ")));
+    }
+
+    headers
+}
+
+/// Returns the settings that apply to the host of `url`, in order.
+pub fn configs<'h>(host_settings: &'h [HostSettings], url: &str) -> Vec<&'h HostConfig> {
+    let url = ::url::Url::parse(url).ok();
+    let host = match url.as_ref().and_then(|url| url.host_str()) {
+        Some(host) => host,
+        None => return vec![],
+    };
+
+    host_settings
+        .iter()
+        .map(|settings| &settings.host)
+        .filter(|config| config.matches(host))
+        .collect()
+}
+
+/// Returns true if the TLS certificate of the host of `url` shouldn't be verified.
+pub fn is_insecure(host_settings: &[HostSettings], url: &str) -> bool {
+    configs(host_settings, url)
+        .iter()
+        .any(|config| config.insecure)
+}
+
+/// Returns the timeouts for `url`: those of the first settings of its host that set each one,
+/// falling back to `timeouts`.
+pub fn timeouts(host_settings: &[HostSettings], url: &str, timeouts: Timeouts) -> Timeouts {
+    configs(host_settings, url)
+        .iter()
+        .rev()
+        .fold(timeouts, |timeouts, config| config.timeouts.or(timeouts))
+}
+
+impl Cookie {
+    // Returns true if the cookie is sent with a request to `path` on `host`.
+    fn matches(&self, host: &str, path: &str, https: bool) -> bool {
+        let domain_matches = host.eq_ignore_ascii_case(&self.domain)
+            || (self.include_subdomains
+                && host.len() > self.domain.len()
+                && host[host.len() - self.domain.len()..].eq_ignore_ascii_case(&self.domain)
+                && host[..host.len() - self.domain.len()].ends_with('.'));
+        let path_matches = path == self.path
+            || (path.starts_with(&self.path)
+                && (self.path.ends_with('/') || path[self.path.len()..].starts_with('/')));
+
+        domain_matches && path_matches && (https || !self.secure)
+    }
+}
+
+// Reads the file at `path`, with the path in any error.
+fn read_file(path: &Path) -> Result<String> {
+    fs::read_to_string(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)).into())
+}
+
+// Returns the value of the environment variable `name`.
+fn env_var(name: &str) -> Result<String> {
+    env::var(name).map_err(|_| Error::MissingEnvVar(name.to_string()))
+}
+
+// Replaces `${NAME}` in `value` by the environment variable `NAME`.
+fn expand_env(value: &str) -> Result<String> {
+    lazy_static! {
+        static ref VAR: Regex = Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap();
+    }
+
+    let mut missing = None;
+    let expanded = VAR.replace_all(value, |cap: &Captures| {
+        env_var(&cap[1]).unwrap_or_else(|e| {
+            missing = Some(e);
+            String::new()
+        })
+    });
+
+    match missing {
+        Some(e) => Err(e),
+        None => Ok(expanded.into_owned()),
+    }
+}
+
+// Returns an `Authorization` header for basic authentication.
+fn basic_auth(username: &str, password: &str) -> String {
+    format!(
+        "Authorization: Basic {}",
+        base64(format!("{}:{}", username, password).as_bytes())
+    )
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::new();
+
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (u32::from(*b) << (16 - 8 * i)));
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+// Parses a cookie jar in the Netscape format: one cookie per line, with tab-separated domain,
+// subdomain flag, path, secure flag, expiry, name and value.
+fn parse_cookie_jar(text: &str) -> Vec<Cookie> {
+    text.lines()
+        .filter_map(|line| {
+            // curl marks HTTP-only cookies with a prefix on otherwise commented-out lines.
+            let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+            if line.starts_with('#') {
+                return None;
+            }
+
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() != 7 {
+                return None;
+            }
+
+            Some(Cookie {
+                domain: fields[0].trim_start_matches('.').to_string(),
+                include_subdomains: fields[1].eq_ignore_ascii_case("TRUE"),
+                path: fields[2].to_string(),
+                secure: fields[3].eq_ignore_ascii_case("TRUE"),
+                name: fields[5].to_string(),
+                value: fields[6].to_string(),
+            })
+        })
+        .collect()
+}
+
+// Parses a netrc file, returning the machine, login and password of each complete entry. Default
+// entries are ignored, as they would send credentials to every host.
+fn parse_netrc(text: &str) -> Vec<(String, String, String)> {
+    let mut entries = vec![];
+    let mut entry: Option<(String, Option<String>, Option<String>)> = None;
+    let mut tokens = text.split_whitespace();
+
+    let mut finish = |entry: Option<(String, Option<String>, Option<String>)>| {
+        if let Some((machine, Some(login), Some(password))) = entry {
+            entries.push((machine, login, password));
+        }
+    };
+
+    while let Some(token) = tokens.next() {
+        match token {
+            "machine" => {
+                finish(entry.take());
+                entry = tokens
+                    .next()
+                    .map(|machine| (machine.to_string(), None, None));
+            }
+            "default" => finish(entry.take()),
+            "login" | "password" => {
+                let value = tokens.next().map(str::to_string);
+                if let Some((_, ref mut login, ref mut password)) = entry {
+                    if token == "login" {
+                        *login = value;
+                    } else {
+                        *password = value;
+                    }
+                }
+            }
+            "macdef" => {
+                // Macro definitions run to the next blank line, which whitespace splitting loses,
+                // so the rest of the file is skipped.
+                finish(entry.take());
+                break;
+            }
+            _ => (),
+        }
+    }
+
+    finish(entry);
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn match_hosts() {
+        let host = HostConfig::new("github.com");
+        assert!(host.matches("github.com"));
+        assert!(host.matches("GitHub.com."));
+        assert!(!host.matches("api.github.com"));
+        assert!(!host.matches("notgithub.com"));
+
+        let host = HostConfig::new("*.corp.com");
+        assert!(host.matches("wiki.corp.com"));
+        assert!(host.matches("a.b.corp.com"));
+        assert!(!host.matches("corp.com"));
+        assert!(!host.matches("evilcorp.com"));
+    }
+
+    #[test]
+    fn insecure_hosts() {
+        let host_settings = |host: HostConfig| HostSettings {
+            host,
+            headers: vec![],
+            cookies: vec![],
+        };
+        let hosts = [
+            host_settings(HostConfig::new("dev.corp.com").insecure()),
+            host_settings(HostConfig::new("*.corp.com")),
+        ];
+
+        assert!(is_insecure(&hosts, "https://dev.corp.com/"));
+        assert!(!is_insecure(&hosts, "https://wiki.corp.com/"));
+        assert!(!is_insecure(&hosts, "https://dev.corp.com.evil.com/"));
+    }
+
+    #[test]
+    fn host_timeouts() {
+        let host_settings = |host: HostConfig| HostSettings {
+            host,
+            headers: vec![],
+            cookies: vec![],
+        };
+        let timeouts = |s: &str| s.parse::<Timeouts>().unwrap();
+        let hosts = [
+            host_settings(HostConfig::new("slow.corp.com").timeouts(timeouts("total:30000"))),
+            host_settings(HostConfig::new("*.corp.com").timeouts(timeouts("total:5000,dns:100"))),
+        ];
+        let global = timeouts("connect:500,total:1000");
+
+        assert_eq!(
+            self::timeouts(&hosts, "https://slow.corp.com/", global).to_string(),
+            "dns:100,connect:500,total:30000"
+        );
+        assert_eq!(
+            self::timeouts(&hosts, "https://wiki.corp.com/", global).to_string(),
+            "dns:100,connect:500,total:5000"
+        );
+        assert_eq!(self::timeouts(&hosts, "https://docs.rs/", global), global);
+    }
+
+    #[test]
+    fn encode_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"a"), "YQ==");
+        assert_eq!(base64(b"ab"), "YWI=");
+        assert_eq!(base64(b"abc"), "YWJj");
+        assert_eq!(base64(b"user:pass word"), "dXNlcjpwYXNzIHdvcmQ=");
+    }
+
+    #[test]
+    fn cookies() {
+        let jar = "\
+# Netscape HTTP Cookie File
+.corp.com\tTRUE\t/\tTRUE\t0\tsession\tabc
+wiki.corp.com\tFALSE\t/docs\tFALSE\t0\tlang\ten
+#HttpOnly_wiki.corp.com\tFALSE\t/\tFALSE\t0\ttoken\txyz
+other.com\tFALSE\t/\tFALSE\t0\tother\t1";
+        let host_settings = [HostSettings {
+            host: HostConfig::new("*.corp.com"),
+            headers: vec![],
+            cookies: parse_cookie_jar(jar),
+        }];
+
+        assert_eq!(
+            headers(&host_settings, "https://wiki.corp.com/docs/page"),
+            vec!["Cookie: session=abc; lang=en; token=xyz"]
+        );
+        assert_eq!(
+            headers(&host_settings, "http://wiki.corp.com/docsx"),
+            vec!["Cookie: token=xyz"]
+        );
+        assert_eq!(
+            headers(&host_settings, "https://git.corp.com/"),
+            vec!["Cookie: session=abc"]
+        );
+        assert!(headers(&host_settings, "https://other.com/").is_empty());
+    }
+
+    #[test]
+    fn netrc() {
+        let netrc = "\
+machine wiki.corp.com login alice password s3cret
+machine ftp.corp.com
+    login bob
+default login anonymous password guest
+machine git.corp.com login carol password hunter2";
+
+        assert_eq!(
+            parse_netrc(netrc),
+            vec![
+                ("wiki.corp.com".into(), "alice".into(), "s3cret".into()),
+                ("git.corp.com".into(), "carol".into(), "hunter2".into()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,440 @@
+//! Source-language detection and comment scanning.
+
+use std::{fmt, ops::Range, path::Path, str::FromStr};
+
+/// A source language whose comments can be scanned for URLs.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Language {
+    /// C and C++ (`.c`, `.h`, `.cpp`, `.hpp`, ...).
+    C,
+    /// Go (`.go`).
+    Go,
+    /// JavaScript and TypeScript (`.js`, `.jsx`, `.mjs`, `.ts`, `.tsx`, ...).
+    JavaScript,
+    /// Python (`.py`, `.pyi`). Docstrings are treated as comments.
+    Python,
+    /// Rust (`.rs`).
+    Rust,
+    /// Shell scripts (`.sh`, `.bash`, `.zsh`).
+    Shell,
+    /// TOML (`.toml`).
+    Toml,
+    /// YAML (`.yml`, `.yaml`).
+    Yaml,
+}
+
+impl Language {
+    /// All supported languages.
+    pub const ALL: &'static [Language] = &[
+        Language::C,
+        Language::Go,
+        Language::JavaScript,
+        Language::Python,
+        Language::Rust,
+        Language::Shell,
+        Language::Toml,
+        Language::Yaml,
+    ];
+
+    /// Returns the language of the file at `path`, based on its extension.
+    pub fn from_path(path: &Path) -> Option<Language> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+
+        Some(match ext.as_str() {
+            "c" | "h" | "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" => Language::C,
+            "go" => Language::Go,
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => Language::JavaScript,
+            "py" | "pyi" => Language::Python,
+            "rs" => Language::Rust,
+            "sh" | "bash" | "zsh" => Language::Shell,
+            "toml" => Language::Toml,
+            "yml" | "yaml" => Language::Yaml,
+            _ => return None,
+        })
+    }
+
+    /// Returns true if a backslash at the end of a line continues the line in the file at `path`.
+    pub fn line_continuations(path: &Path) -> bool {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("");
+
+        match Language::from_path(path) {
+            Some(Language::C) | Some(Language::Python) | Some(Language::Shell) => true,
+            _ => {
+                name == "Dockerfile"
+                    || name.ends_with(".dockerfile")
+                    || name.eq_ignore_ascii_case("makefile")
+                    || name == "GNUmakefile"
+                    || name.ends_with(".mk")
+            }
+        }
+    }
+
+    fn syntax(self) -> &'static Syntax {
+        use Language::*;
+
+        match self {
+            C => &C_SYNTAX,
+            Go => &GO_SYNTAX,
+            JavaScript => &JS_SYNTAX,
+            Python => &PYTHON_SYNTAX,
+            Rust => &RUST_SYNTAX,
+            Shell => &SHELL_SYNTAX,
+            Toml => &TOML_SYNTAX,
+            Yaml => &YAML_SYNTAX,
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Language::*;
+
+        let name = match *self {
+            C => "c",
+            Go => "go",
+            JavaScript => "js",
+            Python => "python",
+            Rust => "rust",
+            Shell => "shell",
+            Toml => "toml",
+            Yaml => "yaml",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Language::*;
+
+        Ok(match s.to_lowercase().as_str() {
+            "c" | "cpp" | "c++" => C,
+            "go" => Go,
+            "js" | "javascript" | "ts" | "typescript" => JavaScript,
+            "py" | "python" => Python,
+            "rs" | "rust" => Rust,
+            "sh" | "shell" | "bash" => Shell,
+            "toml" => Toml,
+            "yml" | "yaml" => Yaml,
+            _ => return Err(format!("Unknown language: {}", s)),
+        })
+    }
+}
+
+// Describes the comment and string syntax of a language.
+struct Syntax {
+    // Line comment markers.
+    line: &'static [&'static str],
+    // Block comment delimiters.
+    block: &'static [(&'static str, &'static str)],
+    // String delimiters, and whether backslash escapes are recognized inside them.
+    strings: &'static [(&'static str, bool)],
+    // Whether block comments nest.
+    nested_blocks: bool,
+    // Whether comments and strings may only start at the beginning of a word.
+    word_boundary: bool,
+}
+
+static C_SYNTAX: Syntax = Syntax {
+    line: &["//"],
+    block: &[("/*", "*/")],
+    strings: &[("\"", true), ("'", true)],
+    nested_blocks: false,
+    word_boundary: false,
+};
+
+static GO_SYNTAX: Syntax = Syntax {
+    line: &["//"],
+    block: &[("/*", "*/")],
+    strings: &[("\"", true), ("`", false), ("'", true)],
+    nested_blocks: false,
+    word_boundary: false,
+};
+
+static JS_SYNTAX: Syntax = Syntax {
+    line: &["//"],
+    block: &[("/*", "*/")],
+    strings: &[("\"", true), ("'", true), ("`", true)],
+    nested_blocks: false,
+    word_boundary: false,
+};
+
+// Docstrings are Python's doc comments, so triple-quoted strings are scanned as block comments.
+static PYTHON_SYNTAX: Syntax = Syntax {
+    line: &["#"],
+    block: &[("\"\"\"", "\"\"\""), ("'''", "'''")],
+    strings: &[("\"", true), ("'", true)],
+    nested_blocks: false,
+    word_boundary: false,
+};
+
+// Raw strings and character literals are handled separately in `comment_ranges`.
+static RUST_SYNTAX: Syntax = Syntax {
+    line: &["//"],
+    block: &[("/*", "*/")],
+    strings: &[("\"", true)],
+    nested_blocks: true,
+    word_boundary: false,
+};
+
+static SHELL_SYNTAX: Syntax = Syntax {
+    line: &["#"],
+    block: &[],
+    strings: &[("\"", true), ("'", false)],
+    nested_blocks: false,
+    word_boundary: true,
+};
+
+static TOML_SYNTAX: Syntax = Syntax {
+    line: &["#"],
+    block: &[],
+    strings: &[("\"\"\"", true), ("'''", false), ("\"", true), ("'", false)],
+    nested_blocks: false,
+    word_boundary: false,
+};
+
+static YAML_SYNTAX: Syntax = Syntax {
+    line: &["#"],
+    block: &[],
+    strings: &[("\"", true), ("'", false)],
+    nested_blocks: false,
+    word_boundary: true,
+};
+
+// Returns true if `text[i..]` is at the start of a word, for languages with `word_boundary` set.
+// Any of the `separators` may also precede a word.
+fn at_word_boundary(text: &str, i: usize, separators: &str) -> bool {
+    match text[..i].chars().next_back() {
+        None => true,
+        Some(c) => c.is_whitespace() || separators.contains(c),
+    }
+}
+
+// Returns the length of a Rust raw string opener (`r"`, `r#"`, `br##"`, ...) at `text[i..]`, along
+// with the number of hashes.
+fn rust_raw_string(text: &str, i: usize) -> Option<(usize, usize)> {
+    let rest = &text[i..];
+    let after_prefix = rest.strip_prefix("br").or_else(|| rest.strip_prefix('r'))?;
+
+    // Don't mistake the end of an identifier for a raw string prefix.
+    if let Some(c) = text[..i].chars().next_back() {
+        if c.is_alphanumeric() || c == '_' {
+            return None;
+        }
+    }
+
+    let hashes = after_prefix.len() - after_prefix.trim_start_matches('#').len();
+    if after_prefix[hashes..].starts_with('"') {
+        Some((rest.len() - after_prefix.len() + hashes + 1, hashes))
+    } else {
+        None
+    }
+}
+
+// Returns the length of a Rust character literal at `text[i..]`, if there is one. Lifetimes are
+// not character literals.
+fn rust_char_literal(text: &str, i: usize) -> Option<usize> {
+    let mut chars = text[i..].char_indices().skip(1);
+
+    match chars.next()? {
+        (_, '\\') => {
+            // Skip the escaped character. Escapes are at most `'\u{10FFFF}'`.
+            text.get(i + 3..)?
+                .char_indices()
+                .take(8)
+                .find(|&(_, c)| c == '\'')
+                .map(|(j, _)| j + 4)
+        }
+        (_, '\'') => None,
+        (_, _) => match chars.next()? {
+            (j, '\'') => Some(j + 1),
+            _ => None,
+        },
+    }
+}
+
+/// Returns the byte ranges of all comments in `text`, excluding the comment markers themselves.
+pub fn comment_ranges(text: &str, lang: Language) -> Vec<Range<usize>> {
+    let syntax = lang.syntax();
+    let mut ranges = vec![];
+    let mut i = 0;
+
+    while i < text.len() {
+        let rest = &text[i..];
+        // Line comments.
+        if !syntax.word_boundary || at_word_boundary(text, i, "") {
+            if let Some(marker) = syntax.line.iter().find(|m| rest.starts_with(*m)) {
+                let start = i + marker.len();
+                let end = text[start..].find('\n').map_or(text.len(), |j| start + j);
+
+                ranges.push(start..end);
+                i = end;
+                continue;
+            }
+        }
+
+        // Block comments.
+        if let Some((open, close)) = syntax.block.iter().find(|(open, _)| rest.starts_with(open)) {
+            let start = i + open.len();
+            let mut depth = 1;
+            let mut j = start;
+
+            while j < text.len() && depth > 0 {
+                if text[j..].starts_with(close) {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                    j += close.len();
+                } else if syntax.nested_blocks && text[j..].starts_with(open) {
+                    depth += 1;
+                    j += open.len();
+                } else {
+                    j += text[j..].chars().next().map_or(1, char::len_utf8);
+                }
+            }
+
+            let end = j.min(text.len());
+            ranges.push(start..end);
+            i = (end + close.len()).min(text.len());
+            continue;
+        }
+
+        // Strings, which are skipped.
+        if lang == Language::Rust {
+            if let Some((len, hashes)) = rust_raw_string(text, i) {
+                let close = format!("\"{}", "#".repeat(hashes));
+                i = text[i + len..]
+                    .find(&close)
+                    .map_or(text.len(), |j| i + len + j + close.len());
+                continue;
+            }
+
+            if rest.starts_with('\'') {
+                i += rust_char_literal(text, i).unwrap_or(1);
+                continue;
+            }
+        }
+
+        if !syntax.word_boundary || at_word_boundary(text, i, "=:,([{") {
+            if let Some(&(delim, escapes)) =
+                syntax.strings.iter().find(|(d, _)| rest.starts_with(d))
+            {
+                let mut j = i + delim.len();
+
+                while j < text.len() && !text[j..].starts_with(delim) {
+                    if escapes && text[j..].starts_with('\\') {
+                        j += 1;
+                    }
+                    j += text[j..].chars().next().map_or(1, char::len_utf8);
+                }
+
+                i = (j + delim.len()).min(text.len());
+                continue;
+            }
+        }
+
+        i += rest.chars().next().map_or(1, char::len_utf8);
+    }
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn comments(text: &str, lang: Language) -> Vec<&str> {
+        comment_ranges(text, lang)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    #[test]
+    fn language_from_path() {
+        assert_eq!(
+            Language::from_path(Path::new("a/b.rs")),
+            Some(Language::Rust)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("b.TSX")),
+            Some(Language::JavaScript)
+        );
+        assert_eq!(Language::from_path(Path::new("b.hpp")), Some(Language::C));
+        assert_eq!(Language::from_path(Path::new("README.md")), None);
+        assert_eq!(Language::from_path(Path::new("Makefile")), None);
+
+        assert!(Language::line_continuations(Path::new("build.sh")));
+        assert!(Language::line_continuations(Path::new("docker/Dockerfile")));
+        assert!(Language::line_continuations(Path::new("Makefile")));
+        assert!(!Language::line_continuations(Path::new("README.md")));
+    }
+
+    #[test]
+    fn comments_rust() {
+        assert_eq!(
+            comments(
+                "let s = \"// http://a.com\"; // http://b.com\n\
+                 /* http://c.com /* nested */ still */ let c = '\"'; let q = '\\''; //d\n\
+                 let r = r#\"/* http://e.com\"#; fn f<'a>() {} // e",
+                Language::Rust
+            ),
+            vec![
+                " http://b.com",
+                " http://c.com /* nested */ still ",
+                "d",
+                " e"
+            ]
+        );
+        assert_eq!(
+            comments("/// doc\n//! inner", Language::Rust),
+            vec!["/ doc", "! inner"]
+        );
+    }
+
+    #[test]
+    fn comments_python() {
+        assert_eq!(
+            comments(
+                "x = 'http://a.com' # http://b.com\n\"\"\"Docs: http://c.com\"\"\"\ny = \"#\"",
+                Language::Python
+            ),
+            vec![" http://b.com", "Docs: http://c.com"]
+        );
+    }
+
+    #[test]
+    fn comments_shell_and_yaml() {
+        assert_eq!(
+            comments(
+                "echo ${#arr} 'http://a.com#x' # http://b.com\n#c",
+                Language::Shell
+            ),
+            vec![" http://b.com", "c"]
+        );
+        assert_eq!(
+            comments("url: http://a.com/#frag # http://b.com", Language::Yaml),
+            vec![" http://b.com"]
+        );
+    }
+
+    #[test]
+    fn comments_unterminated() {
+        assert_eq!(
+            comments("/* http://a.com", Language::C),
+            vec![" http://a.com"]
+        );
+        assert_eq!(
+            comments("\"http://a.com // x", Language::JavaScript),
+            Vec::<&str>::new()
+        );
+    }
+}
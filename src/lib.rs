@@ -3,26 +3,72 @@
 #![forbid(unsafe_code)]
 #![deny(missing_docs)]
 
+#[cfg(feature = "archive")]
+mod archive;
 mod config;
+#[cfg(any(feature = "office", feature = "pdf"))]
+mod document;
+mod encoding;
 mod error;
+mod fix;
+mod host;
+mod lang;
+mod markdown;
+mod notebook;
+#[cfg(feature = "office")]
+mod office;
 mod parse;
+#[cfg(feature = "pdf")]
+mod pdf;
+mod placeholder;
+mod redirect;
+mod request;
+mod resolve;
+mod rustdoc;
+mod scheme;
+mod soft404;
+mod status;
+mod structured;
+mod suppress;
+mod timeout;
+mod tls;
 mod url;
 mod util;
 
 pub use config::Config;
 pub use error::{Error, Result};
-
-use crate::url::ExclusionPattern;
+pub use host::{Auth, HostConfig};
+pub use lang::Language;
+pub use placeholder::Placeholder;
+pub use redirect::{Redirect, RedirectPolicy};
+pub use request::MethodStrategy;
+pub use resolve::{IpVersion, Resolve};
+pub use scheme::Scheme;
+pub use soft404::Soft404;
+pub use status::{Status, StatusCodes, StatusRule, Verdict};
+pub use timeout::{TimeoutPhase, Timeouts};
+pub use tls::TlsVersion;
+
+use crate::{
+    encoding::Skipped, host::HostSettings, notebook::CellKind, parse::LineIndex,
+    redirect::DEFAULT_MAX_REDIRECTS, request::DEFAULT_MAX_BODY_SIZE, rustdoc::IntraDocLinks,
+    soft404::Detector, structured::DataFormat, suppress::Suppressions, url::ExclusionPattern,
+};
 use clap::crate_version;
-use curl::easy::{Easy2, Handler, WriteError};
+use curl::{
+    easy::{Easy2, Handler, HttpVersion, IpResolve, List, WriteError},
+    Version,
+};
 use ignore::{overrides::OverrideBuilder, WalkBuilder};
 use lazy_static::lazy_static;
 use std::{
-    fs::File,
-    io::{self, BufRead, BufReader, Write},
+    fmt, fs,
+    io::Write,
+    mem,
+    ops::Range,
     path::{Path, PathBuf},
     result,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use termcolor::{Color, ColorSpec, StandardStream};
 
@@ -51,33 +97,151 @@ pub struct Info {
     // TODO: implement and test.
     /// Total number of distinct files processed.
     pub num_files: u64,
+    /// Number of files skipped because they appear to be binary.
+    pub num_binary_files: u64,
+    /// Number of files skipped because their text encoding could not be decoded.
+    pub num_undecodable_files: u64,
     // TODO: implement and test.
     /// Total number of distinct URLs processed.
     pub num_urls: u64,
     // TODO: implement and test.
     /// Total number of distinct bad URLs found.
     pub num_bad_urls: u64,
+    /// Number of distinct URLs reported as redirected: those that moved permanently, or with
+    /// `RedirectPolicy::Warn`, all that redirected.
+    pub num_redirected_urls: u64,
+    /// Number of distinct URLs reported as warnings because of their status code, e.g. 429.
+    pub num_warned_urls: u64,
+    /// Number of distinct URLs suspected to be soft 404s, with `Config::soft_404`.
+    pub num_soft_404_urls: u64,
+    /// Number of requests sent to check URLs, including retries and redirects.
+    pub num_requests: u64,
+    /// Number of connections opened to send the requests. Requests to the same host reuse open
+    /// connections.
+    pub num_connections: u64,
+    /// Number of URLs rewritten with `Config::fix`, or that would be with `Config::dry_run`.
+    pub num_fixed_urls: u64,
 }
 
 /// URL in a File.
 /// If the URL could not be resolved, the `bad` field will be set.
-#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct FileUrl {
     /// The URL.
     pub url: String,
     /// The path to the file containing the URL.
     pub filepath: PathBuf,
-    /// The line the URL was found on.
+    /// The line the URL was found on. For links in documents whose position is not known, such as
+    /// PDF link annotations, the line and column are 0.
     pub line: usize,
+    /// The 1-based column the URL starts at, counted in characters.
+    pub column: usize,
+    /// The start and end byte offsets of the URL in the file's text, after decoding it to UTF-8.
+    /// For URLs wrapped across lines, the span includes the line breaks.
+    pub span: (usize, usize),
+    /// For URLs found in compressed files or archives, the path of the file inside the archive,
+    /// e.g. `docs/intro.md`. Not set for compressed files that aren't archives.
+    pub entry: Option<String>,
+    /// For URLs found in structured data files, the path of keys leading to the value containing
+    /// the URL, e.g. `package.repository`.
+    pub key_path: Option<String>,
+    /// For URLs found in Jupyter notebooks, the 1-based index of the cell containing the URL. The
+    /// `line` and `column` are then relative to the start of the cell.
+    pub cell: Option<usize>,
+    /// For URLs found in PDF documents and presentations, the 1-based page or slide containing the
+    /// URL. The `line` and `column` are then relative to the start of the page or slide.
+    pub page: Option<usize>,
+    /// For URLs found in Word documents and presentations, the 1-based paragraph containing the
+    /// URL, within the document or slide. The `line` and `column` are then relative to the start
+    /// of the paragraph.
+    pub paragraph: Option<usize>,
+    /// True for intra-doc links in Rust doc comments, with `Config::check_intra_doc_links`. These
+    /// are resolved against the items declared in the files searched rather than checked.
+    pub intra_doc: bool,
     /// If the URL was checked, the inner value will be true if the URL failed to resolve.
     pub bad: Option<bool>,
-    /// Whether this URL was excluded via --exclude-urls.
-    pub excluded: bool,
+    /// If the URL was excluded from checking, the reason why.
+    pub excluded: Option<Exclusion>,
+    /// The redirects followed while checking the URL, in order. The last one is to the final URL.
+    pub redirects: Vec<Redirect>,
+    /// The URL to replace this one with, if it is good: the URL it permanently redirects to, or
+    /// with `Config::fix`, its `https://` equivalent if that is good too.
+    pub replacement: Option<String>,
+    /// For URLs checked over HTTP, the status code of the final response and the rule that decided
+    /// what it means.
+    pub status: Option<Status>,
+    /// With `Config::soft_404`, why the URL is suspected to be a soft 404 if it is: a missing page
+    /// answered with a good status code.
+    pub soft_404: Option<Soft404>,
+    /// If checking the URL timed out, the timeout that expired, e.g. the connect timeout for an
+    /// unreachable host.
+    pub timeout: Option<TimeoutPhase>,
+}
+
+/// The reason a URL was excluded from checking.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Exclusion {
+    /// The URL matched a pattern passed to --exclude-urls.
+    Pattern,
+    /// The URL is on a line suppressed by a directive such as `loch-ignore-line`.
+    InlineSuppression,
+    /// The URL is a placeholder, such as a templated URL or the URL of a local host, which isn't
+    /// meant to be resolved as written.
+    Placeholder(Placeholder),
+    /// The URL uses a protocol, such as `sftp`, that libcurl was built without.
+    UnsupportedProtocol,
+}
+
+impl fmt::Display for Exclusion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Exclusion::Pattern => "excluded pattern",
+            Exclusion::InlineSuppression => "inline suppression",
+            Exclusion::Placeholder(Placeholder::Template) => "template placeholder",
+            Exclusion::Placeholder(Placeholder::Local) => "local address",
+            Exclusion::Placeholder(Placeholder::Example) => "example domain",
+            Exclusion::UnsupportedProtocol => "unsupported protocol",
+        })
+    }
 }
 
 impl FileUrl {
+    // Constructs an unchecked `FileUrl` at the given line and column.
+    fn new(url: &str, filepath: &Path, (line, column): (usize, usize), span: Range<usize>) -> Self {
+        FileUrl {
+            url: url.to_string(),
+            filepath: filepath.to_owned(),
+            line,
+            column,
+            span: (span.start, span.end),
+            ..Default::default()
+        }
+    }
+
     fn file_ref(&self) -> String {
-        format!("[{}:{}]", self.filepath.to_str().unwrap(), self.line)
+        let mut file_ref = format!("[{}", self.filepath.to_str().unwrap());
+
+        if let Some(ref entry) = self.entry {
+            file_ref.push_str(&format!("!/{}", entry));
+        }
+        if let Some(cell) = self.cell {
+            file_ref.push_str(&format!(":cell {}", cell));
+        }
+        if let Some(page) = self.page {
+            file_ref.push_str(&format!(":page {}", page));
+        }
+        if let Some(paragraph) = self.paragraph {
+            file_ref.push_str(&format!(":paragraph {}", paragraph));
+        }
+        if self.line > 0 {
+            file_ref.push_str(&format!(":{}:{}", self.line, self.column));
+        }
+        if let Some(ref key_path) = self.key_path {
+            file_ref.push_str(&format!(" {}", key_path));
+        }
+
+        file_ref.push(']');
+        file_ref
     }
 }
 
@@ -117,15 +281,86 @@ pub fn check_paths(input_paths: &[&str], config: Option<&Config>) -> Result<Info
     // Get excluded paths.
     let exclude_paths = config.map_or(&empty, |config| &config.exclude_paths);
 
+    // Get languages to only search comments in.
+    let comments_only = config.map_or(&[][..], |config| &config.comments_only[..]);
+
+    // Get kinds of placeholder URLs to check rather than skip.
+    let check_placeholders = config.map_or(&[][..], |config| &config.check_placeholders[..]);
+
+    // Get other URL schemes to find.
+    let schemes = config.map_or(&[][..], |config| &config.schemes[..]);
+
+    // Get the request settings of hosts, reading their secrets.
+    let hosts = config.map_or(&[][..], |config| &config.hosts[..]);
+    let netrc = config.and_then(|config| config.netrc.as_deref());
+    let host_settings = host::read(hosts, netrc)?;
+
+    // Get the addresses to connect to instead of resolving host names. Those given for a port
+    // come first, so that they take precedence over the hosts file.
+    let hosts_file = config.and_then(|config| config.hosts_file.as_deref());
+    let ip_version = config.and_then(|config| config.ip_version);
+    let mut resolves = config.map_or(vec![], |config| config.resolve.clone());
+    if let Some(hosts_file) = hosts_file {
+        resolves.extend(resolve::read_hosts_file(hosts_file)?);
+    }
+
+    // Get the proxy and TLS settings.
+    let proxy = config.and_then(|config| config.proxy.clone());
+    let no_proxy = config.map_or(vec![], |config| config.no_proxy.clone());
+    let ca_bundle = config.and_then(|config| config.ca_bundle.clone());
+    let client_cert = config.and_then(|config| config.client_cert.clone());
+    let client_key = config.and_then(|config| config.client_key.clone());
+    let min_tls_version = config.and_then(|config| config.min_tls_version);
+
+    // Get the text encoding of files, if not detected.
+    let encoding = match config.and_then(|config| config.encoding.as_ref()) {
+        Some(label) => {
+            Some(encoding::for_label(label).ok_or_else(|| Error::InvalidEncoding(label.clone()))?)
+        }
+        None => None,
+    };
+
     // Get flags.
+    let check_intra_doc_links = config.is_some_and(|config| config.check_intra_doc_links);
+    let dry_run = config.is_some_and(|config| config.dry_run);
+    let fix = config.is_some_and(|config| config.fix) || dry_run;
     let follow = config.map_or(false, |config| config.follow);
     let list_files = config.map_or(false, |config| config.list_files);
     let no_check = config.map_or(false, |config| config.no_check);
     let no_color = config.map_or(false, |config| config.no_color);
     let no_http = config.map_or(false, |config| config.no_http);
     let no_ignore = config.map_or(false, |config| config.no_ignore);
+    let notebook_code_comments = config.is_some_and(|config| config.notebook_code_comments);
+    let search_archives = config.is_some_and(|config| config.search_archives);
+
+    // Archives can only be searched with the `archive` feature.
+    if search_archives && !cfg!(feature = "archive") {
+        return Err(Error::MissingFeature(
+            "search-archives requires the archive feature".to_string(),
+        ));
+    }
+
+    let structured_data = config.is_some_and(|config| config.structured_data);
     let silent = config.map_or(false, |config| config.silent);
-    let timeout = config.map_or(None, |config| config.timeout);
+    let soft_404_patterns = config.map_or(&empty, |config| &config.soft_404_patterns);
+    let soft_404 = config.is_some_and(|config| config.soft_404) || !soft_404_patterns.is_empty();
+    let timeout = config.and_then(|config| config.timeout);
+    let timeouts = config
+        .map_or(Default::default(), |config| config.timeouts)
+        .or(Timeouts {
+            total: timeout.map(Duration::from_secs),
+            ..Default::default()
+        });
+    let redirects = config.map_or(Default::default(), |config| config.redirects);
+    let max_redirects = config
+        .and_then(|config| config.max_redirects)
+        .unwrap_or(DEFAULT_MAX_REDIRECTS);
+    let method = config.map_or(Default::default(), |config| config.method);
+    let max_body_size = config
+        .and_then(|config| config.max_body_size)
+        .unwrap_or(DEFAULT_MAX_BODY_SIZE);
+    let accept_status = config.map_or(Default::default(), |config| config.accept_status.clone());
+    let warn_status = config.map_or(Default::default(), |config| config.warn_status.clone());
 
     let verbose = config.map_or(false, |config| config.verbose);
 
@@ -144,12 +379,70 @@ pub fn check_paths(input_paths: &[&str], config: Option<&Config>) -> Result<Info
 
         // Display CLI arguments only (API-only arguments can be accessed programmatically).
         // TODO: Add all parameters here.
+        util::set_and_unset_color(&mut stdout, "  accept-status:", &COLOR_PARAM)?;
+        writeln!(stdout, " {}", accept_status)?;
+        util::set_and_unset_color(&mut stdout, "  ca-bundle:", &COLOR_PARAM)?;
+        writeln!(stdout, " {:?}", ca_bundle)?;
+        util::set_and_unset_color(&mut stdout, "  check-intra-doc-links:", &COLOR_PARAM)?;
+        writeln!(stdout, " {}", check_intra_doc_links)?;
+        util::set_and_unset_color(&mut stdout, "  dry-run:", &COLOR_PARAM)?;
+        writeln!(stdout, " {}", dry_run)?;
+        util::set_and_unset_color(&mut stdout, "  fix:", &COLOR_PARAM)?;
+        writeln!(stdout, " {}", fix)?;
+        util::set_and_unset_color(&mut stdout, "  check-placeholders:", &COLOR_PARAM)?;
+        writeln!(stdout, " {:?}", check_placeholders)?;
+        util::set_and_unset_color(&mut stdout, "  client-cert:", &COLOR_PARAM)?;
+        writeln!(stdout, " {:?}", client_cert)?;
+        util::set_and_unset_color(&mut stdout, "  client-key:", &COLOR_PARAM)?;
+        writeln!(stdout, " {:?}", client_key)?;
+        util::set_and_unset_color(&mut stdout, "  comments-only:", &COLOR_PARAM)?;
+        writeln!(stdout, " {:?}", comments_only)?;
+        util::set_and_unset_color(&mut stdout, "  encoding:", &COLOR_PARAM)?;
+        writeln!(stdout, " {:?}", encoding.map(|encoding| encoding.name()))?;
         util::set_and_unset_color(&mut stdout, "  exclude-paths:", &COLOR_PARAM)?;
         writeln!(stdout, " {:?}", exclude_paths)?;
         util::set_and_unset_color(&mut stdout, "  exclude-urls:", &COLOR_PARAM)?;
         writeln!(stdout, " {:?}", exclude_urls)?;
         util::set_and_unset_color(&mut stdout, "  follow:", &COLOR_PARAM)?;
         writeln!(stdout, " {}", follow)?;
+        util::set_and_unset_color(&mut stdout, "  hosts:", &COLOR_PARAM)?;
+        writeln!(
+            stdout,
+            " {:?}",
+            hosts.iter().map(|host| &host.host).collect::<Vec<_>>()
+        )?;
+        util::set_and_unset_color(&mut stdout, "  hosts-file:", &COLOR_PARAM)?;
+        writeln!(stdout, " {:?}", hosts_file)?;
+        util::set_and_unset_color(&mut stdout, "  insecure:", &COLOR_PARAM)?;
+        writeln!(
+            stdout,
+            " {:?}",
+            hosts
+                .iter()
+                .filter(|host| host.insecure)
+                .map(|host| &host.host)
+                .collect::<Vec<_>>()
+        )?;
+        util::set_and_unset_color(&mut stdout, "  ip-version:", &COLOR_PARAM)?;
+        writeln!(
+            stdout,
+            " {:?}",
+            ip_version.map(|version| version.to_string())
+        )?;
+        util::set_and_unset_color(&mut stdout, "  max-body-size:", &COLOR_PARAM)?;
+        writeln!(stdout, " {}", max_body_size)?;
+        util::set_and_unset_color(&mut stdout, "  max-redirects:", &COLOR_PARAM)?;
+        writeln!(stdout, " {}", max_redirects)?;
+        util::set_and_unset_color(&mut stdout, "  method:", &COLOR_PARAM)?;
+        writeln!(stdout, " {}", method)?;
+        util::set_and_unset_color(&mut stdout, "  min-tls:", &COLOR_PARAM)?;
+        writeln!(
+            stdout,
+            " {:?}",
+            min_tls_version.map(|version| version.to_string())
+        )?;
+        util::set_and_unset_color(&mut stdout, "  netrc:", &COLOR_PARAM)?;
+        writeln!(stdout, " {:?}", netrc)?;
         util::set_and_unset_color(&mut stdout, "  no-check", &COLOR_PARAM)?;
         writeln!(stdout, " {}", no_check)?;
         util::set_and_unset_color(&mut stdout, "  no-color:", &COLOR_PARAM)?;
@@ -158,8 +451,40 @@ pub fn check_paths(input_paths: &[&str], config: Option<&Config>) -> Result<Info
         writeln!(stdout, " {}", no_http)?;
         util::set_and_unset_color(&mut stdout, "  no-ignore:", &COLOR_PARAM)?;
         writeln!(stdout, " {}", no_ignore)?;
+        util::set_and_unset_color(&mut stdout, "  no-proxy:", &COLOR_PARAM)?;
+        writeln!(stdout, " {:?}", no_proxy)?;
+        util::set_and_unset_color(&mut stdout, "  notebook-code-comments:", &COLOR_PARAM)?;
+        writeln!(stdout, " {}", notebook_code_comments)?;
+        util::set_and_unset_color(&mut stdout, "  proxy:", &COLOR_PARAM)?;
+        // Proxy credentials are kept out of the output.
+        writeln!(stdout, " {:?}", proxy.as_deref().map(url::redact_userinfo))?;
+        util::set_and_unset_color(&mut stdout, "  redirects:", &COLOR_PARAM)?;
+        writeln!(stdout, " {}", redirects)?;
+        util::set_and_unset_color(&mut stdout, "  resolve:", &COLOR_PARAM)?;
+        writeln!(
+            stdout,
+            " {:?}",
+            resolves
+                .iter()
+                .map(|resolve| resolve.to_string())
+                .collect::<Vec<_>>()
+        )?;
+        util::set_and_unset_color(&mut stdout, "  schemes:", &COLOR_PARAM)?;
+        writeln!(stdout, " {:?}", schemes)?;
+        util::set_and_unset_color(&mut stdout, "  search-archives:", &COLOR_PARAM)?;
+        writeln!(stdout, " {}", search_archives)?;
+        util::set_and_unset_color(&mut stdout, "  soft-404:", &COLOR_PARAM)?;
+        writeln!(stdout, " {}", soft_404)?;
+        util::set_and_unset_color(&mut stdout, "  soft-404-patterns:", &COLOR_PARAM)?;
+        writeln!(stdout, " {:?}", soft_404_patterns)?;
+        util::set_and_unset_color(&mut stdout, "  structured-data:", &COLOR_PARAM)?;
+        writeln!(stdout, " {}", structured_data)?;
+        util::set_and_unset_color(&mut stdout, "  timeouts:", &COLOR_PARAM)?;
+        writeln!(stdout, " {}", timeouts)?;
         util::set_and_unset_color(&mut stdout, "  verbose:", &COLOR_PARAM)?;
         writeln!(stdout, " {}", verbose)?;
+        util::set_and_unset_color(&mut stdout, "  warn-status:", &COLOR_PARAM)?;
+        writeln!(stdout, " {}", warn_status)?;
     }
 
     // Initialize logic.
@@ -167,9 +492,26 @@ pub fn check_paths(input_paths: &[&str], config: Option<&Config>) -> Result<Info
     // Initialize lists.
     let mut files = if list_files { Some(vec![]) } else { None };
     let mut file_urls = vec![];
+    let mut intra_doc_links = if check_intra_doc_links {
+        Some(IntraDocLinks::default())
+    } else {
+        None
+    };
 
     // Initialize variables.
     let mut num_files = 0;
+    let mut num_binary_files = 0;
+    let mut num_undecodable_files = 0;
+
+    let options = SearchOptions {
+        check_placeholders,
+        comments_only,
+        exclude_urls: &exclude_urls,
+        no_http,
+        notebook_code_comments,
+        schemes,
+        structured_data,
+    };
 
     // Construct the file walker.
     let mut walk_builder = WalkBuilder::new(input_paths[0]);
@@ -212,23 +554,79 @@ pub fn check_paths(input_paths: &[&str], config: Option<&Config>) -> Result<Info
                 writeln!(stdout, " {}", path_str)?;
             }
 
-            // Get the URLs in this file.
-            let mut new_file_urls = match get_file_urls(path, no_http, &exclude_urls) {
-                Err(Error::Io(ref err)) if err.kind() == io::ErrorKind::InvalidData => {
-                    if verbose {
-                        util::set_and_unset_color(
-                            &mut stderr,
-                            "Warning: file did not contain valid data. Skipping.\n",
-                            &COLOR_WARN,
-                        )?;
+            let bytes = fs::read(path)?;
+            let in_archive = search_archives && is_archive(path);
+
+            // Get the files inside archives, or the file itself.
+            let entries = if in_archive {
+                match get_archive_entries(path, bytes) {
+                    Ok(entries) => entries,
+                    Err(err) => {
+                        num_undecodable_files += 1;
+
+                        if verbose {
+                            util::set_and_unset_color(
+                                &mut stderr,
+                                &format!("Warning: could not read archive: {}. Skipping.\n", err),
+                                &COLOR_WARN,
+                            )?;
+                        }
+
+                        vec![]
+                    }
+                }
+            } else {
+                vec![Entry {
+                    path: None,
+                    name: path.to_owned(),
+                    bytes,
+                }]
+            };
+
+            for entry in entries {
+                if verbose {
+                    if let Some(ref entry_path) = entry.path {
+                        util::set_and_unset_color(&mut stdout, "Parsing", &COLOR_PARSE)?;
+                        writeln!(stdout, " {}!/{}", path_str, entry_path)?;
                     }
-
-                    Ok(vec![])
                 }
-                res => res,
-            }?;
 
-            file_urls.append(&mut new_file_urls);
+                // Get the URLs in this file, as a document or as text.
+                let res = match get_document_urls(&entry.name, &entry.bytes, &options) {
+                    Some(res) => res,
+                    None => encoding::decode(&entry.bytes, encoding).map(|text| {
+                        // Intra-doc links are only resolved against the files being walked.
+                        let intra_doc_links = intra_doc_links.as_mut().filter(|_| !in_archive);
+                        get_file_urls(&entry.name, &text, &options, intra_doc_links)
+                    }),
+                };
+
+                match res {
+                    Ok(new_file_urls) => {
+                        file_urls.extend(new_file_urls.into_iter().map(|file_url| FileUrl {
+                            filepath: path.to_owned(),
+                            entry: entry.path.clone(),
+                            ..file_url
+                        }));
+                    }
+                    Err(skipped) => {
+                        let warning = match skipped {
+                            Skipped::Binary => {
+                                num_binary_files += 1;
+                                "Warning: file appears to be binary. Skipping.\n"
+                            }
+                            Skipped::Undecodable => {
+                                num_undecodable_files += 1;
+                                "Warning: file could not be decoded. Skipping.\n"
+                            }
+                        };
+
+                        if verbose {
+                            util::set_and_unset_color(&mut stderr, warning, &COLOR_WARN)?;
+                        }
+                    }
+                }
+            }
 
             if let Some(ref mut files) = files {
                 files.push(path.to_owned());
@@ -237,103 +635,353 @@ pub fn check_paths(input_paths: &[&str], config: Option<&Config>) -> Result<Info
         }
     }
 
+    // Validate intra-doc links now that all item names are known.
+    if let Some(intra_doc_links) = intra_doc_links {
+        file_urls.append(&mut intra_doc_links.resolve());
+    }
+
     // Check the list of found URLs.
 
-    let (num_urls, num_bad_urls) = check_urls(
+    let check_options = CheckOptions {
+        accept_status,
+        ca_bundle,
+        client_cert,
+        client_key,
+        host_settings,
+        ip_version,
+        max_body_size,
+        max_redirects,
+        method,
+        min_tls_version,
+        no_check,
+        no_proxy,
+        proxy,
+        redirects,
+        resolves,
+        soft_404: if soft_404 {
+            Some(soft_404_patterns.clone())
+        } else {
+            None
+        },
+        timeouts,
+        upgrade_https: fix,
+        warn_status,
+    };
+
+    let counts = check_urls(
         &mut file_urls,
         verbose,
         silent,
-        no_check,
-        timeout,
+        &check_options,
         &mut stdout,
         &mut stderr,
     )?;
 
+    // Rewrite URLs that should be replaced.
+    let num_fixed_urls = if fix {
+        fix::fix_files(&file_urls, encoding, dry_run, silent, &mut stdout)?
+    } else {
+        0
+    };
+
     let info = Info {
         file_urls,
         files,
         num_files,
-        num_urls,
-        num_bad_urls,
+        num_binary_files,
+        num_undecodable_files,
+        num_urls: counts.num_urls,
+        num_bad_urls: counts.num_bad_urls,
+        num_redirected_urls: counts.num_redirected_urls,
+        num_warned_urls: counts.num_warned_urls,
+        num_soft_404_urls: counts.num_soft_404_urls,
+        num_requests: counts.num_requests,
+        num_connections: counts.num_connections,
+        num_fixed_urls,
     };
 
     Ok(info)
 }
 
-// Gets a file's URLs.
+#[cfg(any(feature = "office", feature = "pdf"))]
+use document::file_urls as get_document_urls;
+
+// Gets the URLs in a PDF or Office document. Documents aren't supported without the `office` and
+// `pdf` features.
+#[cfg(not(any(feature = "office", feature = "pdf")))]
+fn get_document_urls(
+    _filepath: &Path,
+    _bytes: &[u8],
+    _options: &SearchOptions,
+) -> Option<result::Result<Vec<FileUrl>, Skipped>> {
+    None
+}
+
+// A file inside a compressed file or archive, or a file searched itself.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Entry {
+    // The path of the file inside the archive, e.g. `docs/intro.md`. Paths inside nested archives
+    // are joined with `!/`. Not set for the contents of a compressed file that is not an
+    // archive, such as `notes.md.gz`, or for a file searched itself.
+    path: Option<String>,
+    // The name of the file, used to detect its type, e.g. `docs/intro.md` or `notes.md`.
+    name: PathBuf,
+    // The decompressed contents of the file.
+    bytes: Vec<u8>,
+}
+
+#[cfg(feature = "archive")]
+use archive::{entries as get_archive_entries, is_archive};
+
+// Returns true if the file at `path` is a compressed file or an archive. Archives aren't searched
+// without the `archive` feature.
+#[cfg(not(feature = "archive"))]
+fn is_archive(_path: &Path) -> bool {
+    false
+}
+
+// Returns the file at `path` itself, as archives aren't searched without the `archive` feature.
+#[cfg(not(feature = "archive"))]
+fn get_archive_entries(path: &Path, bytes: Vec<u8>) -> std::io::Result<Vec<Entry>> {
+    Ok(vec![Entry {
+        path: None,
+        name: path.to_owned(),
+        bytes,
+    }])
+}
+
+// Options that control which URLs are found in a file.
+struct SearchOptions<'a> {
+    check_placeholders: &'a [Placeholder],
+    comments_only: &'a [Language],
+    exclude_urls: &'a [ExclusionPattern<'a>],
+    no_http: bool,
+    notebook_code_comments: bool,
+    schemes: &'a [Scheme],
+    structured_data: bool,
+}
+
+// Returns the reason `url` is excluded from checking, if any, other than inline suppression.
+fn get_exclusion(url: &str, options: &SearchOptions) -> Option<Exclusion> {
+    if url::is_url_excluded(url, options.exclude_urls) {
+        return Some(Exclusion::Pattern);
+    }
+
+    if let Some(placeholder) = Placeholder::of(url) {
+        if !options.check_placeholders.contains(&placeholder) {
+            return Some(Exclusion::Placeholder(placeholder));
+        }
+    }
+
+    match url::file_transfer_protocol(url) {
+        Some(protocol) if !url::is_protocol_supported(protocol) => {
+            Some(Exclusion::UnsupportedProtocol)
+        }
+        _ => None,
+    }
+}
+
+// Gets the URLs in `text`, the contents of a file. Intra-doc links in Rust files are added to
+// `intra_doc_links` if set.
 fn get_file_urls(
     filepath: &Path,
-    no_http: bool,
-    exclude_urls: &[ExclusionPattern],
-) -> Result<Vec<FileUrl>> {
+    text: &str,
+    options: &SearchOptions,
+    intra_doc_links: Option<&mut IntraDocLinks>,
+) -> Vec<FileUrl> {
+    let SearchOptions {
+        comments_only,
+        no_http,
+        notebook_code_comments,
+        schemes,
+        structured_data,
+        ..
+    } = *options;
+
     let mut file_urls = vec![];
-    let mut line_num = 1;
-
-    // Get file contents.
-    let file = File::open(filepath)?;
-    let reader = BufReader::new(file);
-
-    for line in reader.lines() {
-        for url in parse::get_urls(&line?, no_http) {
-            let excluded = url::is_url_excluded(url, exclude_urls);
-
-            file_urls.push(FileUrl {
-                url: url.to_string(),
-                filepath: filepath.to_owned(),
-                line: line_num,
-                bad: None,
-                excluded,
-            });
+    let lines = LineIndex::new(text);
+    let suppressions = Suppressions::new(text);
+    let new_file_url = |url: &str, position: (usize, usize), span: Range<usize>| FileUrl {
+        excluded: if suppressions.is_suppressed(lines.line(span.start)) {
+            Some(Exclusion::InlineSuppression)
+        } else {
+            get_exclusion(url, options)
+        },
+        ..FileUrl::new(url, filepath, position, span)
+    };
+
+    let continuations = Language::line_continuations(filepath);
+
+    // Search notebooks cell by cell, skipping outputs.
+    if notebook::is_notebook(filepath) {
+        let notebook = notebook::parse(text);
+        let language = notebook.language.unwrap_or(Language::Python);
+
+        for cell in notebook.cells {
+            let source = &cell.source.text;
+            let searched = match cell.kind {
+                CellKind::Markdown => source.clone(),
+                CellKind::Code if notebook_code_comments => {
+                    parse::keep_ranges(source, &lang::comment_ranges(source, language))
+                }
+                _ => continue,
+            };
+            let cell_lines = LineIndex::new(source);
+
+            // Lines and columns are relative to the cell, spans to the notebook file.
+            for (url, range) in parse::find_urls(&searched, no_http, false, schemes) {
+                file_urls.push(FileUrl {
+                    cell: Some(cell.index),
+                    ..new_file_url(
+                        &url,
+                        cell_lines.position(range.start),
+                        cell.source.source_range(range),
+                    )
+                });
+            }
         }
 
-        line_num += 1;
+        return file_urls;
     }
 
-    Ok(file_urls)
+    // Only search the string values of structured data files.
+    if let Some(format) = DataFormat::from_path(filepath).filter(|_| structured_data) {
+        for value in structured::values(text, format) {
+            for (url, range) in parse::find_urls(&value.text, no_http, false, schemes) {
+                let span = value.source_range(range);
+                file_urls.push(FileUrl {
+                    key_path: Some(value.key_path.clone()),
+                    ..new_file_url(&url, lines.position(span.start), span)
+                });
+            }
+        }
+
+        return file_urls;
+    }
+
+    // Blank out the parts of the file that shouldn't be searched.
+    let searched = match Language::from_path(filepath) {
+        Some(Language::Rust)
+            if comments_only.contains(&Language::Rust) || intra_doc_links.is_some() =>
+        {
+            let (searched, links) = rustdoc::comment_text(text);
+
+            if let Some(intra_doc_links) = intra_doc_links {
+                intra_doc_links.add_file(filepath, text, links);
+            }
+
+            searched
+        }
+        Some(lang) if comments_only.contains(&lang) => {
+            parse::keep_ranges(text, &lang::comment_ranges(text, lang))
+        }
+        _ => text.to_string(),
+    };
+
+    for (url, range) in parse::find_urls(&searched, no_http, continuations, schemes) {
+        file_urls.push(new_file_url(&url, lines.position(range.start), range));
+    }
+
+    file_urls
 }
 
-// Checks a list of URLs and returns the number of unique URLs processed, and the number of bad
-// URLs.
+// Options that control how URLs are checked.
+struct CheckOptions {
+    accept_status: StatusCodes,
+    ca_bundle: Option<PathBuf>,
+    client_cert: Option<PathBuf>,
+    client_key: Option<PathBuf>,
+    host_settings: Vec<HostSettings>,
+    ip_version: Option<IpVersion>,
+    max_body_size: usize,
+    max_redirects: usize,
+    method: MethodStrategy,
+    min_tls_version: Option<TlsVersion>,
+    no_check: bool,
+    no_proxy: Vec<String>,
+    proxy: Option<String>,
+    redirects: RedirectPolicy,
+    // The addresses to connect to instead of resolving host names.
+    resolves: Vec<Resolve>,
+    // The patterns to detect soft 404s with, if detecting them.
+    soft_404: Option<Vec<String>>,
+    timeouts: Timeouts,
+    upgrade_https: bool,
+    warn_status: StatusCodes,
+}
+
+// What was found while checking a URL, other than whether it is bad.
+#[derive(Default)]
+struct Checked {
+    // The redirects followed, in order.
+    redirects: Vec<Redirect>,
+    // The status of the final response, for URLs checked over HTTP.
+    status: Option<Status>,
+    // Why the final page is suspected to be a soft 404, if it is.
+    soft_404: Option<Soft404>,
+    // The timeout that expired, if a request timed out.
+    timeout: Option<TimeoutPhase>,
+}
+
+// The numbers of distinct URLs found by `check_urls`.
+#[derive(Default)]
+struct Counts {
+    num_urls: u64,
+    num_bad_urls: u64,
+    num_redirected_urls: u64,
+    num_warned_urls: u64,
+    num_soft_404_urls: u64,
+    num_requests: u64,
+    num_connections: u64,
+    // The number of distinct hosts requested, by scheme, host and port.
+    num_hosts: u64,
+}
+
+// Checks a list of URLs and returns the number of unique URLs processed, and of those that are bad
+// or have warnings.
 fn check_urls(
-    file_urls: &mut Vec<FileUrl>,
+    file_urls: &mut [FileUrl],
     verbose: bool,
     silent: bool,
-    no_check: bool,
-    timeout: Option<u64>,
+    options: &CheckOptions,
     mut stdout: &mut StandardStream,
     mut stderr: &mut StandardStream,
-) -> Result<(u64, u64)> {
-    let mut num_urls = 0;
-    let mut num_bad_urls = 0;
-
-    // Sort the list first. We won't check the same URL twice.
-    // TODO: Sort by host first, so that we can keep the same connection alive.
-    file_urls.sort();
+) -> Result<Counts> {
+    let no_check = options.no_check;
+
+    let mut counts = Counts::default();
+
+    // Sort the list first. We won't check the same URL twice, comparing URLs by their ASCII form
+    // so that e.g. internationalized domain names and their punycode match. URLs are grouped by
+    // scheme, host and port first, so that the connection to a host is reused for all its URLs.
+    file_urls.sort_by_cached_key(|file_url| {
+        let ascii_url = url::normalize(&file_url.url);
+        (url::origin(&ascii_url), ascii_url, file_url.clone())
+    });
+    let normalized: Vec<String> = file_urls
+        .iter()
+        .map(|file_url| url::normalize(&file_url.url))
+        .collect();
 
     // Get a count of unique URLs.
 
-    let mut prev_file_url: Option<&FileUrl> = None;
-    for file_url in file_urls.iter() {
-        match prev_file_url {
-            Some(prev_file_url) => {
-                if prev_file_url.url != file_url.url {
-                    num_urls += 1;
-                }
-            }
-            None => num_urls += 1,
+    let mut prev_url: Option<&String> = None;
+    for url in normalized.iter() {
+        if prev_url != Some(url) {
+            counts.num_urls += 1;
         }
 
-        prev_file_url = Some(file_url);
+        prev_url = Some(url);
     }
 
     // TODO: refactor and move this to main.
-    if num_urls > 0 {
+    if counts.num_urls > 0 {
         util::set_and_unset_color(
             &mut stdout,
             &format!(
                 "\nChecking {} unique {}.\n\n",
-                num_urls,
-                if num_urls == 1 { "URL" } else { "URLs" }
+                counts.num_urls,
+                if counts.num_urls == 1 { "URL" } else { "URLs" }
             ),
             &COLOR_INFO,
         )?;
@@ -342,29 +990,49 @@ fn check_urls(
     // Begin logic.
 
     // Create the connection handle.
-    let mut handle = init_handle(timeout)?;
+    let mut handle = init_handle(options)?;
+    let mut detector = match options.soft_404 {
+        Some(ref patterns) => Some(Detector::new(patterns)?),
+        None => None,
+    };
 
-    let mut prev_file_url: Option<&mut FileUrl> = None;
-    for mut file_url in file_urls {
+    let mut prev_origin = None;
+    let mut prev_file_url: Option<(&String, &mut FileUrl)> = None;
+    for (file_url, ascii_url) in file_urls.iter_mut().zip(normalized.iter()) {
         let url = &file_url.url;
 
-        // If the previous URL was the same, reuse the `bad` value.
+        // If the previous URL was the same, reuse the `bad` value, redirects, status, soft 404,
+        // timeout and replacement. Excluded URLs neither reuse nor provide these, since inline
+        // suppression can exclude one occurrence of a URL but not another.
         // TODO: Only display check if the previous URL and file weren't the same.
         let mut prev_bad = None;
+        let mut details = Checked::default();
+        let mut replacement = None;
         let mut checked = false;
-        if let Some(prev_file_url) = prev_file_url {
-            if prev_file_url.url == *url {
+        if let Some((prev_ascii_url, prev_file_url)) = &prev_file_url {
+            if *prev_ascii_url == ascii_url && file_url.excluded.is_none() {
                 prev_bad = Some(prev_file_url.bad);
+                details.redirects = prev_file_url.redirects.clone();
+                details.status = prev_file_url.status.clone();
+                details.soft_404 = prev_file_url.soft_404.clone();
+                details.timeout = prev_file_url.timeout;
+                replacement = prev_file_url.replacement.clone();
                 checked = true;
             }
         }
 
         // Print action message.
         if verbose {
-            if no_check {
+            if file_url.intra_doc {
+                util::set_and_unset_color(stdout, "Resolved (intra-doc link)", &COLOR_CHECK)?;
+            } else if no_check {
                 util::set_and_unset_color(&mut stdout, "Not checking", &COLOR_WARN)?;
-            } else if file_url.excluded {
-                util::set_and_unset_color(&mut stdout, "Skipping (excluded)", &COLOR_WARN)?;
+            } else if let Some(exclusion) = file_url.excluded {
+                util::set_and_unset_color(
+                    stdout,
+                    &format!("Skipping (excluded: {})", exclusion),
+                    &COLOR_WARN,
+                )?;
             } else if checked {
                 util::set_and_unset_color(&mut stdout, "Skipping (checked)", &COLOR_WARN)?;
             } else {
@@ -372,7 +1040,11 @@ fn check_urls(
             }
             write!(stdout, " ")?;
 
-            writeln!(stdout, "{}", url)?;
+            if ascii_url == url {
+                writeln!(stdout, "{}", url)?;
+            } else {
+                writeln!(stdout, "{} ({})", url, ascii_url)?;
+            }
             util::set_and_unset_color(&mut stdout, &file_url.file_ref(), &COLOR_PARAM)?;
 
             writeln!(stdout)?;
@@ -385,13 +1057,34 @@ fn check_urls(
                 prev_bad,
                 Some("Previous bad URL was identical.".to_string()),
             )
-        } else if no_check || file_url.excluded {
+        } else if file_url.intra_doc {
+            // Intra-doc links are resolved before checking.
+            (file_url.bad, Some("Unresolved intra-doc link.".to_string()))
+        } else if no_check || file_url.excluded.is_some() {
             (None, None)
+        } else if let Some(scheme) = Scheme::of(url) {
+            // URLs with other schemes are validated offline.
+            match scheme.validate(url) {
+                Some(message) => (Some(true), Some(message)),
+                None => (Some(false), None),
+            }
         } else {
             // Check the URL.
-            match url_is_bad(&mut handle, &url, true)? {
+            let origin = url::origin(ascii_url);
+            if prev_origin.as_ref() != Some(&origin) {
+                counts.num_hosts += 1;
+                prev_origin = Some(origin);
+            }
+
+            match url_is_bad(&mut handle, ascii_url, options, &mut details)? {
                 Some(message) => (Some(true), Some(message)),
-                None => (Some(false), None),
+                None => {
+                    if let Some(ref mut detector) = detector {
+                        details.soft_404 =
+                            detect_soft_404(&mut handle, detector, ascii_url, options, &details)?;
+                    }
+                    (Some(false), None)
+                }
             }
         };
 
@@ -409,71 +1102,598 @@ fn check_urls(
                 }
             }
 
-            num_bad_urls += 1;
+            counts.num_bad_urls += 1;
+        }
+
+        // Warn about good URLs whose status code is only a warning.
+        if let Some(status) = details
+            .status
+            .as_ref()
+            .filter(|status| status.verdict == Verdict::Warning)
+        {
+            if !silent {
+                util::set_and_unset_color(stderr, "Warning url: ", &COLOR_WARN)?;
+                writeln!(stderr, "{}", url)?;
+                util::set_and_unset_color(stderr, &file_url.file_ref(), &COLOR_PARAM)?;
+                writeln!(stderr)?;
+                writeln!(stderr, "Response code: {}", status)?;
+            }
+
+            if !checked {
+                counts.num_warned_urls += 1;
+            }
+        }
+
+        // Warn about good URLs that are suspected to be soft 404s.
+        if let Some(ref soft_404) = details.soft_404 {
+            if !silent {
+                util::set_and_unset_color(stderr, "Soft 404 url: ", &COLOR_WARN)?;
+                writeln!(stderr, "{}", url)?;
+                util::set_and_unset_color(stderr, &file_url.file_ref(), &COLOR_PARAM)?;
+                writeln!(stderr)?;
+                writeln!(stderr, "Suspected because {}.", soft_404)?;
+            }
+
+            if !checked {
+                counts.num_soft_404_urls += 1;
+            }
+        }
+
+        // Warn about good URLs that redirect, with the URL they should be updated to.
+        let redirects = &details.redirects;
+        let permanent_target = redirect::permanent_target(redirects);
+        let target = match options.redirects {
+            RedirectPolicy::Warn => redirects.last().map(|redirect| redirect.url.as_str()),
+            _ => permanent_target,
+        };
+
+        if let (Some(false), Some(target)) = (bad, target) {
+            if !silent {
+                let warning = if permanent_target.is_some() {
+                    "Moved url: "
+                } else {
+                    "Redirected url: "
+                };
+
+                util::set_and_unset_color(stderr, warning, &COLOR_WARN)?;
+                writeln!(stderr, "{}", url)?;
+                util::set_and_unset_color(stderr, &file_url.file_ref(), &COLOR_PARAM)?;
+                writeln!(stderr)?;
+                writeln!(stderr, "Final URL: {}", target)?;
+
+                if verbose {
+                    for redirect in redirects.iter() {
+                        writeln!(stderr, "  {} {}", redirect.status, redirect.url)?;
+                    }
+                }
+            }
+
+            if !checked {
+                counts.num_redirected_urls += 1;
+            }
         }
 
-        // Set the `bad` field.
+        // Find the URL to replace a good URL with: the URL it moved to, or when fixing, its
+        // `https://` equivalent if that is good too.
+        if !checked && bad == Some(false) {
+            replacement = match (permanent_target, url::https_equivalent(url)) {
+                (Some(target), _) => Some(url::with_fragment(target, url)),
+                (None, Some(https_url)) if options.upgrade_https => {
+                    let ascii_https_url = url::normalize(&https_url);
+                    let mut https_details = Checked::default();
+                    match url_is_bad(&mut handle, &ascii_https_url, options, &mut https_details)? {
+                        Some(_) => None,
+                        None => Some(https_url),
+                    }
+                }
+                _ => None,
+            }
+            .filter(|replacement| replacement != url);
+        }
+
+        // Set the `bad`, `redirects`, `status`, `soft_404`, `timeout` and `replacement` fields.
         file_url.bad = bad;
+        file_url.redirects = details.redirects;
+        file_url.status = details.status;
+        file_url.soft_404 = details.soft_404;
+        file_url.timeout = details.timeout;
+        file_url.replacement = replacement;
+
+        if file_url.excluded.is_none() {
+            prev_file_url = Some((ascii_url, file_url));
+        }
+    }
+
+    // Print how well connections were reused.
+    counts.num_requests = handle.get_ref().num_requests;
+    counts.num_connections = handle.get_ref().num_connections;
 
-        prev_file_url = Some(file_url);
+    if verbose && counts.num_requests > 0 {
+        util::set_and_unset_color(
+            stdout,
+            &format!(
+                "\nSent {} requests to {} hosts over {} connections ({} reused).\n",
+                counts.num_requests,
+                counts.num_hosts,
+                counts.num_connections,
+                counts.num_requests.saturating_sub(counts.num_connections)
+            ),
+            &COLOR_INFO,
+        )?;
     }
 
-    Ok((num_urls, num_bad_urls))
+    Ok(counts)
 }
 
-struct Collector(Vec<u8>);
+// Returns why the good page of `url` just fetched with `handle` is suspected to be a soft 404, if
+// it is. Only pages answered with a 2xx status code are considered.
+fn detect_soft_404(
+    handle: &mut Easy2<Collector>,
+    detector: &mut Detector,
+    url: &str,
+    options: &CheckOptions,
+    details: &Checked,
+) -> Result<Option<Soft404>> {
+    match details.status {
+        Some(ref status) if (200..300).contains(&status.code) => (),
+        _ => return Ok(None),
+    }
+
+    let final_url = details
+        .redirects
+        .last()
+        .map_or(url, |redirect| redirect.url.as_str());
+    let body = mem::take(&mut handle.get_mut().body);
+
+    detector.detect(final_url, &body, |missing_url| {
+        let mut missing_details = Checked::default();
+        let bad = url_is_bad(handle, missing_url, options, &mut missing_details)?;
+
+        Ok(match missing_details.status {
+            Some(ref status) if bad.is_none() && (200..300).contains(&status.code) => {
+                Some(mem::take(&mut handle.get_mut().body))
+            }
+            _ => None,
+        })
+    })
+}
+
+// Collects the body of a response, up to a maximum size, and counts the requests sent and
+// connections opened by the handle.
+struct Collector {
+    body: Vec<u8>,
+    max_size: usize,
+    // Set if the transfer was stopped because the body was too large.
+    truncated: bool,
+    // Set once the first byte of the response is received.
+    first_byte: bool,
+    // When to stop the transfer if no byte of the response was received, for transfers whose
+    // total timeout can't stand in for the first byte timeout.
+    first_byte_deadline: Option<Instant>,
+    num_requests: u64,
+    num_connections: u64,
+}
+
+impl Collector {
+    // Empties the collector before a request.
+    fn reset(&mut self) {
+        self.body.clear();
+        self.truncated = false;
+        self.first_byte = false;
+    }
+}
 
 impl Handler for Collector {
     fn write(&mut self, data: &[u8]) -> result::Result<usize, WriteError> {
-        self.0.extend_from_slice(data);
+        let room = self.max_size - self.body.len();
+
+        // Keep what fits and stop the transfer, which curl does when fewer bytes are taken.
+        if data.len() > room {
+            self.body.extend_from_slice(&data[..room]);
+            self.truncated = true;
+            return Ok(0);
+        }
+
+        self.body.extend_from_slice(data);
         Ok(data.len())
     }
+
+    fn header(&mut self, _: &[u8]) -> bool {
+        self.first_byte = true;
+        true
+    }
+
+    fn progress(&mut self, _: f64, _: f64, _: f64, _: f64) -> bool {
+        match self.first_byte_deadline {
+            Some(deadline) => self.first_byte || Instant::now() < deadline,
+            None => true,
+        }
+    }
 }
 
 // Initialize the curl handle which will be reused between calls.
-fn init_handle(timeout: Option<u64>) -> Result<Easy2<Collector>> {
-    let mut handle = Easy2::new(Collector(Vec::new()));
+fn init_handle(options: &CheckOptions) -> Result<Easy2<Collector>> {
+    let mut handle = Easy2::new(Collector {
+        body: Vec::new(),
+        max_size: options.max_body_size,
+        truncated: false,
+        first_byte: false,
+        first_byte_deadline: None,
+        num_requests: 0,
+        num_connections: 0,
+    });
 
     handle.useragent(&USER_AGENT)?;
-    if let Some(timeout) = timeout {
-        handle.timeout(Duration::from_secs(timeout))?;
+
+    // Prefer HTTP/2 over TLS where libcurl supports it, so that each host's requests share a
+    // single connection.
+    if Version::get().feature_http2() {
+        handle.http_version(HttpVersion::V2TLS)?;
+    }
+
+    // Set the proxy and TLS options. Those not set are left to curl's defaults, such as the proxy
+    // environment variables.
+    if let Some(ref proxy) = options.proxy {
+        handle.proxy(proxy)?;
+    }
+    if !options.no_proxy.is_empty() {
+        handle.noproxy(&options.no_proxy.join(","))?;
+    }
+    if let Some(ref ca_bundle) = options.ca_bundle {
+        handle.cainfo(ca_bundle)?;
+    }
+    if let Some(ref client_cert) = options.client_cert {
+        handle.ssl_cert(client_cert)?;
+    }
+    if let Some(ref client_key) = options.client_key {
+        handle.ssl_key(client_key)?;
+    }
+    if let Some(min_tls_version) = options.min_tls_version {
+        handle.ssl_version(min_tls_version.ssl_version())?;
+    }
+
+    // Connect to the addresses given instead of resolving host names, with the given IP version
+    // only. curl's resolve list takes addresses by port, and its connect-to list is used for
+    // those of all ports.
+    if let Some(ip_version) = options.ip_version {
+        handle.ip_resolve(match ip_version {
+            IpVersion::V4 => IpResolve::V4,
+            IpVersion::V6 => IpResolve::V6,
+        })?;
+    }
+    let (resolve_list, connect_to_list) =
+        resolve::curl_lists(&options.resolves, options.ip_version);
+    if !resolve_list.is_empty() {
+        handle.resolve(curl_list(&resolve_list)?)?;
+    }
+    if !connect_to_list.is_empty() {
+        handle.connect_to(curl_list(&connect_to_list)?)?;
     }
 
     Ok(handle)
 }
 
-// Return `Some(error_message)` if the URL is bad.
-// TODO: add option for following/prohibiting redirects?
-fn url_is_bad(handle: &mut Easy2<Collector>, url: &str, initial: bool) -> Result<Option<String>> {
-    if initial {
-        handle.url(url)?;
+// Return `Some(error_message)` if the URL is bad. Redirects are followed here rather than by curl,
+// according to the redirect policy, and added to `details` along with the final status.
+fn url_is_bad(
+    handle: &mut Easy2<Collector>,
+    url: &str,
+    options: &CheckOptions,
+    details: &mut Checked,
+) -> Result<Option<String>> {
+    // Apply the timeouts of the URL's host. The connect timeout also covers resolving the host
+    // name, unless the name is resolved here first for a separate DNS timeout. Names aren't
+    // resolved when connecting through a proxy, which resolves them.
+    let timeouts = host::timeouts(&options.host_settings, url, options.timeouts);
+    handle.connect_timeout(timeouts.connect.unwrap_or_default())?;
+    if let (Some(dns_timeout), None) = (timeouts.dns, &options.proxy) {
+        if !resolve_host(handle, url, dns_timeout, options)? {
+            details.timeout = Some(TimeoutPhase::Dns);
+            return Ok(Some(timed_out(TimeoutPhase::Dns, &timeouts)));
+        }
     }
 
-    // Try a HEAD request first, followed by GET if that fails, as not all servers are
-    // configured for HEAD.
-    if initial {
-        handle.get(false)?;
-        handle.nobody(true)?;
+    if url::file_transfer_protocol(url).is_some() {
+        return file_transfer_url_is_bad(handle, url, &timeouts, details);
+    }
+
+    handle.url(url)?;
+
+    // Only skip verifying the TLS certificates of hosts marked insecure.
+    let verify = !host::is_insecure(&options.host_settings, url);
+    handle.ssl_verify_peer(verify)?;
+    handle.ssl_verify_host(verify)?;
+
+    // Request the URL with the methods of the strategy. Pages are also requested with GET when
+    // detecting soft 404s, which needs their bodies.
+    let needs_body = options.soft_404.is_some();
+    let mut result = match options.method {
+        MethodStrategy::Get => request(handle, url, true, &timeouts, options)?,
+        _ => request(handle, url, false, &timeouts, options)?,
+    };
+    let retry = match (options.method, &result) {
+        (MethodStrategy::Get, _) => false,
+        (_, &Ok(200..=299)) => needs_body,
+        (MethodStrategy::Head, _) => false,
+        (_, &Ok(200..=399)) => false,
+        _ => true,
+    };
+    if retry {
+        result = request(handle, url, true, &timeouts, options)?;
+    }
+
+    let code = match result {
+        Ok(code) => code,
+        Err(e) => {
+            details.timeout = expired_timeout(handle, &e, &timeouts)?;
+            return Ok(Some(match details.timeout {
+                Some(phase) => timed_out(phase, &timeouts),
+                None => e.to_string(),
+            }));
+        }
+    };
+
+    if let (300..=399, Some(target)) = (code, handle.redirect_url()?) {
+        let target = target.to_string();
+        let redirects = &mut details.redirects;
+        redirects.push(Redirect {
+            status: code,
+            url: target.clone(),
+        });
+
+        if options.redirects == RedirectPolicy::Forbid {
+            return Ok(Some(format!("Redirected ({}) to {}", code, target)));
+        }
+        if redirects.len() > options.max_redirects {
+            return Ok(Some(format!(
+                "Too many redirects: more than {}",
+                options.max_redirects
+            )));
+        }
+
+        url_is_bad(handle, &target, options, details)
     } else {
+        // Decide what the status code means, with the rules of the final URL's host.
+        let hosts = host::configs(&options.host_settings, url);
+        let status = status::decide(code, &hosts, &options.accept_status, &options.warn_status);
+        let bad = status.verdict == Verdict::Bad;
+        let message = format!("Response code: {}", status);
+        details.status = Some(status);
+
+        Ok(if bad { Some(message) } else { None })
+    }
+}
+
+// Sends a GET or HEAD request to `url`, which the handle is set to, and returns the status code
+// or the error of the transfer. GET requests only ask for the first byte of the body, unless
+// soft 404s are detected, and are sent again for the whole body if the server can't serve it.
+fn request(
+    handle: &mut Easy2<Collector>,
+    url: &str,
+    get: bool,
+    timeouts: &Timeouts,
+    options: &CheckOptions,
+) -> Result<result::Result<u32, curl::Error>> {
+    let ranged = get && options.soft_404.is_none();
+    let code = request_range(handle, url, get, ranged, timeouts, options)?;
+
+    Ok(match code {
+        // Range Not Satisfiable, e.g. for an empty file.
+        Ok(416) if ranged => request_range(handle, url, get, false, timeouts, options)?,
+        code => code,
+    })
+}
+
+// Sends a GET or HEAD request to `url`, for the first byte only if `ranged`.
+fn request_range(
+    handle: &mut Easy2<Collector>,
+    url: &str,
+    get: bool,
+    ranged: bool,
+    timeouts: &Timeouts,
+    options: &CheckOptions,
+) -> Result<result::Result<u32, curl::Error>> {
+    // Send the headers of the URL's host only, including when redirected from another host.
+    let mut headers = List::new();
+    for header in host::headers(&options.host_settings, url) {
+        headers.append(&header)?;
+    }
+    if ranged {
+        headers.append("Range: bytes=0-0")?;
+    }
+    handle.http_headers(headers)?;
+
+    if get {
         handle.nobody(false)?;
         handle.get(true)?;
+    } else {
+        handle.get(false)?;
+        handle.nobody(true)?;
     }
 
-    match handle.perform() {
+    // Responses without a body, or with a single byte of it, end right after their first byte,
+    // so their first byte timeout is set as curl's timeout. Otherwise, the transfer is stopped by
+    // the collector if the first byte doesn't come in time.
+    let whole_body = get && !ranged;
+    let timeout = if whole_body {
+        timeouts.total
+    } else {
+        timeouts
+            .first_of(&[TimeoutPhase::FirstByte, TimeoutPhase::Total])
+            .and_then(|phase| timeouts.get(phase))
+    };
+    let first_byte_deadline = timeouts
+        .first_byte
+        .filter(|_| whole_body)
+        .map(|timeout| Instant::now() + timeout);
+    handle.timeout(timeout.unwrap_or_default())?;
+    handle.progress(first_byte_deadline.is_some())?;
+
+    // Only keep the body of the last response.
+    let collector = handle.get_mut();
+    collector.reset();
+    collector.first_byte_deadline = first_byte_deadline;
+
+    match perform(handle) {
         Ok(_) => (),
-        Err(e) => return Ok(Some(e.to_string())),
+        // The transfer was stopped at the maximum body size, after the status code was received.
+        Err(ref e) if e.is_write_error() && handle.get_ref().truncated => (),
+        Err(e) => return Ok(Err(e)),
     }
 
-    let code = handle.response_code()?;
-    let bad = code < 200 || code >= 400;
+    Ok(Ok(handle.response_code()?))
+}
+
+// Resolves the host name of `url` within `timeout`, and has curl connect to the addresses found.
+// Returns false if resolving took longer. Names that can't be resolved are left to curl to report,
+// and names with addresses given aren't resolved.
+fn resolve_host(
+    handle: &mut Easy2<Collector>,
+    url: &str,
+    timeout: Duration,
+    options: &CheckOptions,
+) -> Result<bool> {
+    let url = match ::url::Url::parse(url) {
+        Ok(url) => url,
+        Err(_) => return Ok(true),
+    };
+    let (host, port) = match (url.host(), url.port_or_known_default()) {
+        (Some(::url::Host::Domain(host)), Some(port)) => (host, port),
+        _ => return Ok(true),
+    };
+    if options
+        .resolves
+        .iter()
+        .any(|resolve| resolve.matches(host, port))
+    {
+        return Ok(true);
+    }
+
+    match timeout::resolve(host, port, timeout) {
+        Some(Ok(addrs)) => {
+            // Setting the resolve list replaces the one set by `init_handle`.
+            let mut resolves = options.resolves.clone();
+            resolves.push(Resolve {
+                host: host.to_string(),
+                port: Some(port),
+                addrs: addrs.iter().map(|addr| addr.ip()).collect(),
+            });
+            let (resolve_list, _) = resolve::curl_lists(&resolves, options.ip_version);
+            handle.resolve(curl_list(&resolve_list)?)?;
+            Ok(true)
+        }
+        Some(Err(_)) => Ok(true),
+        None => Ok(false),
+    }
+}
+
+// Returns a list of strings to set as a curl option.
+fn curl_list(entries: &[String]) -> Result<List> {
+    let mut list = List::new();
+    for entry in entries {
+        list.append(entry)?;
+    }
+
+    Ok(list)
+}
+
+// Returns the timeout that expired, if the request that failed with `e` timed out. This is the
+// first to expire of the timeouts of the phase the request was in.
+fn expired_timeout(
+    handle: &mut Easy2<Collector>,
+    e: &curl::Error,
+    timeouts: &Timeouts,
+) -> Result<Option<TimeoutPhase>> {
+    use TimeoutPhase::*;
+
+    // Only the collector stops transfers, when the first byte is late.
+    if e.is_aborted_by_callback() {
+        return Ok(Some(FirstByte));
+    }
+    if !e.is_operation_timedout() {
+        return Ok(None);
+    }
 
-    if initial {
-        url_is_bad(handle, url, false)
+    Ok(if handle.connect_time()? == Duration::ZERO {
+        // curl's default connect timeout applies if none is set.
+        timeouts.first_of(&[Connect, Total]).or(Some(Connect))
+    } else if !handle.get_ref().first_byte {
+        timeouts.first_of(&[FirstByte, Total])
     } else {
-        Ok(if bad {
-            Some(format!("Response code: {}", code))
-        } else {
-            None
-        })
+        timeouts.first_of(&[Total])
+    })
+}
+
+// Returns the message of a URL whose `phase` timeout expired.
+fn timed_out(phase: TimeoutPhase, timeouts: &Timeouts) -> String {
+    match timeouts.get(phase) {
+        Some(timeout) => format!(
+            "Timed out: the {} timeout of {} ms expired",
+            phase,
+            timeout.as_millis()
+        ),
+        None => format!("Timed out: the {} timeout expired", phase),
     }
 }
+
+// Performs a request, counting it and the connections it opened.
+fn perform(handle: &mut Easy2<Collector>) -> result::Result<(), curl::Error> {
+    let result = handle.perform();
+    let num_connections = handle.num_connects().unwrap_or(0);
+
+    let collector = handle.get_mut();
+    collector.num_requests += 1;
+    collector.num_connections += num_connections;
+
+    result
+}
+
+// Return `Some(error_message)` if the FTP or SFTP URL is bad. There are no HTTP status codes here:
+// the URL is good if the login succeeds and the file or directory exists.
+fn file_transfer_url_is_bad(
+    handle: &mut Easy2<Collector>,
+    url: &str,
+    timeouts: &Timeouts,
+    details: &mut Checked,
+) -> Result<Option<String>> {
+    use TimeoutPhase::*;
+
+    handle.url(url)?;
+
+    // Don't download the file. Over FTP, libcurl then checks that a file exists with SIZE, and that
+    // a directory exists with CWD.
+    handle.get(false)?;
+    handle.nobody(true)?;
+
+    // Only the connect and total timeouts apply, as there is no response to wait for the first
+    // byte of. Clear what earlier HTTP requests left behind.
+    handle.timeout(timeouts.total.unwrap_or_default())?;
+    handle.progress(false)?;
+    let collector = handle.get_mut();
+    collector.reset();
+    collector.first_byte_deadline = None;
+
+    // FTP reply codes from 400 up are negative. SFTP has no reply codes, so the code is 0.
+    let result = perform(handle);
+    let code = handle.response_code()?;
+
+    Ok(match result {
+        Err(ref e) if e.is_operation_timedout() => {
+            // Connecting lasts until the server's greeting, which is passed to the collector as
+            // a header. curl's default connect timeout applies if none is set.
+            details.timeout = if handle.get_ref().first_byte {
+                timeouts.first_of(&[Total])
+            } else {
+                timeouts.first_of(&[Connect, Total]).or(Some(Connect))
+            };
+            Some(match details.timeout {
+                Some(phase) => timed_out(phase, timeouts),
+                None => e.to_string(),
+            })
+        }
+        Err(e) if code >= 400 => Some(format!("{} (reply code: {})", e, code)),
+        Err(e) => Some(e.to_string()),
+        Ok(_) if code >= 400 => Some(format!("Reply code: {}", code)),
+        Ok(_) => None,
+    })
+}
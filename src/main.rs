@@ -3,18 +3,18 @@
 #![forbid(unsafe_code)]
 
 mod cli;
-mod error;
 mod util;
 
-use crate::{cli::Cli, error::Result};
+use crate::cli::Cli;
 use lazy_static::lazy_static;
-use loch;
+use loch::{self, Result};
 use std::{io::Write, process};
 use termcolor::{Color, ColorSpec, WriteColor};
 
 // Define colors.
 lazy_static! {
     static ref COLOR_GOOD: ColorSpec = util::define_color(Color::Green, true);
+    static ref COLOR_WARN: ColorSpec = util::define_color(Color::Yellow, true);
     static ref COLOR_ERR: ColorSpec = util::define_color(Color::Red, true);
 }
 
@@ -45,6 +45,51 @@ fn main() -> Result<()> {
         Ok(info) => {
             writeln!(&mut stdout)?;
 
+            if info.num_binary_files > 0 || info.num_undecodable_files > 0 {
+                util::set_and_unset_color(
+                    &mut stdout,
+                    &format!(
+                        "Skipped {} binary and {} undecodable files.\n",
+                        info.num_binary_files, info.num_undecodable_files
+                    ),
+                    &COLOR_WARN,
+                )?;
+            }
+
+            if info.num_redirected_urls > 0 {
+                util::set_and_unset_color(
+                    &mut stdout,
+                    &format!("({}) redirected URLs found.\n", info.num_redirected_urls),
+                    &COLOR_WARN,
+                )?;
+            }
+
+            if info.num_warned_urls > 0 {
+                util::set_and_unset_color(
+                    &mut stdout,
+                    &format!("({}) URLs with warnings found.\n", info.num_warned_urls),
+                    &COLOR_WARN,
+                )?;
+            }
+
+            if info.num_soft_404_urls > 0 {
+                util::set_and_unset_color(
+                    &mut stdout,
+                    &format!("({}) suspected soft 404s found.\n", info.num_soft_404_urls),
+                    &COLOR_WARN,
+                )?;
+            }
+
+            if info.num_fixed_urls > 0 {
+                let fixed = if config.dry_run {
+                    format!("({}) URLs to fix.\n", info.num_fixed_urls)
+                } else {
+                    format!("({}) URLs fixed.\n", info.num_fixed_urls)
+                };
+
+                util::set_and_unset_color(&mut stdout, &fixed, &COLOR_GOOD)?;
+            }
+
             if info.num_bad_urls > 0 {
                 util::set_and_unset_color(
                     &mut stdout,
@@ -0,0 +1,161 @@
+//! Markdown link parsing.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::ops::Range;
+
+/// The syntax used by a Markdown link.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LinkKind {
+    /// `[text](destination)`.
+    Inline,
+    /// `[label]: destination`, possibly with the destination on the next line.
+    Definition,
+    /// `[text][label]` or `[label][]`.
+    Reference,
+    /// `[label]`.
+    Shortcut,
+}
+
+/// A link in a Markdown document.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Link {
+    /// The syntax of the link.
+    pub kind: LinkKind,
+    /// The byte range of the link text or label.
+    pub label: Range<usize>,
+    /// The byte range of the destination for inline links and definitions, or of the label
+    /// otherwise.
+    pub target: Range<usize>,
+}
+
+/// Returns all links in `text`, in order.
+pub fn links(text: &str) -> Vec<Link> {
+    lazy_static! {
+        // Link text, allowing one level of nested brackets.
+        static ref TEXT: &'static str = r"\[((?:[^\[\]\n]|\[[^\[\]\n]*\])*)\]";
+        static ref INLINE: Regex = Regex::new(
+            &(TEXT.to_string()
+                + r#"\([ \t]*<?([^\s()<>]*(?:\([^\s()]*\)[^\s()<>]*)*)>?"#
+                + r#"(?:[ \t]+(?:"[^"]*"|'[^']*'))?[ \t]*\)"#)
+        )
+        .unwrap();
+        static ref DEFINITION: Regex =
+            Regex::new(r"(?m)^[ \t]*\[([^\[\]\n]+)\]:[ \t]*\n?[ \t]*<?([^\s<>]+)>?").unwrap();
+        static ref REFERENCE: Regex =
+            Regex::new(&(TEXT.to_string() + r"\[([^\[\]\n]*)\]")).unwrap();
+        static ref SHORTCUT: Regex = Regex::new(r"\[([^\[\]\n]+)\]").unwrap();
+    }
+
+    let mut links = vec![];
+    // Spans of every link found so far, so that shortcut links aren't found inside other links.
+    let mut spans: Vec<Range<usize>> = vec![];
+
+    for cap in DEFINITION.captures_iter(text) {
+        spans.push(cap.get(0).unwrap().range());
+        links.push(Link {
+            kind: LinkKind::Definition,
+            label: cap.get(1).unwrap().range(),
+            target: cap.get(2).unwrap().range(),
+        });
+    }
+
+    for cap in INLINE.captures_iter(text) {
+        spans.push(cap.get(0).unwrap().range());
+        links.push(Link {
+            kind: LinkKind::Inline,
+            label: cap.get(1).unwrap().range(),
+            target: cap.get(2).unwrap().range(),
+        });
+    }
+
+    for cap in REFERENCE.captures_iter(text) {
+        let whole = cap.get(0).unwrap().range();
+        if spans.iter().any(|span| overlaps(span, &whole)) {
+            continue;
+        }
+
+        // A collapsed reference (`[label][]`) uses the link text as the label.
+        let label = cap.get(2).unwrap();
+        let label = if label.as_str().trim().is_empty() {
+            cap.get(1).unwrap().range()
+        } else {
+            label.range()
+        };
+
+        spans.push(whole);
+        links.push(Link {
+            kind: LinkKind::Reference,
+            label: label.clone(),
+            target: label,
+        });
+    }
+
+    for cap in SHORTCUT.captures_iter(text) {
+        let whole = cap.get(0).unwrap().range();
+        if spans.iter().any(|span| overlaps(span, &whole)) {
+            continue;
+        }
+
+        let label = cap.get(1).unwrap().range();
+
+        links.push(Link {
+            kind: LinkKind::Shortcut,
+            label: label.clone(),
+            target: label,
+        });
+    }
+
+    links.sort_by_key(|link| link.target.start);
+    links
+}
+
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn targets(text: &str) -> Vec<(LinkKind, &str)> {
+        links(text)
+            .into_iter()
+            .map(|link| (link.kind, &text[link.target]))
+            .collect()
+    }
+
+    #[test]
+    fn parse_links() {
+        use LinkKind::*;
+
+        assert_eq!(
+            targets("See [the docs](https://docs.rs \"Docs\") and [`Config`](crate::Config)."),
+            vec![(Inline, "https://docs.rs"), (Inline, "crate::Config")]
+        );
+        assert_eq!(
+            targets("[a][b], [c][], [`d`] and [e](<f>)"),
+            vec![
+                (Reference, "b"),
+                (Reference, "c"),
+                (Shortcut, "`d`"),
+                (Inline, "f")
+            ]
+        );
+        assert_eq!(
+            targets("[`Foo`]\n\n [foo]:\n   https://example.com/long\n"),
+            vec![
+                (Shortcut, "`Foo`"),
+                (Definition, "https://example.com/long")
+            ]
+        );
+        assert_eq!(
+            targets("[Rust](https://en.wikipedia.org/wiki/Rust_(programming_language))"),
+            vec![(
+                Inline,
+                "https://en.wikipedia.org/wiki/Rust_(programming_language)"
+            )]
+        );
+    }
+}
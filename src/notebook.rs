@@ -0,0 +1,170 @@
+//! Jupyter notebook (`.ipynb`) cells.
+
+use crate::{
+    structured::{self, DataFormat, Value},
+    Language,
+};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{collections::BTreeMap, path::Path, str::FromStr};
+
+/// The type of a notebook cell.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CellKind {
+    /// A code cell.
+    Code,
+    /// A Markdown cell.
+    Markdown,
+    /// A raw cell, or a cell of unknown type.
+    Raw,
+}
+
+/// A notebook cell.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cell {
+    /// The 1-based index of the cell in the notebook.
+    pub index: usize,
+    /// The type of the cell.
+    pub kind: CellKind,
+    /// The source of the cell, which maps back to offsets in the notebook file. Outputs and
+    /// attachments are not included.
+    pub source: Value,
+}
+
+/// A parsed notebook.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Notebook {
+    /// The cells of the notebook, in order.
+    pub cells: Vec<Cell>,
+    /// The language of the notebook's code cells, if it is known and supported.
+    pub language: Option<Language>,
+}
+
+/// Returns true if the file at `path` is a Jupyter notebook.
+pub fn is_notebook(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "ipynb")
+}
+
+/// Parses the notebook `text`.
+pub fn parse(text: &str) -> Notebook {
+    lazy_static! {
+        static ref CELL_VALUE: Regex =
+            Regex::new(r"^cells\[(\d+)\]\.(cell_type|source)(?:\[\d+\])?$").unwrap();
+    }
+
+    let mut cells: BTreeMap<usize, Cell> = BTreeMap::new();
+    let mut language = None;
+
+    for value in structured::values(text, DataFormat::Json) {
+        match value.key_path.as_str() {
+            "metadata.kernelspec.language" | "metadata.language_info.name" => {
+                language = language.or_else(|| Language::from_str(&value.text).ok());
+                continue;
+            }
+            _ => (),
+        }
+
+        let cap = match CELL_VALUE.captures(&value.key_path) {
+            Some(cap) => cap,
+            None => continue,
+        };
+        // Keys that merely look like cell indices may be out of range.
+        let index = match cap[1].parse::<usize>().ok().and_then(|i| i.checked_add(1)) {
+            Some(index) => index,
+            None => continue,
+        };
+        let cell = cells.entry(index).or_insert_with(|| Cell {
+            index,
+            kind: CellKind::Raw,
+            source: Value::default(),
+        });
+
+        if &cap[2] == "cell_type" {
+            cell.kind = match value.text.as_str() {
+                "code" => CellKind::Code,
+                "markdown" => CellKind::Markdown,
+                _ => CellKind::Raw,
+            };
+        } else {
+            // Sources are either a single string or a list of lines.
+            cell.source.append(&value);
+        }
+    }
+
+    Notebook {
+        cells: cells.into_values().collect(),
+        language,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_notebook() {
+        let text = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": ["# Title\n", "See [docs](https://docs.example.com)."]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "metadata": {},
+   "outputs": [
+    {"data": {"text/html": ["<a href=\"https://output.example.com\">x</a>"]}}
+   ],
+   "source": "# https://comment.example.com\nurl = \"https://code.example.com\""
+  }
+ ],
+ "metadata": {
+  "kernelspec": {"display_name": "Python 3", "language": "python", "name": "python3"}
+ },
+ "nbformat": 4,
+ "nbformat_minor": 2
+}"##;
+
+        let notebook = parse(text);
+        let cells: Vec<(usize, CellKind, &str)> = notebook
+            .cells
+            .iter()
+            .map(|cell| (cell.index, cell.kind, cell.source.text.as_str()))
+            .collect();
+
+        assert_eq!(
+            cells,
+            vec![
+                (
+                    1,
+                    CellKind::Markdown,
+                    "# Title\nSee [docs](https://docs.example.com)."
+                ),
+                (
+                    2,
+                    CellKind::Code,
+                    "# https://comment.example.com\nurl = \"https://code.example.com\""
+                ),
+            ]
+        );
+        assert_eq!(notebook.language, Some(Language::Python));
+
+        // Cell sources map back to the notebook file, across list items.
+        let source = &notebook.cells[0].source;
+        let start = source.text.find("See").unwrap();
+        assert_eq!(&text[source.source_range(start..start + 3)], "See");
+    }
+
+    #[test]
+    fn out_of_range_index() {
+        for text in &[
+            r#"{"cells[99999999999999999999999].source": "https://example.com"}"#,
+            r#"{"cells[18446744073709551615].source": "https://example.com"}"#,
+        ] {
+            assert!(parse(text).cells.is_empty());
+        }
+    }
+}
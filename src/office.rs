@@ -0,0 +1,548 @@
+//! Text and hyperlinks of Office Open XML documents (`.docx`, `.pptx` and `.xlsx`).
+
+use crate::document::Part;
+use lazy_static::lazy_static;
+use quick_xml::{
+    escape,
+    events::{attributes::Attributes, BytesRef, Event},
+    Reader,
+};
+use regex::Regex;
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, Cursor, Read},
+};
+use zip::ZipArchive;
+
+type Archive = ZipArchive<Cursor<Vec<u8>>>;
+
+// Element names that make up the text of a document.
+struct Markup {
+    // A paragraph.
+    paragraph: &'static [u8],
+    // Visible text.
+    text: &'static [u8],
+    // A hyperlink, with its relationship ID in an `r:id` attribute.
+    hyperlink: &'static [u8],
+    // The element that ends the text of a hyperlink.
+    hyperlink_end: &'static [u8],
+}
+
+static WORD: Markup = Markup {
+    paragraph: b"w:p",
+    text: b"w:t",
+    hyperlink: b"w:hyperlink",
+    hyperlink_end: b"w:hyperlink",
+};
+
+static PRESENTATION: Markup = Markup {
+    paragraph: b"a:p",
+    text: b"a:t",
+    hyperlink: b"a:hlinkClick",
+    hyperlink_end: b"a:r",
+};
+
+/// Returns the paragraphs of a Word document or presentation, or the cells of a spreadsheet,
+/// given the extension `ext` and the contents `bytes` of the file.
+pub fn parts(bytes: &[u8], ext: &str) -> io::Result<Vec<Part>> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes.to_vec()))?;
+
+    match ext {
+        "docx" => {
+            let xml = read(&mut archive, "word/document.xml")?.unwrap_or_default();
+            let rels = relationships(&mut archive, "word/_rels/document.xml.rels")?;
+
+            Ok(paragraphs(&xml, &rels, &WORD, None))
+        }
+        "pptx" => {
+            let mut slides: Vec<(usize, String)> = archive
+                .file_names()
+                .filter_map(|name| {
+                    let number = name
+                        .strip_prefix("ppt/slides/slide")?
+                        .strip_suffix(".xml")?
+                        .parse()
+                        .ok()?;
+                    Some((number, name.to_string()))
+                })
+                .collect();
+            slides.sort();
+
+            let mut parts = vec![];
+            for (number, name) in slides {
+                let xml = read(&mut archive, &name)?.unwrap_or_default();
+                let rels = relationships(
+                    &mut archive,
+                    &format!("ppt/slides/_rels/slide{}.xml.rels", number),
+                )?;
+
+                parts.append(&mut paragraphs(&xml, &rels, &PRESENTATION, Some(number)));
+            }
+
+            Ok(parts)
+        }
+        _ => cells(&mut archive),
+    }
+}
+
+// Returns the contents of the file `name` in the archive, if it exists.
+fn read(archive: &mut Archive, name: &str) -> io::Result<Option<String>> {
+    let mut file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut xml = String::new();
+    file.read_to_string(&mut xml)?;
+
+    Ok(Some(xml))
+}
+
+// Returns the targets of the relationships in the file `name`, by ID.
+fn relationships(archive: &mut Archive, name: &str) -> io::Result<HashMap<String, String>> {
+    let xml = read(archive, name)?.unwrap_or_default();
+    let mut reader = Reader::from_str(&xml);
+    let mut rels = HashMap::new();
+
+    loop {
+        match reader.read_event().map_err(invalid_data)? {
+            Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"Relationship" => {
+                let attrs = attributes(e.attributes());
+                if let (Some(id), Some(target)) = (attrs.get("Id"), attrs.get("Target")) {
+                    rels.insert(id.clone(), target.clone());
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    Ok(rels)
+}
+
+// Returns the paragraphs of a Word document or slide, numbered from 1. `page` is the slide number.
+fn paragraphs(
+    xml: &str,
+    rels: &HashMap<String, String>,
+    markup: &Markup,
+    page: Option<usize>,
+) -> Vec<Part> {
+    lazy_static! {
+        static ref FIELD: Regex = Regex::new(r#"HYPERLINK\s+"([^"]+)""#).unwrap();
+    }
+
+    let mut reader = Reader::from_str(xml);
+    let mut parts = vec![];
+    // Paragraphs may be nested, e.g. in text boxes.
+    let mut open: Vec<Part> = vec![];
+    let mut count = 0;
+    let mut in_text = false;
+    let mut in_field = false;
+    let mut hyperlinks: Vec<(String, usize)> = vec![];
+
+    while let Ok(event) = reader.read_event() {
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let name = e.name();
+                let empty = matches!(event, Event::Empty(_));
+
+                if name.as_ref() == markup.paragraph {
+                    count += 1;
+                    let part = Part {
+                        page,
+                        paragraph: Some(count),
+                        ..Default::default()
+                    };
+                    if empty {
+                        parts.push(part);
+                    } else {
+                        open.push(part);
+                    }
+                } else if name.as_ref() == markup.text {
+                    in_text = !empty;
+                } else if name.as_ref() == b"w:instrText" {
+                    in_field = !empty;
+                } else if name.as_ref() == markup.hyperlink {
+                    let target = attributes(e.attributes())
+                        .get("r:id")
+                        .and_then(|id| rels.get(id))
+                        .cloned();
+                    let start = open.last().map_or(0, |part| part.text.len());
+
+                    if let Some(target) = target {
+                        if empty && markup.hyperlink == markup.hyperlink_end {
+                            if let Some(part) = open.last_mut() {
+                                part.links.push((target, Some(start..start)));
+                            }
+                        } else {
+                            hyperlinks.push((target, start));
+                        }
+                    }
+                } else if name.as_ref() == b"w:br" || name.as_ref() == b"a:br" {
+                    if let Some(part) = open.last_mut() {
+                        part.text.push('\n');
+                    }
+                } else if name.as_ref() == b"w:tab" {
+                    if let Some(part) = open.last_mut() {
+                        part.text.push('\t');
+                    }
+                }
+            }
+            Event::End(ref e) => {
+                let name = e.name();
+
+                if name.as_ref() == markup.paragraph {
+                    parts.extend(open.pop());
+                } else if name.as_ref() == markup.text {
+                    in_text = false;
+                } else if name.as_ref() == b"w:instrText" {
+                    in_field = false;
+                }
+
+                if name.as_ref() == markup.hyperlink_end {
+                    if let Some(part) = open.last_mut() {
+                        let end = part.text.len();
+                        for (target, start) in hyperlinks.drain(..) {
+                            part.links.push((target, Some(start..end)));
+                        }
+                    }
+                }
+            }
+            Event::Text(ref e) if in_text || in_field => {
+                let text = e.decode().unwrap_or_default();
+
+                if let Some(part) = open.last_mut() {
+                    if in_text {
+                        part.text.push_str(&text);
+                    } else {
+                        // Field codes such as `HYPERLINK "https://example.com"`.
+                        let start = part.text.len();
+                        for cap in FIELD.captures_iter(&text) {
+                            part.links.push((cap[1].to_string(), Some(start..start)));
+                        }
+                    }
+                }
+            }
+            Event::GeneralRef(ref e) if in_text => {
+                if let (Some(part), Some(c)) = (open.last_mut(), resolve(e)) {
+                    part.text.push_str(&c);
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    parts
+}
+
+// Returns the cells of a spreadsheet that contain text or hyperlinks, in sheet order.
+fn cells(archive: &mut Archive) -> io::Result<Vec<Part>> {
+    let shared_strings =
+        shared_strings(&read(archive, "xl/sharedStrings.xml")?.unwrap_or_default());
+    let workbook = read(archive, "xl/workbook.xml")?.unwrap_or_default();
+    let workbook_rels = relationships(archive, "xl/_rels/workbook.xml.rels")?;
+
+    // The name and path of each sheet.
+    let mut sheets = vec![];
+    let mut reader = Reader::from_str(&workbook);
+    loop {
+        match reader.read_event().map_err(invalid_data)? {
+            Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"sheet" => {
+                let attrs = attributes(e.attributes());
+                let target = attrs.get("r:id").and_then(|id| workbook_rels.get(id));
+
+                if let (Some(name), Some(target)) = (attrs.get("name"), target) {
+                    let path = match target.strip_prefix('/') {
+                        Some(path) => path.to_string(),
+                        None => format!("xl/{}", target),
+                    };
+                    sheets.push((name.clone(), path));
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    let mut parts = vec![];
+
+    for (sheet, path) in sheets {
+        let xml = read(archive, &path)?.unwrap_or_default();
+        let (dir, file) = match path.rfind('/') {
+            Some(i) => (&path[..i], &path[i + 1..]),
+            None => ("", path.as_str()),
+        };
+        let rels = relationships(archive, &format!("{}/_rels/{}.rels", dir, file))?;
+
+        parts.extend(sheet_cells(&xml, &sheet, &shared_strings, &rels).into_values());
+    }
+
+    Ok(parts)
+}
+
+// Returns the cells of a worksheet that contain text or hyperlinks, by cell reference.
+fn sheet_cells(
+    xml: &str,
+    sheet: &str,
+    shared_strings: &[String],
+    rels: &HashMap<String, String>,
+) -> BTreeMap<(usize, String), Part> {
+    lazy_static! {
+        static ref FORMULA: Regex = Regex::new(r#"HYPERLINK\(\s*"([^"]+)""#).unwrap();
+    }
+
+    let mut cells: BTreeMap<(usize, String), Part> = BTreeMap::new();
+
+    let mut reader = Reader::from_str(xml);
+    let mut reference = String::new();
+    let mut kind = String::new();
+    let mut element: Vec<u8> = vec![];
+    let mut content = String::new();
+
+    while let Ok(event) = reader.read_event() {
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => match e.name().as_ref() {
+                b"c" => {
+                    let attrs = attributes(e.attributes());
+                    reference = attrs.get("r").cloned().unwrap_or_default();
+                    kind = attrs.get("t").cloned().unwrap_or_default();
+                }
+                b"hyperlink" => {
+                    let attrs = attributes(e.attributes());
+                    let target = attrs.get("r:id").and_then(|id| rels.get(id));
+
+                    if let (Some(cell_ref), Some(target)) = (attrs.get("ref"), target) {
+                        // A range of cells is reported at its first cell.
+                        let first = cell_ref.split(':').next().unwrap_or(cell_ref);
+                        cell(&mut cells, sheet, first)
+                            .links
+                            .push((target.clone(), None));
+                    }
+                }
+                name => {
+                    element = name.to_vec();
+                    content.clear();
+                }
+            },
+            Event::Text(ref e) => content.push_str(&e.decode().unwrap_or_default()),
+            Event::GeneralRef(ref e) => content.push_str(&resolve(e).unwrap_or_default()),
+            Event::End(ref e) => {
+                let text = match (e.name().as_ref(), kind.as_str()) {
+                    (b"v", "s") => content
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| shared_strings.get(i))
+                        .cloned(),
+                    (b"v", "str") | (b"t", "inlineStr") => Some(content.clone()),
+                    (b"f", _) => {
+                        for cap in FORMULA.captures_iter(&content) {
+                            cell(&mut cells, sheet, &reference)
+                                .links
+                                .push((cap[1].to_string(), None));
+                        }
+                        None
+                    }
+                    _ => None,
+                };
+
+                if let Some(text) = text {
+                    if !reference.is_empty() && e.name().as_ref() == element.as_slice() {
+                        cell(&mut cells, sheet, &reference).text.push_str(&text);
+                    }
+                }
+                content.clear();
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    cells
+}
+
+// Returns the cell `reference` of `sheet`, adding it if needed.
+fn cell<'a>(
+    cells: &'a mut BTreeMap<(usize, String), Part>,
+    sheet: &str,
+    reference: &str,
+) -> &'a mut Part {
+    let row = reference
+        .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+        .parse()
+        .unwrap_or(0);
+
+    cells
+        .entry((row, reference.to_string()))
+        .or_insert_with(|| Part {
+            key_path: Some(format!("{}!{}", sheet, reference)),
+            ..Default::default()
+        })
+}
+
+// Returns the shared strings of a spreadsheet, in order.
+fn shared_strings(xml: &str) -> Vec<String> {
+    let mut reader = Reader::from_str(xml);
+    let mut strings = vec![];
+    let mut in_text = false;
+
+    while let Ok(event) = reader.read_event() {
+        match event {
+            Event::Start(ref e) if e.name().as_ref() == b"si" => strings.push(String::new()),
+            Event::Empty(ref e) if e.name().as_ref() == b"si" => strings.push(String::new()),
+            Event::Start(ref e) if e.name().as_ref() == b"t" => in_text = true,
+            Event::End(ref e) if e.name().as_ref() == b"t" => in_text = false,
+            Event::Text(ref e) if in_text => {
+                if let Some(string) = strings.last_mut() {
+                    string.push_str(&e.decode().unwrap_or_default());
+                }
+            }
+            Event::GeneralRef(ref e) if in_text => {
+                if let (Some(string), Some(c)) = (strings.last_mut(), resolve(e)) {
+                    string.push_str(&c);
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    strings
+}
+
+// Returns the attributes of an element, with values unescaped.
+fn attributes(attributes: Attributes) -> HashMap<String, String> {
+    attributes
+        .filter_map(|attr| attr.ok())
+        .filter_map(|attr| {
+            let key = String::from_utf8(attr.key.as_ref().to_vec()).ok()?;
+            let value = attr.unescape_value().ok()?.into_owned();
+            Some((key, value))
+        })
+        .collect()
+}
+
+// Resolves a character or predefined entity reference.
+fn resolve(reference: &BytesRef) -> Option<String> {
+    if let Ok(Some(c)) = reference.resolve_char_ref() {
+        return Some(c.to_string());
+    }
+
+    let name = reference.decode().ok()?;
+    escape::resolve_predefined_entity(&name).map(|s| s.to_string())
+}
+
+fn invalid_data(err: quick_xml::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn rels(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(id, target)| (id.to_string(), target.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn word_paragraphs() {
+        let xml = r#"<w:document><w:body>
+<w:p><w:r><w:t>Title</w:t></w:r></w:p>
+<w:p><w:r><w:t xml:space="preserve">See </w:t></w:r><w:hyperlink r:id="rId4"><w:r><w:t>the docs</w:t></w:r></w:hyperlink><w:r><w:t> &amp; https://text.example.com.</w:t></w:r></w:p>
+<w:p><w:r><w:fldChar w:fldCharType="begin"/></w:r><w:r><w:instrText> HYPERLINK "https://field.example.com" </w:instrText></w:r><w:r><w:t>field</w:t></w:r></w:p>
+</w:body></w:document>"#;
+
+        let parts = paragraphs(
+            xml,
+            &rels(&[("rId4", "https://docs.example.com/")]),
+            &WORD,
+            None,
+        );
+
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[1].paragraph, Some(2));
+        assert_eq!(parts[1].text, "See the docs & https://text.example.com.");
+        assert_eq!(
+            parts[1].links,
+            vec![("https://docs.example.com/".to_string(), Some(4..12))]
+        );
+        assert_eq!(
+            parts[2].links,
+            vec![("https://field.example.com".to_string(), Some(0..0))]
+        );
+    }
+
+    #[test]
+    fn slide_paragraphs() {
+        let xml = r#"<p:sld><p:cSld><p:spTree><p:sp><p:txBody>
+<a:p><a:r><a:rPr lang="en-US"><a:hlinkClick r:id="rId2"/></a:rPr><a:t>Link</a:t></a:r><a:r><a:t> text</a:t></a:r></a:p>
+</p:txBody></p:sp></p:spTree></p:cSld></p:sld>"#;
+
+        let parts = paragraphs(
+            xml,
+            &rels(&[("rId2", "https://slide.example.com/")]),
+            &PRESENTATION,
+            Some(3),
+        );
+
+        assert_eq!(
+            parts,
+            vec![Part {
+                page: Some(3),
+                paragraph: Some(1),
+                key_path: None,
+                text: "Link text".to_string(),
+                links: vec![("https://slide.example.com/".to_string(), Some(0..4))],
+            }]
+        );
+    }
+
+    #[test]
+    fn spreadsheet_cells() {
+        let shared = shared_strings(
+            r#"<sst><si><t>Name</t></si><si><r><t>See </t></r><r><t>https://shared.example.com/</t></r></si></sst>"#,
+        );
+        assert_eq!(shared, vec!["Name", "See https://shared.example.com/"]);
+
+        let xml = r#"<worksheet><sheetData>
+<row r="1"><c r="A1" t="s"><v>0</v></c></row>
+<row r="2"><c r="A2" t="s"><v>1</v></c><c r="B2" t="inlineStr"><is><t>Docs</t></is></c><c r="C2"><v>42</v></c></row>
+<row r="10"><c r="A10" t="str"><f>HYPERLINK("https://formula.example.com/","x")</f><v>x</v></c></row>
+</sheetData><hyperlinks><hyperlink ref="B2" r:id="rId1"/></hyperlinks></worksheet>"#;
+
+        let cells: Vec<Part> = sheet_cells(
+            xml,
+            "Sheet1",
+            &shared,
+            &rels(&[("rId1", "https://docs.example.com/")]),
+        )
+        .into_values()
+        .collect();
+
+        let summary: Vec<(&str, &str, usize)> = cells
+            .iter()
+            .map(|part| {
+                (
+                    part.key_path.as_deref().unwrap(),
+                    part.text.as_str(),
+                    part.links.len(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Sheet1!A1", "Name", 0),
+                ("Sheet1!A2", "See https://shared.example.com/", 0),
+                ("Sheet1!B2", "Docs", 1),
+                ("Sheet1!A10", "x", 1),
+            ]
+        );
+    }
+}
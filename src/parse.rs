@@ -1,25 +1,228 @@
+use crate::{
+    placeholder,
+    scheme::{self, Scheme},
+};
 use lazy_static::lazy_static;
 use regex::Regex;
+use std::ops::Range;
 
-// Gets the first URL in `line` and returns the rest of the line and the URL.
-pub fn get_urls(line: &str, no_http: bool) -> Vec<&str> {
+/// Returns a copy of `text` with everything outside of `ranges` blanked out. Line breaks are kept
+/// and other characters are replaced by spaces of the same byte length, so that line numbers and
+/// byte offsets are unchanged.
+pub fn keep_ranges(text: &str, ranges: &[Range<usize>]) -> String {
+    let mut kept = blank(text);
+
+    for range in ranges {
+        kept.replace_range(range.clone(), &text[range.clone()]);
+    }
+
+    kept
+}
+
+/// Blanks out `range` in `text`, as in `keep_ranges`.
+pub fn blank_range(text: &mut String, range: Range<usize>) {
+    let blanked = blank(&text[range.clone()]);
+    text.replace_range(range, &blanked);
+}
+
+/// Maps byte offsets in a text to line and column numbers.
+pub struct LineIndex<'a> {
+    text: &'a str,
+    // The offset of the start of each line.
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    /// Creates the line index of `text`.
+    pub fn new(text: &'a str) -> Self {
+        let mut starts = vec![0];
+        starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+
+        LineIndex { text, starts }
+    }
+
+    /// Returns the 1-based line number of the byte at `offset`.
+    pub fn line(&self, offset: usize) -> usize {
+        match self.starts.binary_search(&offset) {
+            Ok(i) => i + 1,
+            Err(i) => i,
+        }
+    }
+
+    /// Returns the 1-based column of the byte at `offset`, counted in characters.
+    pub fn column(&self, offset: usize) -> usize {
+        let start = self.starts[self.line(offset) - 1];
+        self.text[start..offset].chars().count() + 1
+    }
+
+    /// Returns the 1-based line and column of the byte at `offset`.
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        (self.line(offset), self.column(offset))
+    }
+}
+
+/// Returns the byte offset of `slice` within `text`. `slice` must be a subslice of `text`.
+pub fn offset_in(text: &str, slice: &str) -> usize {
+    slice.as_ptr() as usize - text.as_ptr() as usize
+}
+
+fn blank(text: &str) -> String {
+    let mut blanked = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if c == '\n' {
+            blanked.push('\n');
+        } else {
+            blanked.push_str(&" ".repeat(c.len_utf8()));
+        }
+    }
+
+    blanked
+}
+
+/// Returns the URLs in `text` along with the byte range of each, rejoining URLs that are wrapped
+/// across lines (see `unwrap_lines`). The range of a wrapped URL includes the removed line breaks.
+/// URLs containing template placeholders, such as `https://{{ host }}/`, are found whole. URLs with
+/// one of `schemes`, such as `mailto:` URLs, are found as well.
+pub fn find_urls(
+    text: &str,
+    no_http: bool,
+    continuations: bool,
+    schemes: &[Scheme],
+) -> Vec<(String, Range<usize>)> {
+    let (joined, offsets) = unwrap_lines(text, continuations);
+    let masked = placeholder::mask_templates(&joined);
+
+    let mut ranges: Vec<(usize, usize)> = get_urls(&masked, no_http)
+        .into_iter()
+        .map(|url| {
+            let start = offset_in(&masked, url);
+            (start, start + url.len())
+        })
+        .collect();
+    ranges.extend(scheme::find(&masked, schemes));
+    ranges.sort();
+
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            (
+                joined[start..end].to_string(),
+                offsets[start]..offsets[end - 1] + 1,
+            )
+        })
+        .collect()
+}
+
+/// Rejoins URLs that are wrapped across lines. Line breaks, along with surrounding whitespace, are
+/// removed inside angle brackets (`<https://...>`) and Markdown link destinations (`](...)`). If
+/// `continuations` is set, a backslash at the end of a line joins it with the next line.
+///
+/// Returns the joined text, and the offset in `text` of each of its bytes.
+pub fn unwrap_lines(text: &str, continuations: bool) -> (String, Vec<usize>) {
     lazy_static! {
-        static ref FORBIDDEN: &'static str = r##" "'<>\^`\{\|\}"##;
+        static ref OPENING: Regex =
+            Regex::new(r"<[ \t\r\n]*[A-Za-z][A-Za-z0-9+.-]*:|\]\(").unwrap();
+        static ref WRAP: Regex = Regex::new(r"[ \t\r]*\n[ \t\r\n]*").unwrap();
+        static ref CONTINUATION: Regex = Regex::new(r"\\\r?\n").unwrap();
+    }
+
+    let mut removed: Vec<Range<usize>> = vec![];
+
+    for mat in OPENING.find_iter(text) {
+        if let Some(end) = delimited_end(text, mat.start(), mat.end()) {
+            removed.extend(
+                WRAP.find_iter(&text[mat.start()..end])
+                    .map(|wrap| mat.start() + wrap.start()..mat.start() + wrap.end()),
+            );
+        }
+    }
+
+    if continuations {
+        removed.extend(CONTINUATION.find_iter(text).map(|mat| mat.range()));
+    }
+
+    removed.sort_by_key(|range| range.start);
+
+    let mut joined = String::with_capacity(text.len());
+    let mut offsets = Vec::with_capacity(text.len());
+    let mut pos = 0;
+
+    for range in removed {
+        if range.start < pos {
+            continue;
+        }
+
+        joined.push_str(&text[pos..range.start]);
+        offsets.extend(pos..range.start);
+        pos = range.end;
+    }
+    joined.push_str(&text[pos..]);
+    offsets.extend(pos..text.len());
+
+    (joined, offsets)
+}
+
+// Returns the end of the URL delimited by the opening at `start`, if it is properly closed and spans
+// several lines. The URL may not contain a blank line.
+fn delimited_end(text: &str, start: usize, content_start: usize) -> Option<usize> {
+    let angle = text[start..].starts_with('<');
+    let mut depth = 0;
+    let mut newline = false;
+
+    for (i, c) in text[content_start..].char_indices().take(2000) {
+        match c {
+            '>' if angle => return Some(content_start + i).filter(|_| newline),
+            '<' => return None,
+            '(' if !angle => depth += 1,
+            ')' if !angle && depth == 0 => return Some(content_start + i).filter(|_| newline),
+            ')' if !angle => depth -= 1,
+            '\n' => {
+                let rest = text[content_start + i + 1..].trim_start_matches(&[' ', '\t', '\r'][..]);
+                if rest.starts_with('\n') {
+                    return None;
+                }
+                newline = true;
+            }
+            _ => (),
+        }
+    }
+
+    None
+}
+
+/// Returns the URLs in `text`. Unless `no_http` is set, these are the `http`, `https`, `ftp` and
+/// `sftp` URLs.
+///
+/// Parentheses and brackets are kept inside URLs as long as they are balanced, as in
+/// `https://en.wikipedia.org/wiki/Rust_(programming_language)`, and a URL ends at the first
+/// unbalanced closing one. Trailing sentence punctuation is not part of a URL, and neither is a
+/// closing quote. URLs in angle brackets (`<https://...>`) are taken verbatim.
+pub fn get_urls(text: &str, no_http: bool) -> Vec<&str> {
+    lazy_static! {
+        // Includes typographic quotes and guillemets.
+        static ref FORBIDDEN: &'static str = r##" "<>\^`\{\|\}\x{201C}\x{201D}\x{2018}\x{2019}\x{AB}\x{BB}"##;
         static ref INVALID: String = {
             let mut invalid = FORBIDDEN.to_string();
-            invalid.push_str(r##"\s\[\]\(\),"##);
+            invalid.push_str(r##"\s,\x{3001}\x{FF0C}"##);
             invalid
         };
         static ref VALID_CHARS: String =
             r##"[^[INVALID]]"##.replace("[INVALID]", &INVALID);
-        static ref BOUNDARY_CHARS: String =
-            r##"[$[^[INVALID].:]]"##.replace("[INVALID]", &INVALID);
-        static ref START_BOUNDARY_CHARS: String =
-            r##"[$[^[INVALID].:/]]"##.replace("[INVALID]", &INVALID);
+        // Sentence punctuation, including the full-width punctuation of Chinese and Japanese.
+        static ref PUNCTUATION: &'static str =
+            r##".:;!?\x{3001}\x{3002}\x{FF01}\x{FF0C}\x{FF1A}\x{FF1B}\x{FF1F}"##;
+        // URLs don't end with sentence punctuation, an opening bracket or an apostrophe.
+        static ref BOUNDARY_CHARS: String = r##"[^[INVALID][PUNCTUATION](\[']"##
+            .replace("[INVALID]", &INVALID)
+            .replace("[PUNCTUATION]", &PUNCTUATION);
+        static ref START_BOUNDARY_CHARS: String = r##"[^[INVALID][PUNCTUATION]/()\[\]']"##
+            .replace("[INVALID]", &INVALID)
+            .replace("[PUNCTUATION]", &PUNCTUATION);
         // Require at least two valid characters and a boundary character.
         // This makes the optional double slash at the beginning work as expected.
         static ref REGEX_HTTP: Regex = Regex::new(
-            &r"https?:/?/?[VALID]+[VALID]+[BOUNDARY]"
+            &r"(?:https?|s?ftp):/?/?[VALID]+[VALID]+[BOUNDARY]"
                 .replace("[VALID]", &VALID_CHARS)
                 .replace("[BOUNDARY]", &BOUNDARY_CHARS)
         )
@@ -33,22 +236,143 @@ pub fn get_urls(line: &str, no_http: bool) -> Vec<&str> {
         .unwrap();
     }
 
-    if no_http {
-        REGEX_NOHTTP
-            .find_iter(line)
-            .map(|mat| mat.as_str())
-            .filter(|s| s.contains('/'))
-            .collect()
-    } else {
-        REGEX_HTTP.find_iter(line).map(|mat| mat.as_str()).collect()
+    let regex: &Regex = if no_http { &REGEX_NOHTTP } else { &REGEX_HTTP };
+    let mut urls = vec![];
+    let mut pos = 0;
+
+    while let Some(mat) = regex.find_at(text, pos) {
+        let start = mat.start();
+        let quoted = text[..start].ends_with('\'');
+
+        let url = autolink(text, start).or_else(|| {
+            // Matching again removes any punctuation left at the end of the cut URL.
+            let cut = &mat.as_str()[..url_end(mat.as_str(), quoted)];
+            regex
+                .find(cut)
+                .filter(|mat| mat.start() == 0)
+                .map(|mat| mat.as_str())
+        });
+
+        match url {
+            Some(url) => {
+                if !no_http || url.contains('/') {
+                    urls.push(url);
+                }
+                pos = start + url.len();
+            }
+            None => pos = start + text[start..].chars().next().map_or(1, char::len_utf8),
+        }
     }
+
+    urls
+}
+
+// Returns the URL starting at `start` if it is enclosed in angle brackets, as in Markdown
+// autolinks.
+fn autolink(text: &str, start: usize) -> Option<&str> {
+    if !text[..start].ends_with('<') {
+        return None;
+    }
+
+    let len = text[start..].find(|c: char| c.is_whitespace() || c == '<' || c == '>')?;
+    Some(&text[start..start + len]).filter(|_| text[start + len..].starts_with('>'))
+}
+
+// Returns the length of `url` up to its first unbalanced closing bracket or parenthesis, or its
+// closing quote. An apostrophe inside a word, as in `Conway's_Game_of_Life`, is kept unless the
+// URL is `quoted` with apostrophes.
+fn url_end(url: &str, quoted: bool) -> usize {
+    let mut open = vec![];
+    let mut chars = url.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '(' => open.push(')'),
+            '[' => open.push(']'),
+            ')' | ']' if open.pop() != Some(c) => return i,
+            '\'' => {
+                let in_word = matches!(chars.peek(), Some((_, c)) if c.is_alphanumeric());
+                if quoted || !in_word {
+                    return i;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    url.len()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::get_urls;
+    use super::{find_urls, get_urls, LineIndex};
     use crate::{url, util::test_utils};
     use ::url::Url;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn line_index() {
+        let index = LineIndex::new("a\nbc\n\nd");
+
+        assert_eq!(index.line(0), 1);
+        assert_eq!(index.line(1), 1);
+        assert_eq!(index.line(2), 2);
+        assert_eq!(index.line(5), 3);
+        assert_eq!(index.line(6), 4);
+
+        let index = LineIndex::new("ab\n\u{e9}t\u{e9} x");
+
+        assert_eq!(index.position(0), (1, 1));
+        assert_eq!(index.position(1), (1, 2));
+        assert_eq!(index.position(3), (2, 1));
+        assert_eq!(index.position(9), (2, 5));
+    }
+
+    #[test]
+    fn find_wrapped_urls() {
+        fn urls(text: &str, continuations: bool) -> Vec<(String, usize)> {
+            find_urls(text, false, continuations, &[])
+                .into_iter()
+                .map(|(url, range)| (url, range.start))
+                .collect()
+        }
+
+        // Angle brackets.
+        let text = "See <https://example.com/very/\n    long/path> and\n<https://a.com/\n\nb>.";
+        assert_eq!(
+            urls(text, false),
+            vec![
+                ("https://example.com/very/long/path".to_string(), 5),
+                ("https://a.com/".to_string(), 51),
+            ]
+        );
+
+        // Markdown link destinations.
+        let text = "[Guide](\n  https://docs.example.com/\n  guide/intro.html) [a](b)";
+        assert_eq!(
+            urls(text, false),
+            vec![("https://docs.example.com/guide/intro.html".to_string(), 11)]
+        );
+
+        // Line continuations.
+        let text = "curl https://example.com/a\\\n/b \\\n  https://example.com/c";
+        assert_eq!(
+            urls(text, true),
+            vec![
+                ("https://example.com/a/b".to_string(), 5),
+                ("https://example.com/c".to_string(), 35),
+            ]
+        );
+        assert_eq!(urls(text, false)[0].0, "https://example.com/a\\");
+
+        // Ranges cover the whole URL in the source, including removed line breaks.
+        let text = "x <https://a.com/\n  b> https://c.com";
+        let ranges: Vec<_> = find_urls(text, false, false, &[])
+            .into_iter()
+            .map(|(_, range)| range)
+            .collect();
+        assert_eq!(ranges, vec![3..21, 23..36]);
+    }
 
     #[test]
     fn parse_urls_http() {
@@ -145,6 +469,86 @@ mod tests {
             "https://www.bytedude.com' https://' http://'",
             &["https://www.bytedude.com"]
         );
+
+        test_parse!(
+            "Mirrors: ftp://ftp.gnu.org/gnu/, sftp://files.host.org/pub/a.tar.gz.",
+            &[
+                "ftp://ftp.gnu.org/gnu/",
+                "sftp://files.host.org/pub/a.tar.gz"
+            ],
+        );
+    }
+
+    #[test]
+    fn url_boundaries() {
+        // Each text, and the URLs found in it.
+        let corpus: &[(&str, &[&str])] = &[
+            // Balanced parentheses and brackets.
+            (
+                "See https://en.wikipedia.org/wiki/Rust_(programming_language).",
+                &["https://en.wikipedia.org/wiki/Rust_(programming_language)"],
+            ),
+            (
+                "(see https://en.wikipedia.org/wiki/Rust_(programming_language))",
+                &["https://en.wikipedia.org/wiki/Rust_(programming_language)"],
+            ),
+            (
+                "https://example.com/search?q=(a)&b=[c]",
+                &["https://example.com/search?q=(a)&b=[c]"],
+            ),
+            ("http://[::1]:8080/status", &["http://[::1]:8080/status"]),
+            // Unbalanced closing parentheses and brackets.
+            ("(https://example.com/a)", &["https://example.com/a"]),
+            (
+                "[docs](https://example.com/docs).",
+                &["https://example.com/docs"],
+            ),
+            ("(https://example.com/x), and", &["https://example.com/x"]),
+            ("[https://example.com/(a])", &["https://example.com/(a"]),
+            // Sentence punctuation.
+            (
+                "Read https://example.com/faq?",
+                &["https://example.com/faq"],
+            ),
+            (
+                "https://example.com/a; https://example.com/b!",
+                &["https://example.com/a", "https://example.com/b"],
+            ),
+            ("https://example.com/a(.", &["https://example.com/a"]),
+            // Quotes.
+            (
+                "'https://example.com/quoted'",
+                &["https://example.com/quoted"],
+            ),
+            (
+                "https://en.wikipedia.org/wiki/Conway's_Game_of_Life",
+                &["https://en.wikipedia.org/wiki/Conway's_Game_of_Life"],
+            ),
+            (
+                "\u{201c}https://example.com/smart\u{201d}",
+                &["https://example.com/smart"],
+            ),
+            // Internationalized domain names and IRIs.
+            (
+                "Siehe https://münchen.de/straße.",
+                &["https://münchen.de/straße"],
+            ),
+            (
+                "詳しくは https://例え.テスト/パス。",
+                &["https://例え.テスト/パス"],
+            ),
+            (
+                "参见https://例子.测试/路径，谢谢",
+                &["https://例子.测试/路径"],
+            ),
+            // Angle-bracket autolinks.
+            ("<https://example.com/a_(b>", &["https://example.com/a_(b"]),
+            ("<https://example.com/end.>", &["https://example.com/end."]),
+        ];
+
+        for (text, urls) in corpus {
+            assert_eq!(&get_urls(text, false), urls, "{}", text);
+        }
     }
 
     #[test]
@@ -0,0 +1,46 @@
+//! Text and link annotations of PDF documents.
+
+use crate::document::Part;
+use lopdf::{Document, Object};
+use std::io;
+
+/// Returns a part for each page of the PDF document `bytes`, with the page's text and the URIs of
+/// its link annotations.
+pub fn parts(bytes: &[u8]) -> io::Result<Vec<Part>> {
+    let document =
+        Document::load_mem(bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let mut parts = vec![];
+
+    for (number, page_id) in document.get_pages() {
+        // Pages whose text can't be extracted, e.g. because of an unsupported font encoding, may
+        // still have link annotations.
+        let text = document.extract_text(&[number]).unwrap_or_default();
+        let mut links = vec![];
+
+        for annotation in document.get_page_annotations(page_id).unwrap_or_default() {
+            let subtype = annotation.get(b"Subtype").and_then(Object::as_name);
+            if subtype.ok() != Some(&b"Link"[..]) {
+                continue;
+            }
+
+            let uri = annotation
+                .get_deref(b"A", &document)
+                .and_then(Object::as_dict)
+                .and_then(|action| action.get_deref(b"URI", &document))
+                .and_then(Object::as_str);
+
+            if let Ok(uri) = uri {
+                links.push((String::from_utf8_lossy(uri).trim().to_string(), None));
+            }
+        }
+
+        parts.push(Part {
+            page: Some(number as usize),
+            text,
+            links,
+            ..Default::default()
+        });
+    }
+
+    Ok(parts)
+}
@@ -0,0 +1,221 @@
+//! Detection of templated, local and example URLs, which are skipped rather than checked.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{
+    fmt,
+    net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+/// A kind of URL that is not meant to be resolved as written.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Placeholder {
+    /// URLs containing template syntax, such as `https://{{ .Values.host }}/api` (Jinja, Go
+    /// templates and Handlebars), `https://${DOMAIN}/` (shell variables) or
+    /// `https://<your-org>.example.com` (angle-bracket placeholders).
+    Template,
+    /// URLs of local or private hosts, such as `localhost`, `127.0.0.1`, `192.168.1.1` or `fd00::1`.
+    Local,
+    /// URLs of domains reserved for examples by RFC 2606, such as `example.com` or `site.test`.
+    Example,
+}
+
+impl Placeholder {
+    /// All kinds of placeholder URLs.
+    pub const ALL: &'static [Placeholder] = &[
+        Placeholder::Template,
+        Placeholder::Local,
+        Placeholder::Example,
+    ];
+
+    /// Returns the kind of placeholder `url` is, if any.
+    pub fn of(url: &str) -> Option<Placeholder> {
+        lazy_static! {
+            static ref TEMPLATE: Regex =
+                Regex::new(r"\{\{|\$\{|\$[A-Z_][A-Z0-9_]*\b|<[A-Za-z][A-Za-z0-9_-]*>").unwrap();
+        }
+
+        if TEMPLATE.is_match(url) {
+            return Some(Placeholder::Template);
+        }
+
+        // URLs found without a scheme in `no_http` mode.
+        let parsed = ::url::Url::parse(url)
+            .ok()
+            .filter(|parsed| parsed.has_host())
+            .or_else(|| ::url::Url::parse(&format!("http://{}", url)).ok())?;
+
+        match parsed.host()? {
+            ::url::Host::Domain(domain) => {
+                let domain = domain.trim_end_matches('.');
+                let ends_with =
+                    |suffix: &str| domain == suffix || domain.ends_with(&format!(".{}", suffix));
+
+                if ends_with("localhost") || ends_with("local") {
+                    Some(Placeholder::Local)
+                } else if ["example", "test", "invalid"]
+                    .iter()
+                    .any(|tld| ends_with(tld))
+                    || ["example.com", "example.net", "example.org"]
+                        .iter()
+                        .any(|domain| ends_with(domain))
+                {
+                    Some(Placeholder::Example)
+                } else {
+                    None
+                }
+            }
+            ::url::Host::Ipv4(addr) => Some(Placeholder::Local).filter(|_| is_local_v4(addr)),
+            ::url::Host::Ipv6(addr) => Some(Placeholder::Local).filter(|_| is_local_v6(addr)),
+        }
+    }
+}
+
+impl fmt::Display for Placeholder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Placeholder::*;
+
+        let name = match *self {
+            Template => "template",
+            Local => "local",
+            Example => "example",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Placeholder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Placeholder::*;
+
+        Ok(match s.to_lowercase().as_str() {
+            "template" | "templates" => Template,
+            "local" | "localhost" => Local,
+            "example" | "examples" => Example,
+            _ => return Err(format!("Unknown placeholder kind: {}", s)),
+        })
+    }
+}
+
+/// Returns a copy of `text` with the template placeholders that may appear in URLs, such as
+/// `{{ .Values.host }}`, replaced by the same number of bytes of plain characters, so that the URLs
+/// containing them are found whole. Byte offsets are unchanged.
+pub fn mask_templates(text: &str) -> String {
+    lazy_static! {
+        static ref PLACEHOLDER: Regex =
+            Regex::new(r"\{\{[^\n]*?\}\}|\$\{[^}\n]*\}|<[A-Za-z][A-Za-z0-9_-]*>").unwrap();
+    }
+    // HTML elements which are not angle-bracket placeholders.
+    static TAGS: &[&str] = &[
+        "a", "b", "br", "code", "div", "em", "hr", "i", "img", "li", "p", "span", "strong", "td",
+        "tr", "ul",
+    ];
+
+    if !text.contains("{{") && !text.contains("${") && !text.contains('<') {
+        return text.to_string();
+    }
+
+    let mut masked = text.to_string();
+
+    for mat in PLACEHOLDER.find_iter(text) {
+        let placeholder = mat.as_str();
+
+        if placeholder.starts_with('<') {
+            // Angle-bracket placeholders must be part of a URL, e.g. `https://<host>/`.
+            let name = placeholder[1..placeholder.len() - 1].to_lowercase();
+            let in_url = text[..mat.start()].ends_with(&['/', '.', ':', '@', '='][..])
+                || text[mat.end()..].starts_with(&['/', '.'][..]);
+            if !in_url || TAGS.contains(&name.as_str()) {
+                continue;
+            }
+        }
+
+        masked.replace_range(mat.range(), &"x".repeat(placeholder.len()));
+    }
+
+    masked
+}
+
+fn is_local_v4(addr: Ipv4Addr) -> bool {
+    addr.is_loopback() || addr.is_private() || addr.is_link_local() || addr.is_unspecified()
+}
+
+fn is_local_v6(addr: Ipv6Addr) -> bool {
+    let first = addr.segments()[0];
+
+    addr.is_loopback()
+        || addr.is_unspecified()
+        // Unique local addresses, fc00::/7.
+        || first & 0xfe00 == 0xfc00
+        // Link-local addresses, fe80::/10.
+        || first & 0xffc0 == 0xfe80
+        || matches!(addr.to_ipv4_mapped(), Some(addr) if is_local_v4(addr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn placeholder_kinds() {
+        use Placeholder::*;
+
+        let urls = [
+            ("https://{{ .Values.host }}/api", Some(Template)),
+            ("https://{{host}}/", Some(Template)),
+            ("https://${DOMAIN}/", Some(Template)),
+            ("https://$DOMAIN/path", Some(Template)),
+            ("https://<your-org>.example.com", Some(Template)),
+            ("https://api.example.com/odata?$filter=name", Some(Example)),
+            ("https://api.service.io/odata?$filter=name", None),
+            ("http://localhost:8080", Some(Local)),
+            ("http://app.localhost/", Some(Local)),
+            ("http://printer.local/", Some(Local)),
+            ("http://127.0.0.1:3000/", Some(Local)),
+            ("http://10.0.0.1/", Some(Local)),
+            ("http://172.16.5.4/", Some(Local)),
+            ("http://192.168.1.1/admin", Some(Local)),
+            ("http://169.254.169.254/latest", Some(Local)),
+            ("http://0.0.0.0:8000/", Some(Local)),
+            ("http://[::1]:8080/", Some(Local)),
+            ("http://[fd00::1]/", Some(Local)),
+            ("http://[::ffff:192.168.0.1]/", Some(Local)),
+            ("http://8.8.8.8/", None),
+            ("http://172.32.0.1/", None),
+            ("https://example.com/", Some(Example)),
+            ("https://docs.EXAMPLE.org/page", Some(Example)),
+            ("https://site.test/", Some(Example)),
+            ("https://nothing.invalid/", Some(Example)),
+            ("example.net/page", Some(Example)),
+            ("https://example.co/", None),
+            ("https://myexample.com/", None),
+            ("https://www.rust-lang.org/", None),
+        ];
+
+        for (url, kind) in urls.iter() {
+            assert_eq!(Placeholder::of(url), *kind, "{}", url);
+        }
+    }
+
+    #[test]
+    fn mask_placeholders() {
+        assert_eq!(
+            mask_templates("url: https://{{ .Values.host }}/api"),
+            "url: https://xxxxxxxxxxxxxxxxxx/api"
+        );
+        assert_eq!(
+            mask_templates("https://${DOMAIN}/ and <https://a.com/>"),
+            "https://xxxxxxxxx/ and <https://a.com/>"
+        );
+        assert_eq!(
+            mask_templates("https://<your-org>.example.com <b>bold</b> https://a.com/<br>"),
+            "https://xxxxxxxxxx.example.com <b>bold</b> https://a.com/<br>"
+        );
+        assert_eq!(mask_templates("x <T> y"), "x <T> y");
+    }
+}
@@ -0,0 +1,111 @@
+//! Redirect policies and the redirects followed while checking URLs.
+
+use std::{fmt, str::FromStr};
+
+/// The number of redirects followed by default before a URL is reported as bad.
+pub const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+/// What to do when a URL redirects to another URL.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum RedirectPolicy {
+    /// Follow redirects, warning about permanent (301 and 308) redirects only.
+    #[default]
+    Follow,
+    /// Follow redirects, warning about all of them.
+    Warn,
+    /// Report URLs that redirect as bad.
+    Forbid,
+}
+
+impl fmt::Display for RedirectPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use RedirectPolicy::*;
+
+        let name = match *self {
+            Follow => "follow",
+            Warn => "warn",
+            Forbid => "forbid",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for RedirectPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use RedirectPolicy::*;
+
+        Ok(match s.to_lowercase().as_str() {
+            "follow" => Follow,
+            "warn" => Warn,
+            "forbid" | "deny" => Forbid,
+            _ => return Err(format!("Unknown redirect policy: {}", s)),
+        })
+    }
+}
+
+/// A redirect followed while checking a URL.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Redirect {
+    /// The HTTP status code of the redirect, e.g. 301.
+    pub status: u32,
+    /// The URL redirected to.
+    pub url: String,
+}
+
+impl Redirect {
+    /// Returns true if the redirect is permanent, i.e. has status code 301 or 308.
+    pub fn is_permanent(&self) -> bool {
+        self.status == 301 || self.status == 308
+    }
+}
+
+/// Returns the URL that a link with the redirect chain `redirects` has permanently moved to, if
+/// any. This is the target of the last of the redirects at the start of the chain that are
+/// permanent, as a temporary redirect, e.g. to a login page, doesn't mean the link should change.
+pub fn permanent_target(redirects: &[Redirect]) -> Option<&str> {
+    redirects
+        .iter()
+        .take_while(|redirect| redirect.is_permanent())
+        .last()
+        .map(|redirect| redirect.url.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn permanent_targets() {
+        let redirect = |status, url: &str| Redirect {
+            status,
+            url: url.to_string(),
+        };
+
+        assert_eq!(permanent_target(&[]), None);
+        assert_eq!(
+            permanent_target(&[
+                redirect(301, "https://a.com/"),
+                redirect(308, "https://b.com/")
+            ]),
+            Some("https://b.com/")
+        );
+        assert_eq!(
+            permanent_target(&[
+                redirect(301, "https://a.com/"),
+                redirect(302, "https://login/")
+            ]),
+            Some("https://a.com/")
+        );
+        assert_eq!(
+            permanent_target(&[
+                redirect(307, "https://a.com/"),
+                redirect(301, "https://b.com/")
+            ]),
+            None
+        );
+    }
+}
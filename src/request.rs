@@ -0,0 +1,49 @@
+//! How URLs are requested.
+
+use std::{fmt, str::FromStr};
+
+/// The largest response body kept by default, in bytes. Bodies are only read to detect soft 404s,
+/// and the rest of a larger body isn't downloaded.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// The HTTP methods URLs are requested with.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum MethodStrategy {
+    /// Send a HEAD request, and a GET request only if that fails, as not all servers are
+    /// configured for HEAD.
+    #[default]
+    HeadThenGet,
+    /// Only send HEAD requests.
+    Head,
+    /// Only send GET requests.
+    Get,
+}
+
+impl fmt::Display for MethodStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use MethodStrategy::*;
+
+        let name = match *self {
+            HeadThenGet => "head-then-get",
+            Head => "head",
+            Get => "get",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for MethodStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use MethodStrategy::*;
+
+        Ok(match s.to_lowercase().as_str() {
+            "head-then-get" => HeadThenGet,
+            "head" => Head,
+            "get" => Get,
+            _ => return Err(format!("Unknown request method strategy: {}", s)),
+        })
+    }
+}
@@ -0,0 +1,274 @@
+//! Overrides of how host names are resolved, e.g. to check a site on a staging server.
+
+use crate::{Error, Result};
+use std::{fmt, fs, io, net::IpAddr, path::Path, result, str::FromStr};
+
+/// The IP version to connect with.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum IpVersion {
+    /// IPv4.
+    V4,
+    /// IPv6.
+    V6,
+}
+
+impl IpVersion {
+    /// Returns true if `addr` is of this version.
+    pub fn matches(self, addr: &IpAddr) -> bool {
+        match self {
+            IpVersion::V4 => addr.is_ipv4(),
+            IpVersion::V6 => addr.is_ipv6(),
+        }
+    }
+}
+
+impl fmt::Display for IpVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use IpVersion::*;
+
+        let name = match *self {
+            V4 => "4",
+            V6 => "6",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for IpVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        use IpVersion::*;
+
+        Ok(match s.to_lowercase().as_str() {
+            "4" | "v4" | "ipv4" => V4,
+            "6" | "v6" | "ipv6" => V6,
+            _ => return Err(format!("Unknown IP version: {}", s)),
+        })
+    }
+}
+
+/// The addresses to connect to for a host name, instead of resolving it.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Resolve {
+    /// The host name, e.g. `docs.example.com`.
+    pub host: String,
+    /// The port the addresses are for. All ports if not set.
+    pub port: Option<u16>,
+    /// The addresses, in order of preference.
+    pub addrs: Vec<IpAddr>,
+}
+
+impl Resolve {
+    /// Returns true if the addresses are for `port` of `host`.
+    pub fn matches(&self, host: &str, port: u16) -> bool {
+        self.host.eq_ignore_ascii_case(host.trim_end_matches('.'))
+            && self.port.is_none_or(|own_port| own_port == port)
+    }
+}
+
+impl fmt::Display for Resolve {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let addrs: Vec<String> = self.addrs.iter().map(|&addr| bracketed(addr)).collect();
+        let port = self.port.map_or(String::new(), |port| port.to_string());
+
+        write!(f, "{}:{}:{}", self.host, port, addrs.join(","))
+    }
+}
+
+impl FromStr for Resolve {
+    type Err = String;
+
+    /// Parses addresses in the format of curl's `--resolve`: `HOST:PORT:ADDR[,ADDR]...`, with
+    /// IPv6 addresses optionally in brackets.
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        let mut parts = s.splitn(3, ':');
+        let (host, port, addrs) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(host), Some(port), Some(addrs)) if !host.is_empty() => (host, port, addrs),
+            _ => return Err(format!("'{}': expected HOST:PORT:ADDR", s)),
+        };
+
+        let port = port
+            .parse()
+            .map_err(|_| format!("Invalid port: {}", port))?;
+        let addrs = addrs
+            .split(',')
+            .map(|addr| {
+                addr.trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .parse()
+                    .map_err(|_| format!("Invalid IP address: {}", addr))
+            })
+            .collect::<result::Result<_, _>>()?;
+
+        Ok(Resolve {
+            host: host.to_lowercase(),
+            port: Some(port),
+            addrs,
+        })
+    }
+}
+
+/// Reads a mapping of host names to addresses in the format of `/etc/hosts`: an address followed
+/// by the host names mapped to it on each line, with `#` starting comments. The mapping applies
+/// to all ports.
+pub fn read_hosts_file(path: &Path) -> Result<Vec<Resolve>> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+
+    parse_hosts_file(&text)
+        .map_err(|reason| Error::InvalidHostsFile(format!("{}: {}", path.display(), reason)))
+}
+
+// Parses a hosts file, merging the addresses of host names given on several lines.
+fn parse_hosts_file(text: &str) -> result::Result<Vec<Resolve>, String> {
+    let mut resolves: Vec<Resolve> = vec![];
+
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        let addr = match fields.next() {
+            Some(addr) => addr
+                .parse()
+                .map_err(|_| format!("Invalid IP address: {}", addr))?,
+            None => continue,
+        };
+
+        for host in fields {
+            let host = host.to_lowercase();
+            match resolves.iter_mut().find(|resolve| resolve.host == host) {
+                Some(resolve) => resolve.addrs.push(addr),
+                None => resolves.push(Resolve {
+                    host,
+                    port: None,
+                    addrs: vec![addr],
+                }),
+            }
+        }
+    }
+
+    Ok(resolves)
+}
+
+/// Returns the entries of curl's resolve list for the addresses of `resolves` with a port, and
+/// of its connect-to list for those for all ports, with the addresses of `ip_version` only.
+/// Addresses with a port take precedence, as curl connects to the first match of the connect-to
+/// list, which they are added to as connecting to their own host and port.
+pub fn curl_lists(
+    resolves: &[Resolve],
+    ip_version: Option<IpVersion>,
+) -> (Vec<String>, Vec<String>) {
+    let mut resolve_list = vec![];
+    let mut own_list = vec![];
+    let mut connect_to_list = vec![];
+
+    for resolve in resolves {
+        let addrs: Vec<IpAddr> = resolve
+            .addrs
+            .iter()
+            .copied()
+            .filter(|addr| ip_version.is_none_or(|version| version.matches(addr)))
+            .collect();
+        if addrs.is_empty() {
+            continue;
+        }
+
+        match resolve.port {
+            Some(port) => {
+                let host_port = format!("{}:{}", resolve.host, port);
+                own_list.push(format!("{}:{}", host_port, host_port));
+                resolve_list.push(
+                    Resolve {
+                        addrs,
+                        ..resolve.clone()
+                    }
+                    .to_string(),
+                );
+            }
+            // The connect-to list only takes one address, and keeps the port of the URL.
+            None => connect_to_list.push(format!("{}::{}:", resolve.host, bracketed(addrs[0]))),
+        }
+    }
+
+    if !connect_to_list.is_empty() {
+        own_list.append(&mut connect_to_list);
+        connect_to_list = own_list;
+    }
+
+    (resolve_list, connect_to_list)
+}
+
+// Returns an IP address as curl takes it, with IPv6 addresses in brackets.
+fn bracketed(addr: IpAddr) -> String {
+    match addr {
+        IpAddr::V4(addr) => addr.to_string(),
+        IpAddr::V6(addr) => format!("[{}]", addr),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_resolve() {
+        let resolve: Resolve = "Docs.Example.com:443:10.0.0.5,[::1]".parse().unwrap();
+
+        assert_eq!(resolve.host, "docs.example.com");
+        assert_eq!(resolve.port, Some(443));
+        assert!(resolve.matches("docs.example.com.", 443));
+        assert!(!resolve.matches("docs.example.com", 80));
+        assert_eq!(resolve.to_string(), "docs.example.com:443:10.0.0.5,[::1]");
+
+        assert!("docs.example.com:443".parse::<Resolve>().is_err());
+        assert!("docs.example.com:https:10.0.0.5"
+            .parse::<Resolve>()
+            .is_err());
+        assert!("docs.example.com:443:staging".parse::<Resolve>().is_err());
+    }
+
+    #[test]
+    fn hosts_file() {
+        let hosts = "\
+# Staging servers
+10.0.0.5    docs.example.com  api.example.com
+fd00::5     docs.example.com  # IPv6
+";
+        let mut resolves = parse_hosts_file(hosts).unwrap();
+
+        assert_eq!(resolves.len(), 2);
+        assert!(resolves[0].matches("docs.example.com", 8080));
+        assert_eq!(
+            curl_lists(&resolves, None),
+            (
+                vec![],
+                vec![
+                    "docs.example.com::10.0.0.5:".to_string(),
+                    "api.example.com::10.0.0.5:".to_string()
+                ]
+            )
+        );
+        assert_eq!(
+            curl_lists(&resolves, Some(IpVersion::V6)).1,
+            vec!["docs.example.com::[fd00::5]:"]
+        );
+        assert!(parse_hosts_file("staging docs.example.com").is_err());
+
+        // Addresses given for a port take precedence.
+        resolves.insert(0, "docs.example.com:443:127.0.0.1".parse().unwrap());
+        assert_eq!(
+            curl_lists(&resolves, Some(IpVersion::V4)),
+            (
+                vec!["docs.example.com:443:127.0.0.1".to_string()],
+                vec![
+                    "docs.example.com:443:docs.example.com:443".to_string(),
+                    "docs.example.com::10.0.0.5:".to_string(),
+                    "api.example.com::10.0.0.5:".to_string()
+                ]
+            )
+        );
+        assert_eq!(curl_lists(&resolves[..1], None).1, Vec::<String>::new());
+    }
+}
@@ -0,0 +1,348 @@
+//! Rust doc comments and intra-doc links.
+
+use crate::{
+    lang::{self, Language},
+    markdown::{self, LinkKind},
+    parse::{self, LineIndex},
+    FileUrl,
+};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{collections::HashSet, ops::Range, path::Path};
+
+// Disambiguators that may prefix an intra-doc link, e.g. `struct@Config`.
+static DISAMBIGUATORS: &[&str] = &[
+    "const",
+    "constant",
+    "derive",
+    "enum",
+    "field",
+    "fn",
+    "function",
+    "macro",
+    "method",
+    "mod",
+    "module",
+    "prim",
+    "primitive",
+    "static",
+    "struct",
+    "trait",
+    "type",
+    "union",
+    "value",
+    "variant",
+];
+
+// Primitive types and prelude items, which always resolve.
+static PRELUDE: &[&str] = &[
+    "bool",
+    "char",
+    "f32",
+    "f64",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+    "isize",
+    "str",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "usize",
+    "Box",
+    "Clone",
+    "Copy",
+    "Default",
+    "Drop",
+    "Eq",
+    "Err",
+    "Fn",
+    "FnMut",
+    "FnOnce",
+    "From",
+    "Into",
+    "Iterator",
+    "None",
+    "Ok",
+    "Option",
+    "Ord",
+    "PartialEq",
+    "PartialOrd",
+    "Result",
+    "Send",
+    "Sized",
+    "Some",
+    "String",
+    "Sync",
+    "ToOwned",
+    "ToString",
+    "Vec",
+];
+
+// Crates whose items are always considered to resolve.
+static EXTERN_ROOTS: &[&str] = &["alloc", "core", "std"];
+
+// Path roots that refer to the current crate.
+static LOCAL_ROOTS: &[&str] = &["crate", "self", "Self", "super"];
+
+/// An intra-doc link found in a doc comment.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IntraDocLink {
+    /// The linked item path, without disambiguators.
+    pub path: String,
+    /// The byte range of the link target in the file.
+    pub span: Range<usize>,
+}
+
+/// Returns a copy of the Rust source `text` with only the contents of comments kept, along with
+/// the intra-doc links found in doc comments. Doc comment markers and intra-doc link targets are
+/// blanked out, so that the remaining text can be searched for URLs.
+pub fn comment_text(text: &str) -> (String, Vec<IntraDocLink>) {
+    lazy_static! {
+        // Leading `*` on the continuation lines of block comments.
+        static ref BLOCK_CONTINUATION: Regex = Regex::new(r"(?m)^[ \t]*(\*)").unwrap();
+    }
+
+    let mut comment_ranges = vec![];
+    let mut doc_ranges = vec![];
+
+    for range in lang::comment_ranges(text, Language::Rust) {
+        match doc_content(text, &range) {
+            Some(content) => {
+                comment_ranges.push(content.clone());
+                doc_ranges.push(content);
+            }
+            None => comment_ranges.push(range),
+        }
+    }
+
+    let mut searched = parse::keep_ranges(text, &comment_ranges);
+    let mut doc = parse::keep_ranges(text, &doc_ranges);
+
+    let stars: Vec<Range<usize>> = BLOCK_CONTINUATION
+        .captures_iter(&doc)
+        .map(|cap| cap.get(1).unwrap().range())
+        .collect();
+    for star in stars {
+        parse::blank_range(&mut doc, star.clone());
+        parse::blank_range(&mut searched, star);
+    }
+
+    let links = markdown::links(&doc);
+
+    // Labels with a link definition are ordinary Markdown links, not intra-doc links.
+    let definitions: HashSet<String> = links
+        .iter()
+        .filter(|link| link.kind == LinkKind::Definition)
+        .map(|link| doc[link.label.clone()].to_lowercase())
+        .collect();
+
+    let mut intra_doc_links = vec![];
+
+    for link in links {
+        if link.kind != LinkKind::Inline
+            && link.kind != LinkKind::Definition
+            && definitions.contains(&doc[link.label.clone()].to_lowercase())
+        {
+            continue;
+        }
+
+        if let Some(path) = intra_doc_path(&doc[link.target.clone()], link.kind) {
+            intra_doc_links.push(IntraDocLink {
+                path: path.to_string(),
+                span: link.target.clone(),
+            });
+            parse::blank_range(&mut searched, link.target);
+        }
+    }
+
+    (searched, intra_doc_links)
+}
+
+/// Returns the names of all items declared in the Rust source `text`.
+pub fn item_names(text: &str) -> Vec<&str> {
+    lazy_static! {
+        static ref ITEM: Regex = Regex::new(
+            r"\b(?:fn|struct|enum|union|trait|type|const|static(?:\s+mut)?|mod|macro_rules!)\s+(?:r#)?([A-Za-z_][A-Za-z0-9_]*)"
+        )
+        .unwrap();
+    }
+
+    ITEM.captures_iter(text)
+        .map(|cap| cap.get(1).unwrap().as_str())
+        .collect()
+}
+
+/// Intra-doc links and item names collected from the Rust files being checked, used to validate
+/// the links against the items declared in those files.
+#[derive(Debug, Default)]
+pub struct IntraDocLinks {
+    links: Vec<FileUrl>,
+    items: HashSet<String>,
+}
+
+impl IntraDocLinks {
+    /// Adds the intra-doc links and item names of a file.
+    pub fn add_file(&mut self, filepath: &Path, text: &str, links: Vec<IntraDocLink>) {
+        let lines = LineIndex::new(text);
+
+        self.items
+            .extend(item_names(text).into_iter().map(|name| name.to_string()));
+
+        for link in links {
+            let position = lines.position(link.span.start);
+            self.links.push(FileUrl {
+                intra_doc: true,
+                ..FileUrl::new(&link.path, filepath, position, link.span)
+            });
+        }
+    }
+
+    /// Returns all collected links, with `bad` set if the link did not resolve.
+    pub fn resolve(self) -> Vec<FileUrl> {
+        let items = self.items;
+
+        self.links
+            .into_iter()
+            .map(|mut file_url| {
+                file_url.bad = Some(!path_resolves(&file_url.url, &items));
+                file_url
+            })
+            .collect()
+    }
+}
+
+// Returns true if `path` resolves to a known item. Paths into external crates other than `std`,
+// `core` and `alloc` can't be resolved and are assumed to be valid.
+fn path_resolves(path: &str, items: &HashSet<String>) -> bool {
+    let path = path.split('#').next().unwrap_or(path);
+    let all: Vec<&str> = path.trim_start_matches("::").split("::").collect();
+    let local = LOCAL_ROOTS.contains(&all[0]);
+    let segments: Vec<&str> = all
+        .into_iter()
+        .filter(|segment| !LOCAL_ROOTS.contains(segment))
+        .collect();
+
+    let (first, last) = match (segments.first(), segments.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        // A path consisting only of `crate`, `self` or `super`.
+        _ => return true,
+    };
+
+    if EXTERN_ROOTS.contains(&first) || PRELUDE.contains(&last) {
+        return true;
+    }
+    if segments.len() > 1 && !local && !items.contains(first) {
+        // An external crate.
+        return true;
+    }
+
+    // Variants, fields and associated items are resolved through their parent.
+    items.contains(last) || segments.len() > 1 && items.contains(segments[segments.len() - 2])
+}
+
+// Returns the range of the doc comment contents, without the doc marker, if the comment at `range`
+// is a doc comment.
+fn doc_content(text: &str, range: &Range<usize>) -> Option<Range<usize>> {
+    let marker = &text[range.start - 2..range.start];
+    let content = &text[range.clone()];
+
+    let doc = match marker {
+        "//" => content.starts_with('!') || content.starts_with('/') && !content.starts_with("//"),
+        "/*" => content.starts_with('!') || content.starts_with('*') && !content.starts_with("**"),
+        _ => false,
+    };
+
+    if doc {
+        Some(range.start + 1..range.end)
+    } else {
+        None
+    }
+}
+
+// Returns the item path if `target` is an intra-doc link target.
+fn intra_doc_path(target: &str, kind: LinkKind) -> Option<&str> {
+    lazy_static! {
+        static ref PATH: Regex =
+            Regex::new(r"^(?:::)?[A-Za-z_][A-Za-z0-9_]*(?:::[A-Za-z_][A-Za-z0-9_]*)*(?:#\S*)?$")
+                .unwrap();
+    }
+
+    let target = target.trim();
+    let code = target.len() >= 2 && target.starts_with('`') && target.ends_with('`');
+    let mut path = target.trim_matches('`');
+
+    if let Some(i) = path.find('@') {
+        if DISAMBIGUATORS.contains(&&path[..i]) {
+            path = &path[i + 1..];
+        }
+    }
+    let path = path.trim_end_matches("()").trim_end_matches('!');
+
+    if !PATH.is_match(path) {
+        return None;
+    }
+
+    // Plain words in brackets are usually not meant as links.
+    match kind {
+        LinkKind::Shortcut if !code && !path.contains("::") => None,
+        _ => Some(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn doc_comment_text() {
+        let text = "/// See [`Config`] and [docs](crate::docs).\n\
+                    /// [Home][home], [optional] and [home].\n\
+                    ///\n\
+                    /// [home]:\n\
+                    ///     https://example.com/home\n\
+                    /**\n * [`Info`](struct@Info) https://a.com\n */\n\
+                    let s = \"[`Foo`] https://b.com\"; // https://c.com\n";
+
+        let (searched, links) = comment_text(text);
+        let paths: Vec<&str> = links.iter().map(|link| link.path.as_str()).collect();
+
+        assert_eq!(paths, vec!["Config", "crate::docs", "Info"]);
+        assert_eq!(&text[links[0].span.clone()], "`Config`");
+
+        assert_eq!(searched.len(), text.len());
+        assert!(!searched.contains("Config"));
+        assert!(!searched.contains("crate::docs"));
+        assert!(!searched.contains("///"));
+        assert!(!searched.contains("b.com"));
+        assert!(searched.contains("https://example.com/home"));
+        assert!(searched.contains("https://a.com"));
+        assert!(searched.contains("https://c.com"));
+    }
+
+    #[test]
+    fn resolve_paths() {
+        let items: HashSet<String> = item_names(
+            "pub struct Config;\nenum Kind { A }\nfn check_paths() {}\nmacro_rules! m {}",
+        )
+        .into_iter()
+        .map(|name| name.to_string())
+        .collect();
+
+        assert!(path_resolves("Config", &items));
+        assert!(path_resolves("crate::Config", &items));
+        assert!(path_resolves("self::check_paths", &items));
+        assert!(path_resolves("Kind::A", &items));
+        assert!(path_resolves("m", &items));
+        assert!(path_resolves("std::fmt::Display", &items));
+        assert!(path_resolves("Option", &items));
+        assert!(path_resolves("serde::Serialize", &items));
+        assert!(!path_resolves("Missing", &items));
+        assert!(!path_resolves("crate::Missing", &items));
+    }
+}
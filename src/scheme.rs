@@ -0,0 +1,375 @@
+//! URLs with schemes other than HTTP, which are validated offline.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{fmt, str::FromStr};
+
+/// A URL scheme, other than `http` and `https`, whose URLs can be found and validated offline.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Scheme {
+    /// `mailto:` URLs. Each address must have the syntax of an RFC 5322 address, such as
+    /// `support@example.com`.
+    Mailto,
+    /// `tel:` URLs. Global numbers must have the shape of an E.164 number, such as
+    /// `tel:+1-201-555-0123`, and local numbers must have a `phone-context`.
+    Tel,
+    /// `data:` URIs. The media type must be well-formed, as well as the data, which must be valid
+    /// base64 if the URI is base64-encoded.
+    Data,
+}
+
+impl Scheme {
+    /// All supported schemes.
+    pub const ALL: &'static [Scheme] = &[Scheme::Mailto, Scheme::Tel, Scheme::Data];
+
+    /// Returns the scheme of `url`, if it is supported.
+    pub fn of(url: &str) -> Option<Scheme> {
+        let (scheme, _) = split(url)?;
+        scheme.parse().ok()
+    }
+
+    /// Returns an error message if `url` is not well-formed.
+    pub fn validate(self, url: &str) -> Option<String> {
+        let rest = split(url).map_or("", |(_, rest)| rest);
+
+        match self {
+            Scheme::Mailto => validate_mailto(rest),
+            Scheme::Tel => validate_tel(rest),
+            Scheme::Data => validate_data(rest),
+        }
+    }
+}
+
+impl fmt::Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Scheme::*;
+
+        let name = match *self {
+            Mailto => "mailto",
+            Tel => "tel",
+            Data => "data",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Scheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Scheme::*;
+
+        Ok(match s.to_lowercase().as_str() {
+            "mailto" | "email" => Mailto,
+            "tel" | "phone" => Tel,
+            "data" => Data,
+            _ => return Err(format!("Unknown URL scheme: {}", s)),
+        })
+    }
+}
+
+/// Returns the byte ranges of the URLs with one of `schemes` in `text`.
+pub fn find(text: &str, schemes: &[Scheme]) -> Vec<(usize, usize)> {
+    lazy_static! {
+        // Addresses and headers, e.g. `mailto:a@example.com?subject=Hello%20there`.
+        static ref MAILTO: Regex =
+            Regex::new(r#"(?i-u:\bmailto):[^\s"'<>()\[\]{}|\\^`,;]*[^\s"'<>()\[\]{}|\\^`,;.:!?]"#)
+                .unwrap();
+        // Numbers with visual separators and parameters, e.g. `tel:+1-201-555-0123;ext=42`.
+        static ref TEL: Regex =
+            Regex::new(r#"(?i-u:\btel):[^\s"'<>\[\]{}|\\^`,]*[0-9A-Za-z)]"#).unwrap();
+        // The media type and parameters, then the data, e.g. `data:text/plain;base64,SGk=`.
+        static ref DATA: Regex =
+            Regex::new(r#"(?i-u:\bdata):[^\s"'<>()\[\]{}|\\^`,]*,[A-Za-z0-9+/=%._~!$&*;:@-]*"#)
+                .unwrap();
+    }
+
+    let mut ranges = vec![];
+
+    for scheme in schemes {
+        let regex: &Regex = match scheme {
+            Scheme::Mailto => &MAILTO,
+            Scheme::Tel => &TEL,
+            Scheme::Data => &DATA,
+        };
+
+        ranges.extend(regex.find_iter(text).map(|mat| {
+            // Phone numbers may be followed by a closing parenthesis, as in `(tel:+1-555-0100)`.
+            let url = mat.as_str();
+            let end = if url.ends_with(')') && url.matches('(').count() < url.matches(')').count() {
+                mat.end() - 1
+            } else {
+                mat.end()
+            };
+            (mat.start(), end)
+        }));
+    }
+
+    ranges.sort();
+    ranges
+}
+
+// Splits `url` into its scheme and the rest.
+fn split(url: &str) -> Option<(&str, &str)> {
+    let colon = url.find(':')?;
+    Some((&url[..colon], &url[colon + 1..]))
+}
+
+fn validate_mailto(rest: &str) -> Option<String> {
+    lazy_static! {
+        // The local part of an address, as a dot-atom or a quoted string.
+        static ref LOCAL: Regex = Regex::new(
+            r#"^([A-Za-z0-9!#$%&'*+/=?^_`{|}~-]+(\.[A-Za-z0-9!#$%&'*+/=?^_`{|}~-]+)*|"([^"\\]|\\.)*")$"#
+        )
+        .unwrap();
+        static ref DOMAIN: Regex = Regex::new(
+            r"^([A-Za-z0-9]([A-Za-z0-9-]{0,61}[A-Za-z0-9])?\.)+[A-Za-z]([A-Za-z0-9-]{0,61}[A-Za-z0-9])?$"
+        )
+        .unwrap();
+    }
+
+    let (addresses, headers) = match rest.find('?') {
+        Some(i) => (&rest[..i], &rest[i + 1..]),
+        None => (rest, ""),
+    };
+
+    // The addresses may all be given in headers instead, e.g. `mailto:?to=a@example.com`.
+    if addresses.is_empty() {
+        return if headers.split('&').any(|header| header.starts_with("to=")) {
+            None
+        } else {
+            Some("Missing email address.".to_string())
+        };
+    }
+
+    let address = match percent_decode(addresses) {
+        Some(address) => address,
+        None => return Some(format!("Invalid percent-encoding: {}", addresses)),
+    };
+
+    let valid = match address.rfind('@') {
+        Some(at) => {
+            let (local, domain) = (&address[..at], &address[at + 1..]);
+            // Internationalized domain names are validated in their ASCII form.
+            let domain = ::url::Host::parse(domain).map(|host| host.to_string());
+
+            LOCAL.is_match(local) && matches!(domain, Ok(domain) if DOMAIN.is_match(&domain))
+        }
+        None => false,
+    };
+
+    if valid {
+        None
+    } else {
+        Some(format!("Invalid email address: {}", address))
+    }
+}
+
+fn validate_tel(rest: &str) -> Option<String> {
+    let mut parts = rest.split(';');
+    let number = parts.next().unwrap_or("");
+    let params: Vec<&str> = parts.collect();
+
+    // Visual separators are ignored.
+    let digits: String = number
+        .chars()
+        .filter(|c| !matches!(c, '-' | '.' | '(' | ')'))
+        .collect();
+
+    let valid = if let Some(digits) = digits.strip_prefix('+') {
+        // E.164 numbers have at most 15 digits, and country codes don't start with 0.
+        (3..=15).contains(&digits.len())
+            && digits.chars().all(|c| c.is_ascii_digit())
+            && !digits.starts_with('0')
+    } else {
+        // Local numbers need a context, such as `tel:7042;phone-context=example.com`.
+        !digits.is_empty()
+            && digits
+                .chars()
+                .all(|c| c.is_ascii_hexdigit() || c == '*' || c == '#')
+            && params
+                .iter()
+                .any(|param| param.to_ascii_lowercase().starts_with("phone-context="))
+    };
+
+    if valid {
+        None
+    } else {
+        Some(format!("Invalid phone number: {}", number))
+    }
+}
+
+fn validate_data(rest: &str) -> Option<String> {
+    lazy_static! {
+        // An RFC 2045 token.
+        static ref TOKEN: &'static str = r"[A-Za-z0-9!#$&^_.+-]+";
+        static ref MEDIA_TYPE: Regex =
+            Regex::new(&format!(r"^{}/{}$", *TOKEN, *TOKEN)).unwrap();
+        static ref PARAMETER: Regex =
+            Regex::new(&format!(r"^{}=([A-Za-z0-9!#$&^_.+%-]+)$", *TOKEN)).unwrap();
+        static ref BASE64: Regex =
+            Regex::new(r"^([A-Za-z0-9+/]{4})*([A-Za-z0-9+/]{2}==|[A-Za-z0-9+/]{3}=)?$").unwrap();
+    }
+
+    let comma = match rest.find(',') {
+        Some(comma) => comma,
+        None => return Some("Missing data in data URI.".to_string()),
+    };
+    let (header, data) = (&rest[..comma], &rest[comma + 1..]);
+
+    let mut params: Vec<&str> = header.split(';').collect();
+    let base64 = matches!(params.last(), Some(param) if param.eq_ignore_ascii_case("base64"));
+    if base64 {
+        params.pop();
+    }
+
+    // A missing media type defaults to `text/plain;charset=US-ASCII`.
+    let (media_type, params) = params.split_first().unwrap_or((&"", &[]));
+    if !media_type.is_empty() && !MEDIA_TYPE.is_match(media_type) {
+        return Some(format!("Invalid media type in data URI: {}", media_type));
+    }
+    if let Some(param) = params.iter().find(|param| !PARAMETER.is_match(param)) {
+        return Some(format!("Invalid parameter in data URI: {}", param));
+    }
+
+    let data = match percent_decode(data) {
+        Some(data) => data,
+        None => return Some("Invalid percent-encoding in data URI.".to_string()),
+    };
+    if base64 && !BASE64.is_match(&data) {
+        return Some("Invalid base64 data in data URI.".to_string());
+    }
+
+    None
+}
+
+// Decodes the percent-encoded characters in `s`. Returns `None` if an escape is malformed, or if
+// the result is not valid UTF-8.
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn find_schemes() {
+        let text = "Write to mailto:support@example.com. Call (tel:+1-201-555-0123), \
+                    see data:text/plain;base64,SGk= or MAILTO:a@b.example?subject=Hi%21, \
+                    not metadata:x,y or mailto: alone.";
+        let urls = |schemes: &[Scheme]| -> Vec<&str> {
+            find(text, schemes)
+                .into_iter()
+                .map(|(start, end)| &text[start..end])
+                .collect()
+        };
+
+        assert_eq!(
+            urls(Scheme::ALL),
+            vec![
+                "mailto:support@example.com",
+                "tel:+1-201-555-0123",
+                "data:text/plain;base64,SGk=",
+                "MAILTO:a@b.example?subject=Hi%21",
+            ]
+        );
+        assert_eq!(urls(&[Scheme::Tel]), vec!["tel:+1-201-555-0123"]);
+        assert!(urls(&[]).is_empty());
+    }
+
+    #[test]
+    fn validate_schemes() {
+        let valid = [
+            "mailto:support@example.com",
+            "mailto:first.last+tag@mail.example.co.uk?subject=Hello%20there",
+            "mailto:%22a%22@example.com?",
+            "mailto:?to=support@example.com",
+            "mailto:info@m\u{fc}nchen.de",
+            "tel:+1-201-555-0123",
+            "tel:+44(0)20.7946.0958",
+            "tel:+1-201-555-0123;ext=42",
+            "tel:7042;phone-context=example.com",
+            "data:,Hello%2C%20World%21",
+            "data:text/plain;charset=UTF-8;base64,SGVsbG8=",
+            "data:image/png;base64,iVBORw0KGgo=",
+            "DATA:;base64,SGk=",
+            "data:base64,SGk=",
+            "data:,",
+        ];
+        let invalid = [
+            (
+                "mailto:support@exmaple",
+                "Invalid email address: support@exmaple",
+            ),
+            (
+                "mailto:support.example.com",
+                "Invalid email address: support.example.com",
+            ),
+            (
+                "mailto:a..b@example.com",
+                "Invalid email address: a..b@example.com",
+            ),
+            (
+                "mailto:a@-example.com",
+                "Invalid email address: a@-example.com",
+            ),
+            (
+                "mailto:a%2@example.com",
+                "Invalid percent-encoding: a%2@example.com",
+            ),
+            ("mailto:", "Missing email address."),
+            ("tel:555-0123", "Invalid phone number: 555-0123"),
+            (
+                "tel:+0-201-555-0123",
+                "Invalid phone number: +0-201-555-0123",
+            ),
+            (
+                "tel:+1234567890123456",
+                "Invalid phone number: +1234567890123456",
+            ),
+            ("tel:+1-CALL-NOW", "Invalid phone number: +1-CALL-NOW"),
+            ("data:text/plain", "Missing data in data URI."),
+            (
+                "data:text;base64,SGk=",
+                "Invalid media type in data URI: text",
+            ),
+            (
+                "data:text/plain;charset,abc",
+                "Invalid parameter in data URI: charset",
+            ),
+            ("data:;base64,SGk", "Invalid base64 data in data URI."),
+            ("data:,100%", "Invalid percent-encoding in data URI."),
+        ];
+
+        for url in valid.iter() {
+            assert_eq!(Scheme::of(url).unwrap().validate(url), None, "{}", url);
+        }
+        for (url, message) in invalid.iter() {
+            assert_eq!(
+                Scheme::of(url).unwrap().validate(url),
+                Some(message.to_string()),
+                "{}",
+                url
+            );
+        }
+        assert_eq!(Scheme::of("https://example.com/"), None);
+    }
+}
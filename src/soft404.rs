@@ -0,0 +1,214 @@
+//! Detection of soft 404s: missing pages answered with a good status code.
+
+use crate::{Error, Result};
+use lazy_static::lazy_static;
+use regex::bytes::{Regex, RegexBuilder};
+use std::{
+    collections::HashMap,
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+lazy_static! {
+    // Matches the title of an HTML page.
+    static ref REGEX_TITLE: Regex = Regex::new(r"(?is-u)<title[^>]*>(.*?)</title>").unwrap();
+    // Matches titles of pages that weren't found.
+    static ref REGEX_NOT_FOUND_TITLE: Regex = Regex::new(
+        r"(?i-u)\b(404|not found|(does not|doesn't|no longer) exists?|no longer available)\b"
+    )
+    .unwrap();
+}
+
+/// Why a page is suspected to be a soft 404.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Soft404 {
+    /// The title of the page says it wasn't found.
+    Title(String),
+    /// The page matches one of the patterns of `Config::soft_404_patterns`.
+    Pattern(String),
+    /// The page is the same as the one served for a path that doesn't exist on the host.
+    SameAsMissingPage,
+}
+
+impl fmt::Display for Soft404 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Soft404::Title(title) => write!(f, "the title is \"{}\"", title),
+            Soft404::Pattern(pattern) => write!(f, "the page matches \"{}\"", pattern),
+            Soft404::SameAsMissingPage => {
+                write!(f, "the page is the same as for a path that doesn't exist")
+            }
+        }
+    }
+}
+
+// A pattern matched against pages, with the pattern as given.
+struct Pattern {
+    regex: Regex,
+    pattern: String,
+}
+
+/// Detects soft 404s among pages that were answered with a good status code.
+pub struct Detector {
+    patterns: Vec<Pattern>,
+    // The page served for a missing path, by origin, if the host answered it with a good status.
+    missing_pages: HashMap<String, Option<Vec<u8>>>,
+}
+
+impl Detector {
+    /// Creates a detector matching pages against the case-insensitive regular expressions
+    /// `patterns`, in addition to the built-in checks.
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .unicode(false)
+                    .build()
+                    .map(|regex| Pattern {
+                        regex,
+                        pattern: pattern.clone(),
+                    })
+                    .map_err(|_| Error::InvalidRegex(pattern.clone()))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Detector {
+            patterns,
+            missing_pages: HashMap::new(),
+        })
+    }
+
+    /// Returns why the page `body` of `url` is suspected to be a soft 404, if it is. The page
+    /// served for a missing path on the same host is fetched once per host with `fetch`, which
+    /// returns the body if the status code of the response is good.
+    pub fn detect<F>(&mut self, url: &str, body: &[u8], fetch: F) -> Result<Option<Soft404>>
+    where
+        F: FnOnce(&str) -> Result<Option<Vec<u8>>>,
+    {
+        if let Some(title) = title(body) {
+            if REGEX_NOT_FOUND_TITLE.is_match(title.as_bytes()) {
+                return Ok(Some(Soft404::Title(title)));
+            }
+        }
+
+        if let Some(pattern) = self
+            .patterns
+            .iter()
+            .find(|pattern| pattern.regex.is_match(body))
+        {
+            return Ok(Some(Soft404::Pattern(pattern.pattern.clone())));
+        }
+
+        let url = match ::url::Url::parse(url) {
+            Ok(url) if url.has_host() => url,
+            _ => return Ok(None),
+        };
+        let origin = url.origin().ascii_serialization();
+
+        if !self.missing_pages.contains_key(&origin) {
+            let missing_url = missing_url(&origin);
+            let missing_page =
+                fetch(&missing_url)?.map(|page| without(&page, &missing_url[origin.len()..]));
+            self.missing_pages.insert(origin.clone(), missing_page);
+        }
+
+        Ok(match self.missing_pages[&origin] {
+            Some(ref missing_page) if *missing_page == without(body, url.path()) => {
+                Some(Soft404::SameAsMissingPage)
+            }
+            _ => None,
+        })
+    }
+}
+
+// Returns the URL of a path on the host of `origin` that shouldn't exist.
+fn missing_url(origin: &str) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.subsec_nanos());
+
+    format!("{}/loch-missing-page-{:08x}", origin, nanos)
+}
+
+// Returns the title of an HTML page, with whitespace collapsed.
+fn title(body: &[u8]) -> Option<String> {
+    let title = REGEX_TITLE.captures(body)?.get(1)?.as_bytes();
+    let title = String::from_utf8_lossy(title);
+
+    Some(title.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+// Returns `page` without the occurrences of `path`, as pages often mention the path requested.
+fn without(page: &[u8], path: &str) -> Vec<u8> {
+    let path = path.as_bytes();
+    if path.is_empty() || path == b"/" {
+        return page.to_vec();
+    }
+
+    let mut result = Vec::with_capacity(page.len());
+    let mut i = 0;
+    while i < page.len() {
+        if page[i..].starts_with(path) {
+            i += path.len();
+        } else {
+            result.push(page[i]);
+            i += 1;
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn detect_soft_404s() {
+        let mut detector = Detector::new(&["we couldn't find".to_string()]).unwrap();
+        let no_page = |_: &str| Ok(None);
+
+        let detect = |detector: &mut Detector, body: &str| {
+            detector
+                .detect("https://docs.rs/page", body.as_bytes(), no_page)
+                .unwrap()
+        };
+
+        assert_eq!(
+            detect(&mut detector, "<title>\n  Page Not Found\n</title>"),
+            Some(Soft404::Title("Page Not Found".to_string()))
+        );
+        assert_eq!(
+            detect(&mut detector, "<h1>Sorry, We Couldn't Find it</h1>"),
+            Some(Soft404::Pattern("we couldn't find".to_string()))
+        );
+        assert_eq!(detect(&mut detector, "<title>Found it</title>"), None);
+        assert!(Detector::new(&["(".to_string()]).is_err());
+    }
+
+    #[test]
+    fn same_as_missing_page() {
+        let mut detector = Detector::new(&[]).unwrap();
+        let missing = |url: &str| {
+            let path = &url["https://docs.rs".len()..];
+            Ok(Some(format!("Nothing at {}", path).into_bytes()))
+        };
+
+        assert_eq!(
+            detector
+                .detect("https://docs.rs/gone", b"Nothing at /gone", missing)
+                .unwrap(),
+            Some(Soft404::SameAsMissingPage)
+        );
+        // The missing page is only fetched once per host.
+        assert_eq!(
+            detector
+                .detect("https://docs.rs/page", b"A page", |_| unreachable!())
+                .unwrap(),
+            None
+        );
+    }
+}
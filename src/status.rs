@@ -0,0 +1,205 @@
+//! Rules deciding what the HTTP status code of a URL means.
+
+use crate::HostConfig;
+use std::{fmt, str::FromStr};
+
+/// A set of HTTP status codes, given as codes and ranges of codes, e.g. `403,500-599`.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct StatusCodes(Vec<(u32, u32)>);
+
+impl StatusCodes {
+    /// Returns true if the set contains `code`.
+    pub fn contains(&self, code: u32) -> bool {
+        self.0
+            .iter()
+            .any(|&(first, last)| first <= code && code <= last)
+    }
+
+    /// Returns true if the set contains no codes.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for StatusCodes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ranges: Vec<String> = self
+            .0
+            .iter()
+            .map(|&(first, last)| {
+                if first == last {
+                    first.to_string()
+                } else {
+                    format!("{}-{}", first, last)
+                }
+            })
+            .collect();
+
+        write!(f, "{}", ranges.join(","))
+    }
+}
+
+impl FromStr for StatusCodes {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = |code: &str| match code.trim().parse() {
+            Ok(code) if (100..=999).contains(&code) => Ok(code),
+            _ => Err(format!("Invalid status code: {}", code)),
+        };
+
+        s.split(',')
+            .filter(|range| !range.trim().is_empty())
+            .map(|range| match range.find('-') {
+                Some(i) => Ok((code(&range[..i])?, code(&range[i + 1..])?)),
+                None => code(range).map(|code| (code, code)),
+            })
+            .collect::<Result<_, _>>()
+            .map(StatusCodes)
+    }
+}
+
+/// What the status code of a URL means.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Verdict {
+    /// The URL is good.
+    Good,
+    /// The URL isn't bad, but is reported as a warning, e.g. when rate limited with 429.
+    Warning,
+    /// The URL is bad.
+    Bad,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Verdict::*;
+
+        let name = match *self {
+            Good => "good",
+            Warning => "warning",
+            Bad => "bad",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// The rule that decided what a status code means.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum StatusRule {
+    /// Codes from 200 to 399 are good, and all others bad.
+    Default,
+    /// The accepted and warning status codes of `Config`.
+    Global,
+    /// The accepted and warning status codes of the host settings for the given host.
+    Host(String),
+}
+
+impl fmt::Display for StatusRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StatusRule::Default => write!(f, "by default"),
+            StatusRule::Global => write!(f, "by global rule"),
+            StatusRule::Host(host) => write!(f, "by rule for {}", host),
+        }
+    }
+}
+
+/// The HTTP status code a URL was answered with, and what it was decided to mean.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Status {
+    /// The status code of the last response, after following redirects.
+    pub code: u32,
+    /// What the status code means.
+    pub verdict: Verdict,
+    /// The rule that decided the verdict.
+    pub rule: StatusRule,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({} {})", self.code, self.verdict, self.rule)
+    }
+}
+
+/// Decides what the status code `code` means. The rules of the hosts `hosts` matching the URL come
+/// first, in order, then the global rules `accept` and `warn`, then the default rule. Warning
+/// codes take precedence over accepted ones in the same rule.
+pub fn decide(
+    code: u32,
+    hosts: &[&HostConfig],
+    accept: &StatusCodes,
+    warn: &StatusCodes,
+) -> Status {
+    let verdict = |accept: &StatusCodes, warn: &StatusCodes| {
+        if warn.contains(code) {
+            Some(Verdict::Warning)
+        } else if accept.contains(code) {
+            Some(Verdict::Good)
+        } else {
+            None
+        }
+    };
+
+    let (verdict, rule) = hosts
+        .iter()
+        .find_map(|host| {
+            verdict(&host.accept_status, &host.warn_status)
+                .map(|verdict| (verdict, StatusRule::Host(host.host.clone())))
+        })
+        .or_else(|| verdict(accept, warn).map(|verdict| (verdict, StatusRule::Global)))
+        .unwrap_or_else(|| {
+            let verdict = if (200..400).contains(&code) {
+                Verdict::Good
+            } else {
+                Verdict::Bad
+            };
+            (verdict, StatusRule::Default)
+        });
+
+    Status {
+        code,
+        verdict,
+        rule,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_status_codes() {
+        let codes: StatusCodes = "403, 500-599,".parse().unwrap();
+
+        assert!(codes.contains(403));
+        assert!(codes.contains(503));
+        assert!(!codes.contains(404));
+        assert_eq!(codes.to_string(), "403,500-599");
+
+        assert!("abc".parse::<StatusCodes>().is_err());
+        assert!("4000".parse::<StatusCodes>().is_err());
+        assert!("".parse::<StatusCodes>().unwrap().is_empty());
+    }
+
+    #[test]
+    fn decide_rules() {
+        let codes = |s: &str| s.parse::<StatusCodes>().unwrap();
+        let linkedin = HostConfig::new("*.linkedin.com").accept_status(codes("403,999"));
+        let none = StatusCodes::default();
+        let hosts = [&linkedin];
+
+        let status = decide(999, &hosts, &none, &codes("429"));
+        assert_eq!(status.verdict, Verdict::Good);
+        assert_eq!(status.to_string(), "999 (good by rule for *.linkedin.com)");
+
+        let status = decide(429, &hosts, &none, &codes("429"));
+        assert_eq!(status.verdict, Verdict::Warning);
+        assert_eq!(status.rule, StatusRule::Global);
+
+        let status = decide(403, &[], &none, &codes("429"));
+        assert_eq!(status.to_string(), "403 (bad by default)");
+        assert_eq!(decide(301, &[], &none, &none).verdict, Verdict::Good);
+    }
+}
@@ -0,0 +1,903 @@
+//! Extraction of string values from structured data files (JSON, YAML and TOML).
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{char, collections::HashMap, ops::Range, path::Path};
+
+// The deepest nesting of arrays and tables parsed. Values nested deeper are ignored, along with
+// the rest of the document.
+const MAX_DEPTH: usize = 128;
+
+/// A structured data format.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DataFormat {
+    /// JSON (`.json`).
+    Json,
+    /// TOML (`.toml`).
+    Toml,
+    /// YAML (`.yml`, `.yaml`).
+    Yaml,
+}
+
+impl DataFormat {
+    /// Returns the data format of the file at `path`, based on its extension.
+    pub fn from_path(path: &Path) -> Option<DataFormat> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+
+        Some(match ext.as_str() {
+            "json" => DataFormat::Json,
+            "toml" => DataFormat::Toml,
+            "yml" | "yaml" => DataFormat::Yaml,
+            _ => return None,
+        })
+    }
+}
+
+/// A string value in a structured data file.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Value {
+    /// The path of keys leading to the value, e.g. `package.repository` or `servers[0].url`.
+    pub key_path: String,
+    /// The value, with escape sequences decoded.
+    pub text: String,
+    // The source offset of each byte in `text`.
+    offsets: Vec<usize>,
+}
+
+impl Value {
+    fn new(key_path: &str) -> Self {
+        Value {
+            key_path: key_path.to_string(),
+            ..Default::default()
+        }
+    }
+
+    // Creates a value from a verbatim slice of the source.
+    fn verbatim(key_path: &str, text: &str, start: usize) -> Self {
+        let mut value = Value::new(key_path);
+        value.push_str(text, start);
+        value
+    }
+
+    fn push(&mut self, c: char, offset: usize) {
+        self.offsets.extend(offset..offset + c.len_utf8());
+        self.text.push(c);
+    }
+
+    fn remove_prefix(&mut self, len: usize) {
+        self.text.drain(..len);
+        self.offsets.drain(..len);
+    }
+
+    fn push_str(&mut self, s: &str, start: usize) {
+        for (i, c) in s.char_indices() {
+            self.push(c, start + i);
+        }
+    }
+
+    /// Returns the offset in the source file of byte `i` of the value.
+    pub fn source_offset(&self, i: usize) -> usize {
+        self.offsets
+            .get(i)
+            .copied()
+            .unwrap_or_else(|| self.offsets.last().map_or(0, |offset| offset + 1))
+    }
+
+    /// Returns the range in the source file of the bytes of the value in `range`.
+    pub fn source_range(&self, range: Range<usize>) -> Range<usize> {
+        let start = self.source_offset(range.start);
+
+        if range.is_empty() {
+            start..start
+        } else {
+            start..self.source_offset(range.end - 1) + 1
+        }
+    }
+
+    /// Appends `other` to the value, keeping the source offsets of its bytes.
+    pub fn append(&mut self, other: &Value) {
+        self.text.push_str(&other.text);
+        self.offsets.extend_from_slice(&other.offsets);
+    }
+}
+
+/// Returns all string values in `text`, a document in the given format. Parsing is lenient:
+/// values are returned up to the first syntax error, or the first array or table nested more than
+/// 128 levels deep.
+pub fn values(text: &str, format: DataFormat) -> Vec<Value> {
+    let mut values = vec![];
+
+    match format {
+        DataFormat::Json => {
+            Scanner::new(text).json_value("", &mut values);
+        }
+        DataFormat::Toml => toml_values(text, &mut values),
+        DataFormat::Yaml => yaml_values(text, &mut values),
+    }
+
+    values
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn index(path: &str, i: usize) -> String {
+    format!("{}[{}]", path, i)
+}
+
+// How escape sequences are handled in a quoted string.
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Escapes {
+    // No escapes.
+    None,
+    // Backslash escapes.
+    Backslash,
+    // Backslash escapes, and a backslash at the end of a line skips all following whitespace.
+    BackslashContinuation,
+    // A doubled quote stands for a single quote.
+    Doubled,
+}
+
+struct Scanner<'a> {
+    text: &'a str,
+    pos: usize,
+    // The number of arrays and tables the scanner is in.
+    depth: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(text: &'a str) -> Self {
+        Scanner {
+            text,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    // Skips the opening bracket of an array or table. Returns `None` if it is nested too deeply.
+    fn open(&mut self) -> Option<()> {
+        self.bump();
+        self.depth += 1;
+
+        if self.depth > MAX_DEPTH {
+            None
+        } else {
+            Some(())
+        }
+    }
+
+    // Skips the closing bracket of an array or table.
+    fn close(&mut self) {
+        self.bump();
+        self.depth -= 1;
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_while(&mut self, f: impl Fn(char) -> bool) {
+        while self.peek().is_some_and(&f) {
+            self.bump();
+        }
+    }
+
+    // Skips whitespace, newlines and `#` comments.
+    fn skip_blank(&mut self, comments: bool) {
+        loop {
+            self.skip_while(char::is_whitespace);
+
+            if comments && self.peek() == Some('#') {
+                self.skip_while(|c| c != '\n');
+            } else {
+                break;
+            }
+        }
+    }
+
+    // Parses a string enclosed in `delim`, starting at the opening delimiter.
+    fn quoted(&mut self, key_path: &str, delim: &str, escapes: Escapes) -> Option<Value> {
+        let mut value = Value::new(key_path);
+
+        if !self.eat(delim) {
+            return None;
+        }
+
+        loop {
+            let start = self.pos;
+
+            if escapes == Escapes::Doubled && self.rest().starts_with(delim.repeat(2).as_str()) {
+                self.pos += delim.len() * 2;
+                value.push_str(delim, start);
+                continue;
+            }
+            if self.eat(delim) {
+                return Some(value);
+            }
+
+            let c = self.bump()?;
+            if c != '\\' || escapes == Escapes::None || escapes == Escapes::Doubled {
+                value.push(c, start);
+                continue;
+            }
+
+            let escaped = self.bump()?;
+            let decoded = match escaped {
+                'b' => '\u{8}',
+                'e' => '\u{1b}',
+                'f' => '\u{c}',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                '0' => '\0',
+                'x' => self.hex(2)?,
+                'u' => self.hex(4)?,
+                'U' => self.hex(8)?,
+                c if c.is_whitespace() && escapes == Escapes::BackslashContinuation => {
+                    self.skip_while(char::is_whitespace);
+                    continue;
+                }
+                c => c,
+            };
+
+            value.push(decoded, start);
+        }
+    }
+
+    // Parses a hexadecimal escape of `len` digits. UTF-16 surrogate pairs are combined.
+    fn hex(&mut self, len: usize) -> Option<char> {
+        let digits = self.rest().get(..len)?;
+        let code = u32::from_str_radix(digits, 16).ok()?;
+        self.pos += len;
+
+        if (0xD800..0xDC00).contains(&code) && self.rest().starts_with("\\u") {
+            let low = self
+                .rest()
+                .get(2..6)
+                .and_then(|digits| u32::from_str_radix(digits, 16).ok());
+
+            if let Some(low) = low.filter(|low| (0xDC00..0xE000).contains(low)) {
+                self.pos += 6;
+                return char::from_u32(0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00));
+            }
+        }
+
+        Some(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    // Skips an unquoted scalar, up to any of the `terminators` or whitespace.
+    fn skip_scalar(&mut self, terminators: &str) -> Option<()> {
+        let start = self.pos;
+        self.skip_while(|c| !c.is_whitespace() && !terminators.contains(c));
+
+        if self.pos == start {
+            None
+        } else {
+            Some(())
+        }
+    }
+
+    fn json_value(&mut self, key_path: &str, values: &mut Vec<Value>) -> Option<()> {
+        self.skip_blank(false);
+
+        match self.peek()? {
+            '{' => {
+                self.open()?;
+
+                loop {
+                    self.skip_blank(false);
+
+                    match self.peek()? {
+                        '}' => {
+                            self.close();
+                            return Some(());
+                        }
+                        ',' => {
+                            self.bump();
+                        }
+                        '"' => {
+                            let key = self.quoted("", "\"", Escapes::Backslash)?;
+                            self.skip_blank(false);
+                            if !self.eat(":") {
+                                return None;
+                            }
+
+                            self.json_value(&join(key_path, &key.text), values)?;
+                        }
+                        _ => return None,
+                    }
+                }
+            }
+            '[' => {
+                self.open()?;
+                let mut i = 0;
+
+                loop {
+                    self.skip_blank(false);
+
+                    match self.peek()? {
+                        ']' => {
+                            self.close();
+                            return Some(());
+                        }
+                        ',' => {
+                            self.bump();
+                        }
+                        _ => {
+                            self.json_value(&index(key_path, i), values)?;
+                            i += 1;
+                        }
+                    }
+                }
+            }
+            '"' => {
+                values.push(self.quoted(key_path, "\"", Escapes::Backslash)?);
+                Some(())
+            }
+            _ => self.skip_scalar(",]}"),
+        }
+    }
+
+    // Parses a TOML key, which may be dotted and quoted.
+    fn toml_key(&mut self) -> Option<String> {
+        let mut path = String::new();
+
+        loop {
+            self.skip_while(|c| c == ' ' || c == '\t');
+
+            let key = match self.peek()? {
+                '"' => self.quoted("", "\"", Escapes::Backslash)?.text,
+                '\'' => self.quoted("", "'", Escapes::None)?.text,
+                _ => {
+                    let start = self.pos;
+                    self.skip_while(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+                    if self.pos == start {
+                        return None;
+                    }
+                    self.text[start..self.pos].to_string()
+                }
+            };
+            path = join(&path, &key);
+
+            self.skip_while(|c| c == ' ' || c == '\t');
+            if !self.eat(".") {
+                return Some(path);
+            }
+        }
+    }
+
+    fn toml_keyval(&mut self, table: &str, values: &mut Vec<Value>) -> Option<()> {
+        let key = self.toml_key()?;
+
+        self.skip_while(|c| c == ' ' || c == '\t');
+        if !self.eat("=") {
+            return None;
+        }
+        self.skip_while(|c| c == ' ' || c == '\t');
+
+        self.toml_value(&join(table, &key), values)
+    }
+
+    fn toml_value(&mut self, key_path: &str, values: &mut Vec<Value>) -> Option<()> {
+        let rest = self.rest();
+
+        if rest.starts_with("\"\"\"") || rest.starts_with("'''") {
+            let delim = &rest[..3];
+            let escapes = if delim == "'''" {
+                Escapes::None
+            } else {
+                Escapes::BackslashContinuation
+            };
+            let mut value = self.quoted(key_path, delim, escapes)?;
+
+            // A newline immediately after the opening delimiter is trimmed.
+            if value.text.starts_with('\n') {
+                value.remove_prefix(1);
+            } else if value.text.starts_with("\r\n") {
+                value.remove_prefix(2);
+            }
+
+            values.push(value);
+            return Some(());
+        }
+
+        match self.peek()? {
+            '"' => values.push(self.quoted(key_path, "\"", Escapes::Backslash)?),
+            '\'' => values.push(self.quoted(key_path, "'", Escapes::None)?),
+            '[' => {
+                self.open()?;
+                let mut i = 0;
+
+                loop {
+                    self.skip_blank(true);
+
+                    match self.peek()? {
+                        ']' => {
+                            self.close();
+                            break;
+                        }
+                        ',' => {
+                            self.bump();
+                        }
+                        _ => {
+                            self.toml_value(&index(key_path, i), values)?;
+                            i += 1;
+                        }
+                    }
+                }
+            }
+            '{' => {
+                self.open()?;
+
+                loop {
+                    self.skip_blank(false);
+
+                    match self.peek()? {
+                        '}' => {
+                            self.close();
+                            break;
+                        }
+                        ',' => {
+                            self.bump();
+                        }
+                        _ => self.toml_keyval(key_path, values)?,
+                    }
+                }
+            }
+            _ => self.skip_scalar(",]}#")?,
+        }
+
+        Some(())
+    }
+}
+
+fn toml_values(text: &str, values: &mut Vec<Value>) {
+    let mut scanner = Scanner::new(text);
+    let mut table = String::new();
+    // The current index of each array of tables.
+    let mut arrays: HashMap<String, usize> = HashMap::new();
+
+    loop {
+        scanner.skip_blank(true);
+        if scanner.peek().is_none() {
+            break;
+        }
+
+        if scanner.eat("[") {
+            let array = scanner.eat("[");
+            let keys = match scanner.toml_key() {
+                Some(keys) => keys,
+                None => break,
+            };
+
+            // Insert the current index of any arrays of tables into the path.
+            let mut path = String::new();
+            let segments: Vec<&str> = keys.split('.').collect();
+            for (i, segment) in segments.iter().enumerate() {
+                path = join(&path, segment);
+
+                if array && i == segments.len() - 1 {
+                    let next = arrays.get(&path).map_or(0, |i| i + 1);
+                    arrays.insert(path.clone(), next);
+                    path = index(&path, next);
+                } else if let Some(i) = arrays.get(&path) {
+                    path = index(&path, *i);
+                }
+            }
+
+            table = path;
+            scanner.skip_while(|c| c != '\n');
+        } else if scanner.toml_keyval(&table, values).is_none() {
+            // Skip the rest of an invalid line.
+            scanner.skip_while(|c| c != '\n');
+        }
+    }
+}
+
+// A YAML block collection: a mapping opened by a key without a value, or a sequence item.
+struct Frame {
+    indent: usize,
+    key_path: String,
+    // Whether this frame was opened by a key, in which case sequence items may share its indent.
+    key: bool,
+    // The number of sequence items found in this frame so far.
+    items: usize,
+}
+
+// Returns the key and the length of the key including the `:`, if `content` starts with a key.
+fn yaml_key(content: &str) -> Option<(String, usize)> {
+    lazy_static! {
+        static ref KEY: Regex =
+            Regex::new(r#"^(?:"((?:[^"\\]|\\.)*)"|'((?:[^']|'')*)'|([^\s#'"\[\]{},&*!|>%@`-][^#]*?|-[^\s#]*?))[ \t]*:(?:[ \t]|$)"#)
+                .unwrap();
+    }
+
+    let cap = KEY.captures(content)?;
+    let key = cap
+        .get(1)
+        .or_else(|| cap.get(2))
+        .or_else(|| cap.get(3))
+        .unwrap()
+        .as_str()
+        .trim_end();
+    let len = cap.get(0).unwrap().as_str().trim_end().len();
+
+    Some((key.to_string(), len))
+}
+
+// Returns the indentation of `line`, or `None` if it is blank.
+fn yaml_indent(line: &str) -> Option<usize> {
+    let content = line.trim_start_matches(' ');
+
+    if content.trim().is_empty() {
+        None
+    } else {
+        Some(line.len() - content.len())
+    }
+}
+
+// Returns the range of the line starting at `pos`, without the line break, and the position of
+// the next line.
+fn line_at(text: &str, pos: usize) -> (&str, usize) {
+    match text[pos..].find('\n') {
+        Some(i) => (&text[pos..pos + i], pos + i + 1),
+        None => (&text[pos..], text.len()),
+    }
+}
+
+fn yaml_values(text: &str, values: &mut Vec<Value>) {
+    let mut stack = vec![Frame {
+        indent: 0,
+        key_path: String::new(),
+        key: true,
+        items: 0,
+    }];
+    let mut pos = 0;
+
+    while pos < text.len() {
+        let (line, next) = line_at(text, pos);
+        let trimmed = line.trim_start_matches(' ');
+        let indent = line.len() - trimmed.len();
+
+        if trimmed.trim().is_empty()
+            || trimmed.starts_with('#')
+            || trimmed.starts_with("---")
+            || trimmed.starts_with("...")
+            || trimmed.starts_with('%')
+        {
+            pos = next;
+            continue;
+        }
+
+        let item = trimmed.trim_end() == "-" || trimmed.starts_with("- ");
+        while stack.len() > 1 {
+            let frame = stack.last().unwrap();
+            if frame.indent > indent || frame.indent == indent && !(item && frame.key) {
+                stack.pop();
+            } else {
+                break;
+            }
+        }
+
+        let mut column = indent;
+        let mut content = trimmed.trim_end_matches('\r');
+        let mut key_path = stack.last().unwrap().key_path.clone();
+
+        // Sequence items, possibly nested.
+        while content == "-" || content.starts_with("- ") {
+            let parent = stack.last_mut().unwrap();
+            key_path = index(&parent.key_path, parent.items);
+            parent.items += 1;
+
+            let rest = content[1..].trim_start_matches(' ');
+            stack.push(Frame {
+                indent: column,
+                key_path: key_path.clone(),
+                key: false,
+                items: 0,
+            });
+            column += content.len() - rest.len();
+            content = rest;
+        }
+        if stack.len() > MAX_DEPTH {
+            return;
+        }
+
+        // Multi-line values must be indented further than their key, or their sequence item.
+        let mut parent_indent = indent;
+
+        if let Some((key, len)) = yaml_key(content) {
+            key_path = join(&key_path, &key);
+            parent_indent = column;
+
+            let rest = &content[len..];
+            let value = rest.trim_start_matches(&[' ', '\t'][..]);
+            if value.is_empty() || value.starts_with('#') {
+                if stack.len() == MAX_DEPTH {
+                    return;
+                }
+                stack.push(Frame {
+                    indent: column,
+                    key_path,
+                    key: true,
+                    items: 0,
+                });
+                pos = next;
+                continue;
+            }
+
+            column += len + rest.len() - value.len();
+            content = value;
+        }
+
+        pos = if content.is_empty() {
+            next
+        } else {
+            yaml_value(text, pos + column, parent_indent, &key_path, values)
+        };
+    }
+}
+
+// Parses the YAML value at `start` and returns the position of the next line to parse. Lines
+// indented further than `indent` continue the value.
+fn yaml_value(
+    text: &str,
+    start: usize,
+    indent: usize,
+    key_path: &str,
+    values: &mut Vec<Value>,
+) -> usize {
+    let (line, mut next) = line_at(text, start);
+    let mut scanner = Scanner::new(text);
+    scanner.pos = start;
+
+    // Skip anchors and tags.
+    while scanner.peek() == Some('&') || scanner.peek() == Some('!') {
+        scanner.skip_while(|c| !c.is_whitespace());
+        scanner.skip_while(|c| c == ' ' || c == '\t');
+    }
+    if scanner.pos >= next || scanner.peek() == Some('*') {
+        return next;
+    }
+
+    let quoted = match scanner.peek() {
+        Some('"') => Some(("\"", Escapes::BackslashContinuation)),
+        Some('\'') => Some(("'", Escapes::Doubled)),
+        _ => None,
+    };
+    if let Some((delim, escapes)) = quoted {
+        if let Some(value) = scanner.quoted(key_path, delim, escapes) {
+            values.push(value);
+        }
+        return line_at(text, scanner.pos).1;
+    }
+
+    let mut value = Value::new(key_path);
+
+    if line.starts_with('|') || line.starts_with('>') {
+        // Block scalars continue on all following lines that are blank or more indented.
+        while next < text.len() {
+            let (line, after) = line_at(text, next);
+
+            match yaml_indent(line) {
+                Some(line_indent) if line_indent <= indent => break,
+                Some(line_indent) => value.push_str(&line[line_indent..], next + line_indent),
+                None => (),
+            }
+            value.push('\n', after - 1);
+            next = after;
+        }
+    } else if line.starts_with('[') || line.starts_with('{') {
+        // Flow collections are kept verbatim.
+        value = Value::verbatim(key_path, line, start);
+    } else {
+        // Plain scalars end at a comment, and continue on more indented lines.
+        let end = line.find(" #").unwrap_or(line.len());
+        value = Value::verbatim(key_path, line[..end].trim_end(), start);
+
+        while next < text.len() {
+            let (line, after) = line_at(text, next);
+
+            match yaml_indent(line) {
+                Some(line_indent) if line_indent > indent && !line.trim().starts_with('#') => {
+                    let end = line.find(" #").unwrap_or(line.len());
+                    value.push('\n', next - 1);
+                    value.push_str(line[line_indent..end].trim_end(), next + line_indent);
+                }
+                _ => break,
+            }
+            next = after;
+        }
+    }
+
+    values.push(value);
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn paths_and_values(text: &str, format: DataFormat) -> Vec<(String, String)> {
+        values(text, format)
+            .into_iter()
+            .map(|value| (value.key_path, value.text))
+            .collect()
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(path, value)| (path.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn json() {
+        let text = r#"{
+  "homepage": "https://example.com",
+  "servers": [{"url": "https:\/\/api.example.com\u0021"}, 42, null],
+  "nested": {"$ref": "https://example.com/schema.json#/defs/a", "ok": true}
+}"#;
+
+        assert_eq!(
+            paths_and_values(text, DataFormat::Json),
+            pairs(&[
+                ("homepage", "https://example.com"),
+                ("servers[0].url", "https://api.example.com!"),
+                ("nested.$ref", "https://example.com/schema.json#/defs/a"),
+            ])
+        );
+
+        let values = values(text, DataFormat::Json);
+        assert_eq!(&text[values[0].source_offset(0)..][..8], "https://");
+        assert_eq!(&text[values[1].source_offset(6)..][..2], "\\/");
+        assert_eq!(&text[values[0].source_range(0..19)], "https://example.com");
+        assert_eq!(&text[values[1].source_range(0..9)], "https:\\/\\/a");
+    }
+
+    #[test]
+    fn toml() {
+        let text = r#"
+[package]
+name = "loch" # https://comment.example.com
+repository = 'https://github.com/m-cat/loch'
+"description" = """
+A long description: \
+    https://example.com/wrapped"""
+
+[[bin]]
+links = ["https://a.com", { url = "https://b.com" }]
+
+[[bin]]
+doc.url = "https://c.com"
+
+[bin.meta]
+x = 1
+y = "https://d.com"
+"#;
+
+        assert_eq!(
+            paths_and_values(text, DataFormat::Toml),
+            pairs(&[
+                ("package.name", "loch"),
+                ("package.repository", "https://github.com/m-cat/loch"),
+                (
+                    "package.description",
+                    "A long description: https://example.com/wrapped"
+                ),
+                ("bin[0].links[0]", "https://a.com"),
+                ("bin[0].links[1].url", "https://b.com"),
+                ("bin[1].doc.url", "https://c.com"),
+                ("bin[1].meta.y", "https://d.com"),
+            ])
+        );
+    }
+
+    #[test]
+    fn yaml() {
+        let text = r#"# https://comment.example.com
+openapi: 3.0.0
+servers:
+- url: https://api.example.com/v1 # Production
+  description: "Main \"server\""
+- url: 'https://staging.example.com'
+info:
+  license:
+    name: MIT
+    url: https://opensource.org/licenses/MIT
+  description: |
+    See https://docs.example.com
+    for details.
+steps:
+  - uses: actions/checkout@v2
+  - run: >
+      curl https://example.com/install.sh
+  - - https://nested.example.com
+tags: [a, "https://flow.example.com"]
+"#;
+
+        assert_eq!(
+            paths_and_values(text, DataFormat::Yaml),
+            pairs(&[
+                ("openapi", "3.0.0"),
+                ("servers[0].url", "https://api.example.com/v1"),
+                ("servers[0].description", "Main \"server\""),
+                ("servers[1].url", "https://staging.example.com"),
+                ("info.license.name", "MIT"),
+                ("info.license.url", "https://opensource.org/licenses/MIT"),
+                (
+                    "info.description",
+                    "See https://docs.example.com\nfor details.\n"
+                ),
+                ("steps[0].uses", "actions/checkout@v2"),
+                ("steps[1].run", "curl https://example.com/install.sh\n"),
+                ("steps[2][0]", "https://nested.example.com"),
+                ("tags", "[a, \"https://flow.example.com\"]"),
+            ])
+        );
+    }
+
+    #[test]
+    fn nesting_depth() {
+        let nested = |open: &str, close: &str, depth: usize| {
+            format!(
+                "{}\"https://deep.example.com\"{}",
+                open.repeat(depth),
+                close.repeat(depth)
+            )
+        };
+
+        let text = format!(
+            r#"{{"a": "https://a.com", "b": {}, "c": {}}}"#,
+            nested("[", "]", MAX_DEPTH - 1),
+            nested("[", "]", 100_000)
+        );
+        let values = paths_and_values(&text, DataFormat::Json);
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[1].1, "https://deep.example.com");
+
+        let text = format!(
+            "a = \"https://a.com\"\nb = {}\n",
+            nested("{ x = ", " }", 100_000)
+        );
+        assert_eq!(
+            paths_and_values(&text, DataFormat::Toml),
+            pairs(&[("a", "https://a.com")])
+        );
+
+        let text = (0..MAX_DEPTH * 2).fold(String::from("a: https://a.com\n"), |text, i| {
+            text + &" ".repeat(i) + "b:\n"
+        }) + &" ".repeat(MAX_DEPTH * 2)
+            + "c: https://deep.example.com\n";
+        assert_eq!(
+            paths_and_values(&text, DataFormat::Yaml),
+            pairs(&[("a", "https://a.com")])
+        );
+    }
+}
@@ -0,0 +1,94 @@
+//! Inline directives that suppress links, such as `loch-ignore-line`.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+/// Lines of a file whose links are suppressed by inline directives. Directives can appear in any
+/// comment syntax, or anywhere else on a line:
+///
+/// + `loch-ignore-line` suppresses the links on its own line.
+/// + `loch-ignore-next-line` suppresses the links on the following line.
+/// + `loch-disable` suppresses the links on every line from its own up to the line of the next
+///   `loch-enable`, or to the end of the file.
+#[derive(Debug, Default)]
+pub struct Suppressions {
+    // Whether each line is suppressed, by 0-based line number.
+    lines: Vec<bool>,
+}
+
+impl Suppressions {
+    /// Finds the directives in `text`, the contents of a file.
+    pub fn new(text: &str) -> Self {
+        lazy_static! {
+            static ref DIRECTIVE: Regex =
+                Regex::new(r"\bloch-(ignore-line|ignore-next-line|disable|enable)\b").unwrap();
+        }
+
+        // Skip the search for most files.
+        if !text.contains("loch-") {
+            return Default::default();
+        }
+
+        let mut lines = vec![];
+        let mut disabled = false;
+        let mut ignore_next = false;
+
+        for line in text.lines() {
+            let mut suppressed = disabled || ignore_next;
+            ignore_next = false;
+
+            for cap in DIRECTIVE.captures_iter(line) {
+                match &cap[1] {
+                    "ignore-line" => suppressed = true,
+                    "ignore-next-line" => ignore_next = true,
+                    "disable" => {
+                        disabled = true;
+                        suppressed = true;
+                    }
+                    _ => disabled = false,
+                }
+            }
+
+            lines.push(suppressed);
+        }
+
+        Suppressions { lines }
+    }
+
+    /// Returns true if the links on the 1-based `line` are suppressed.
+    pub fn is_suppressed(&self, line: usize) -> bool {
+        line > 0 && self.lines.get(line - 1).cloned().unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn directives() {
+        let text = "\
+a // loch-ignore-line
+b
+# loch-ignore-next-line
+c
+d
+<!-- loch-disable -->
+e
+f <!-- loch-enable -->
+g
+/* loch-ignore-lines */
+h
+loch-disable";
+        let suppressions = Suppressions::new(text);
+
+        assert_eq!(
+            (0..=13)
+                .filter(|line| suppressions.is_suppressed(*line))
+                .collect::<Vec<_>>(),
+            vec![1, 4, 6, 7, 8, 12]
+        );
+        assert!(!Suppressions::new("a\nb").is_suppressed(1));
+    }
+}
@@ -0,0 +1,229 @@
+//! Timeouts for the phases of a request.
+
+use std::{
+    fmt, io,
+    net::{SocketAddr, ToSocketAddrs},
+    str::FromStr,
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+/// A phase of a request, with its own timeout.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum TimeoutPhase {
+    /// Resolving the host name.
+    Dns,
+    /// Connecting to the host, including the TLS handshake. Also includes resolving the host
+    /// name, unless the DNS timeout is set.
+    Connect,
+    /// Waiting for the first byte of the response, from the start of the request.
+    FirstByte,
+    /// The whole request, from its start.
+    Total,
+}
+
+impl TimeoutPhase {
+    /// All phases, in the order they happen.
+    pub const ALL: [TimeoutPhase; 4] = [
+        TimeoutPhase::Dns,
+        TimeoutPhase::Connect,
+        TimeoutPhase::FirstByte,
+        TimeoutPhase::Total,
+    ];
+
+    // The name of the phase in `Timeouts` settings.
+    fn key(self) -> &'static str {
+        use TimeoutPhase::*;
+
+        match self {
+            Dns => "dns",
+            Connect => "connect",
+            FirstByte => "first-byte",
+            Total => "total",
+        }
+    }
+}
+
+impl fmt::Display for TimeoutPhase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use TimeoutPhase::*;
+
+        let name = match *self {
+            Dns => "DNS",
+            Connect => "connect",
+            FirstByte => "first byte",
+            Total => "total",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for TimeoutPhase {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TimeoutPhase::ALL
+            .iter()
+            .copied()
+            .find(|phase| phase.key() == s.trim().to_lowercase())
+            .ok_or_else(|| format!("Unknown timeout phase: {}", s))
+    }
+}
+
+/// The timeouts of the phases of a request. Phases without a timeout are left to curl, which only
+/// limits connecting, to 300 seconds.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Timeouts {
+    /// The time to resolve the host name in.
+    pub dns: Option<Duration>,
+    /// The time to connect in, from the start of the request.
+    pub connect: Option<Duration>,
+    /// The time to receive the first byte of the response in, from the start of the request.
+    pub first_byte: Option<Duration>,
+    /// The time to complete the request in.
+    pub total: Option<Duration>,
+}
+
+impl Timeouts {
+    /// Returns the timeout of `phase`.
+    pub fn get(&self, phase: TimeoutPhase) -> Option<Duration> {
+        use TimeoutPhase::*;
+
+        match phase {
+            Dns => self.dns,
+            Connect => self.connect,
+            FirstByte => self.first_byte,
+            Total => self.total,
+        }
+    }
+
+    /// Set the timeout of `phase`.
+    pub fn set(mut self, phase: TimeoutPhase, timeout: Duration) -> Self {
+        use TimeoutPhase::*;
+
+        let field = match phase {
+            Dns => &mut self.dns,
+            Connect => &mut self.connect,
+            FirstByte => &mut self.first_byte,
+            Total => &mut self.total,
+        };
+        *field = Some(timeout);
+        self
+    }
+
+    /// Returns these timeouts, with those not set taken from `other`.
+    pub fn or(self, other: Timeouts) -> Self {
+        Timeouts {
+            dns: self.dns.or(other.dns),
+            connect: self.connect.or(other.connect),
+            first_byte: self.first_byte.or(other.first_byte),
+            total: self.total.or(other.total),
+        }
+    }
+
+    /// Returns which of the timeouts of `phases` expires first, if any is set.
+    pub fn first_of(&self, phases: &[TimeoutPhase]) -> Option<TimeoutPhase> {
+        phases
+            .iter()
+            .copied()
+            .filter(|&phase| self.get(phase).is_some())
+            .min_by_key(|&phase| self.get(phase))
+    }
+}
+
+impl fmt::Display for Timeouts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let timeouts: Vec<String> = TimeoutPhase::ALL
+            .iter()
+            .filter_map(|&phase| {
+                self.get(phase)
+                    .map(|timeout| format!("{}:{}", phase.key(), timeout.as_millis()))
+            })
+            .collect();
+
+        write!(f, "{}", timeouts.join(","))
+    }
+}
+
+impl FromStr for Timeouts {
+    type Err = String;
+
+    /// Parses timeouts in milliseconds, given as e.g. `connect:500,total:5000`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .filter(|timeout| !timeout.trim().is_empty())
+            .try_fold(Timeouts::default(), |timeouts, timeout| {
+                let i = timeout
+                    .find(':')
+                    .ok_or_else(|| format!("'{}': expected PHASE:MILLISECONDS", timeout))?;
+                let phase = TimeoutPhase::from_str(&timeout[..i])?;
+                let millis = timeout[i + 1..]
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("Invalid timeout: {}", &timeout[i + 1..]))?;
+
+                Ok(timeouts.set(phase, Duration::from_millis(millis)))
+            })
+    }
+}
+
+/// Resolves `host` within `timeout`. Returns `None` if resolving took longer, in which case it is
+/// left to finish in the background.
+pub fn resolve(host: &str, port: u16, timeout: Duration) -> Option<io::Result<Vec<SocketAddr>>> {
+    let (sender, receiver) = mpsc::channel();
+    let host = host.to_string();
+
+    thread::spawn(move || {
+        let addrs = (host.as_str(), port)
+            .to_socket_addrs()
+            .map(|addrs| addrs.collect());
+        // The receiver is gone if resolving timed out.
+        let _ = sender.send(addrs);
+    });
+
+    receiver.recv_timeout(timeout).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_timeouts() {
+        let timeouts: Timeouts = "connect:500, first-byte:2000,".parse().unwrap();
+
+        assert_eq!(timeouts.connect, Some(Duration::from_millis(500)));
+        assert_eq!(timeouts.dns, None);
+        assert_eq!(timeouts.to_string(), "connect:500,first-byte:2000");
+        assert_eq!(
+            timeouts.first_of(&[TimeoutPhase::FirstByte, TimeoutPhase::Total]),
+            Some(TimeoutPhase::FirstByte)
+        );
+
+        let timeouts = timeouts.or("total:1000,connect:100".parse().unwrap());
+        assert_eq!(
+            timeouts.to_string(),
+            "connect:500,first-byte:2000,total:1000"
+        );
+        assert_eq!(
+            timeouts.first_of(&[TimeoutPhase::FirstByte, TimeoutPhase::Total]),
+            Some(TimeoutPhase::Total)
+        );
+
+        assert!("connect".parse::<Timeouts>().is_err());
+        assert!("connect:1.5".parse::<Timeouts>().is_err());
+        assert!("lookup:100".parse::<Timeouts>().is_err());
+    }
+
+    #[test]
+    fn resolve_hosts() {
+        let addrs = resolve("127.0.0.1", 80, Duration::from_secs(5));
+        assert_eq!(
+            addrs.unwrap().unwrap(),
+            vec!["127.0.0.1:80".parse::<SocketAddr>().unwrap()]
+        );
+    }
+}
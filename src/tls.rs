@@ -0,0 +1,80 @@
+//! TLS options.
+
+use curl::easy::SslVersion;
+use std::{fmt, str::FromStr};
+
+/// A version of TLS.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum TlsVersion {
+    /// TLS 1.0.
+    V1_0,
+    /// TLS 1.1.
+    V1_1,
+    /// TLS 1.2.
+    V1_2,
+    /// TLS 1.3.
+    V1_3,
+}
+
+impl TlsVersion {
+    /// Returns the curl option value requiring at least this version.
+    pub fn ssl_version(self) -> SslVersion {
+        use TlsVersion::*;
+
+        match self {
+            V1_0 => SslVersion::Tlsv10,
+            V1_1 => SslVersion::Tlsv11,
+            V1_2 => SslVersion::Tlsv12,
+            V1_3 => SslVersion::Tlsv13,
+        }
+    }
+}
+
+impl fmt::Display for TlsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use TlsVersion::*;
+
+        let name = match *self {
+            V1_0 => "1.0",
+            V1_1 => "1.1",
+            V1_2 => "1.2",
+            V1_3 => "1.3",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for TlsVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use TlsVersion::*;
+
+        let lower = s.to_lowercase();
+        let version = lower.trim_start_matches("tls").trim_start_matches('v');
+
+        Ok(match version {
+            "1" | "1.0" => V1_0,
+            "1.1" => V1_1,
+            "1.2" => V1_2,
+            "1.3" => V1_3,
+            _ => return Err(format!("Unknown TLS version: {}", s)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_versions() {
+        assert_eq!("1.2".parse(), Ok(TlsVersion::V1_2));
+        assert_eq!("TLSv1.3".parse(), Ok(TlsVersion::V1_3));
+        assert_eq!("tls1".parse(), Ok(TlsVersion::V1_0));
+        assert!("1.4".parse::<TlsVersion>().is_err());
+        assert_eq!(TlsVersion::V1_1.to_string(), "1.1");
+    }
+}
@@ -45,12 +45,105 @@ pub fn url_matches_url_pattern(url: &str, url_pattern: &str) -> Result<bool> {
     Ok(url_matches_pattern(url, &pattern))
 }
 
+/// Returns the ASCII form of `url`, used to compare and check URLs. Internationalized domain names
+/// are converted to punycode and other non-ASCII characters are percent-encoded, so that
+/// `https://münchen.de/straße` becomes `https://xn--mnchen-3ya.de/stra%C3%9Fe`. URLs that can't be
+/// parsed, such as URLs without a scheme, are returned unchanged.
+pub fn normalize(url: &str) -> String {
+    match ::url::Url::parse(url) {
+        Ok(parsed) if parsed.has_host() => parsed.into(),
+        _ => url.to_string(),
+    }
+}
+
+/// Returns the scheme, host and port of `url`, such as `https://docs.rs` or `http://localhost:8080`,
+/// which are shared by the URLs that can reuse a connection. The port is left out if it is the
+/// default of a web scheme, but kept for other schemes, e.g. `sftp://files.example.com:22`. URLs
+/// without a host, such as URLs without a scheme, are returned unchanged.
+pub fn origin(url: &str) -> String {
+    let parsed = match ::url::Url::parse(url) {
+        Ok(parsed) if parsed.has_host() => parsed,
+        _ => return url.to_string(),
+    };
+
+    match parsed.origin() {
+        origin @ ::url::Origin::Tuple(..) => origin.ascii_serialization(),
+        // Schemes the `url` crate doesn't know the default port of, such as `sftp:`, have no
+        // origin of their own.
+        ::url::Origin::Opaque(_) => {
+            let scheme = parsed.scheme();
+            let host = parsed.host_str().unwrap_or_default().to_lowercase();
+            let default_port = match scheme {
+                "sftp" => Some(22),
+                _ => None,
+            };
+
+            match parsed.port().or(default_port) {
+                Some(port) => format!("{}://{}:{}", scheme, host, port),
+                None => format!("{}://{}", scheme, host),
+            }
+        }
+    }
+}
+
+/// Returns `url` with its user name and password, if any, replaced with `***`, so that it can be
+/// printed. The scheme is optional, as in proxy URLs.
+pub fn redact_userinfo(url: &str) -> String {
+    let start = url.find("://").map_or(0, |i| i + 3);
+    let end = url[start..]
+        .find(&['/', '?', '#'][..])
+        .map_or(url.len(), |i| start + i);
+
+    match url[start..end].rfind('@') {
+        Some(i) => format!("{}***{}", &url[..start], &url[start + i..]),
+        None => url.to_string(),
+    }
+}
+
+/// Returns the `https://` equivalent of an `http://` URL.
+pub fn https_equivalent(url: &str) -> Option<String> {
+    url.get(..7)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("http://"))
+        .map(|_| format!("https://{}", &url[7..]))
+}
+
+/// Returns `target` with the fragment of `url`, if `url` has one and `target` doesn't. Fragments
+/// aren't sent to servers, so they are lost when a URL redirects.
+pub fn with_fragment(target: &str, url: &str) -> String {
+    match url.find('#') {
+        Some(i) if !target.contains('#') => format!("{}{}", target, &url[i..]),
+        _ => target.to_string(),
+    }
+}
+
+/// Returns the file transfer protocol of `url`, `ftp` or `sftp`, if it has one.
+pub fn file_transfer_protocol(url: &str) -> Option<&'static str> {
+    let scheme = url.split(':').next()?.to_lowercase();
+
+    ["ftp", "sftp"]
+        .iter()
+        .find(|protocol| **protocol == scheme)
+        .cloned()
+}
+
+/// Returns true if libcurl was built with support for `protocol`. SFTP, for one, requires libssh2.
+pub fn is_protocol_supported(protocol: &str) -> bool {
+    lazy_static! {
+        static ref PROTOCOLS: Vec<String> = curl::Version::get()
+            .protocols()
+            .map(str::to_string)
+            .collect();
+    }
+
+    PROTOCOLS.iter().any(|supported| supported == protocol)
+}
+
 /// Returns prefix if present, list of domain elements in order, and the path if present.
 pub fn split_pattern(url_pattern: &str) -> Option<(Option<&str>, Vec<&str>, Vec<&str>)> {
     lazy_static! {
         static ref PATTERN_PARTS: Regex = Regex::new(
             // Three match groups: the prefix, the domains, and the path.
-            &r"(https?:/?/?)?([^/]+)(/[^/]*)*"
+            r"((?:https?|s?ftp):/?/?)?([^/]+)(/[^/]*)*"
         )
         .unwrap();
     }
@@ -78,6 +171,94 @@ pub fn split_pattern(url_pattern: &str) -> Option<(Option<&str>, Vec<&str>, Vec<
 mod tests {
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn normalize_urls() {
+        use super::normalize;
+
+        assert_eq!(
+            normalize("https://münchen.de/straße"),
+            "https://xn--mnchen-3ya.de/stra%C3%9Fe"
+        );
+        assert_eq!(
+            normalize("https://xn--mnchen-3ya.de/stra%C3%9Fe"),
+            "https://xn--mnchen-3ya.de/stra%C3%9Fe"
+        );
+        assert_eq!(
+            normalize("http://例え.テスト/パス?q=ä#ü"),
+            "http://xn--r8jz45g.xn--zckzah/%E3%83%91%E3%82%B9?q=%C3%A4#%C3%BC"
+        );
+        assert_eq!(normalize("https://Example.com"), "https://example.com/");
+        assert_eq!(normalize("münchen.de/straße"), "münchen.de/straße");
+    }
+
+    #[test]
+    fn origins() {
+        use super::origin;
+
+        assert_eq!(origin("https://Docs.rs/loch/?q=a#b"), "https://docs.rs");
+        assert_eq!(
+            origin("http://user@localhost:8080/a"),
+            "http://localhost:8080"
+        );
+        assert_eq!(origin("https://docs.rs:443/"), "https://docs.rs");
+        assert_eq!(origin("docs.rs/loch"), "docs.rs/loch");
+        assert_eq!(
+            origin("sftp://user@Files.example.com/pub/a.txt"),
+            "sftp://files.example.com:22"
+        );
+        assert_eq!(
+            origin("sftp://files.example.com:2222/b.txt"),
+            "sftp://files.example.com:2222"
+        );
+        assert_eq!(
+            origin("ftp://files.example.com/c.txt"),
+            "ftp://files.example.com"
+        );
+    }
+
+    #[test]
+    fn redacted_urls() {
+        use super::redact_userinfo;
+
+        assert_eq!(
+            redact_userinfo("http://user:p@ss@proxy.example.com:3128/"),
+            "http://***@proxy.example.com:3128/"
+        );
+        assert_eq!(
+            redact_userinfo("user:secret@proxy.example.com:3128"),
+            "***@proxy.example.com:3128"
+        );
+        assert_eq!(
+            redact_userinfo("http://proxy.example.com/a@b"),
+            "http://proxy.example.com/a@b"
+        );
+    }
+
+    #[test]
+    fn replacement_urls() {
+        use super::{https_equivalent, with_fragment};
+
+        assert_eq!(
+            https_equivalent("HTTP://example.com/a"),
+            Some("https://example.com/a".to_string())
+        );
+        assert_eq!(https_equivalent("https://example.com/"), None);
+        assert_eq!(https_equivalent("ftp://example.com/"), None);
+
+        assert_eq!(
+            with_fragment("https://b.com/docs", "http://a.com/docs#usage"),
+            "https://b.com/docs#usage"
+        );
+        assert_eq!(
+            with_fragment("https://b.com/docs#top", "http://a.com/docs#usage"),
+            "https://b.com/docs#top"
+        );
+        assert_eq!(
+            with_fragment("https://b.com/", "http://a.com/"),
+            "https://b.com/"
+        );
+    }
+
     // TODO: Un-ignore this test
     #[ignore]
     #[test]
@@ -1,6 +1,6 @@
 //! Utility.
 
-use crate::error::Result;
+use crate::Result;
 use atty::{self, Stream};
 use std::{env, io::Write};
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
@@ -0,0 +1,94 @@
+#![cfg(feature = "archive")]
+
+extern crate loch;
+
+mod util;
+
+use loch::{Config, FileUrl, Placeholder, Result};
+
+static TEST_DIR: &str = "tests/archive_dir/";
+
+// Construct an unchecked FileUrl at the given line and column of a file inside an archive.
+fn fileurl(
+    url: &str,
+    filepath: &str,
+    entry: Option<&str>,
+    (line, column): (usize, usize),
+    span: (usize, usize),
+) -> FileUrl {
+    FileUrl {
+        entry: entry.map(|entry| entry.into()),
+        ..util::fileurl(
+            url,
+            &format!("{}{}", TEST_DIR, filepath),
+            (line, column),
+            span,
+        )
+    }
+}
+
+// Test that compressed files and the files inside archives are searched.
+#[test]
+fn search_archives() -> Result<()> {
+    let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
+        .search_archives()
+        .no_check()
+        .silent();
+
+    let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
+
+    util::assert_list_eq(
+        &info.file_urls,
+        &[
+            fileurl(
+                "https://intro.example.com/",
+                "docs.zip",
+                Some("docs/intro.md"),
+                (12, 5),
+                (29, 55),
+            ),
+            fileurl(
+                "https://guide.example.com/",
+                "bundle.tar.gz",
+                Some("guide/README.md"),
+                (2, 1),
+                (6, 32),
+            ),
+            fileurl(
+                "https://nested.example.com/",
+                "bundle.tar.gz",
+                Some("guide/notes.txt.gz"),
+                (1, 9),
+                (8, 35),
+            ),
+            fileurl(
+                "https://release.example.com/",
+                "release-notes.md.gz",
+                None,
+                (1, 11),
+                (10, 38),
+            ),
+        ],
+    );
+    assert_eq!(info.num_files, 3);
+    assert_eq!(info.num_binary_files, 1);
+
+    Ok(())
+}
+
+// Test that archives are skipped as binary files by default.
+#[test]
+fn search_archives_disabled() -> Result<()> {
+    let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
+        .no_check()
+        .silent();
+
+    let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
+
+    assert!(info.file_urls.is_empty());
+    assert_eq!(info.num_binary_files, 3);
+
+    Ok(())
+}
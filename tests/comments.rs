@@ -0,0 +1,144 @@
+extern crate loch;
+
+mod util;
+
+use loch::{Config, FileUrl, Language, Placeholder, Result};
+
+static TEST_DIR: &str = "tests/comments_dir/";
+
+// Construct an unchecked FileUrl at the given line and column.
+fn fileurl(
+    url: &str,
+    filepath: &str,
+    (line, column): (usize, usize),
+    span: (usize, usize),
+) -> FileUrl {
+    util::fileurl(
+        url,
+        &format!("{}{}", TEST_DIR, filepath),
+        (line, column),
+        span,
+    )
+}
+
+// Construct a resolved intra-doc link.
+fn intra_doc_link(
+    path: &str,
+    filepath: &str,
+    position: (usize, usize),
+    span: (usize, usize),
+    bad: bool,
+) -> FileUrl {
+    FileUrl {
+        intra_doc: true,
+        bad: Some(bad),
+        ..fileurl(path, filepath, position, span)
+    }
+}
+
+// Test that only comments are searched in source files of the given languages.
+#[test]
+fn comments_only() -> Result<()> {
+    let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
+        .comments_only(&[Language::Rust])
+        .no_check()
+        .silent();
+
+    let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
+
+    util::assert_list_eq(
+        &info.file_urls,
+        &[
+            fileurl(
+                "https://docs.example.com/crate",
+                "code.rs",
+                (1, 17),
+                (16, 46),
+            ),
+            fileurl("https://block.example.com/", "code.rs", (4, 8), (104, 130)),
+            fileurl(
+                "https://trailing.example.com/",
+                "code.rs",
+                (5, 17),
+                (150, 179),
+            ),
+            fileurl("https://home.example.com/", "docs.rs", (5, 9), (108, 133)),
+            fileurl(
+                "https://docstring.example.com/",
+                "script.py",
+                (1, 17),
+                (16, 46),
+            ),
+            fileurl("https://string.example.com/", "script.py", (3, 8), (58, 85)),
+            fileurl(
+                "https://comment.example.com/",
+                "script.py",
+                (3, 40),
+                (90, 118),
+            ),
+            fileurl("https://text.example.com/", "notes.txt", (1, 1), (0, 25)),
+        ],
+    );
+
+    Ok(())
+}
+
+// Test that all lines are searched by default.
+#[test]
+fn comments_only_disabled() -> Result<()> {
+    let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
+        .no_check()
+        .silent();
+
+    let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
+
+    assert_eq!(info.file_urls.len(), 9);
+    assert!(info
+        .file_urls
+        .iter()
+        .any(|file_url| file_url.url == "https://string.example.com/"
+            && file_url.filepath.ends_with("code.rs")));
+
+    Ok(())
+}
+
+// Test that intra-doc links are validated against the items declared in Rust files.
+#[test]
+fn check_intra_doc_links() -> Result<()> {
+    let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
+        .check_intra_doc_links()
+        .exclude_paths(&["*.py", "*.txt"])
+        .no_check()
+        .silent();
+
+    let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
+
+    util::assert_list_eq(
+        &info.file_urls,
+        &[
+            fileurl(
+                "https://docs.example.com/crate",
+                "code.rs",
+                (1, 17),
+                (16, 46),
+            ),
+            fileurl("https://block.example.com/", "code.rs", (4, 8), (104, 130)),
+            fileurl(
+                "https://trailing.example.com/",
+                "code.rs",
+                (5, 17),
+                (150, 179),
+            ),
+            fileurl("https://home.example.com/", "docs.rs", (5, 9), (108, 133)),
+            intra_doc_link("Config", "docs.rs", (1, 10), (9, 17), false),
+            intra_doc_link("crate::guide", "docs.rs", (1, 36), (35, 47), false),
+            intra_doc_link("Missing", "docs.rs", (2, 23), (72, 81), true),
+        ],
+    );
+    assert_eq!(info.num_bad_urls, 1);
+
+    Ok(())
+}
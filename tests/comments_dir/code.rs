@@ -0,0 +1,5 @@
+//! Crate docs: https://docs.example.com/crate
+const URL: &str = "https://string.example.com/";
+
+/* See https://block.example.com/ */
+fn main() {} // https://trailing.example.com/
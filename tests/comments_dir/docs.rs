@@ -0,0 +1,10 @@
+//! See [`Config`] and the [guide](crate::guide).
+//! [Home][home] and [`Missing`].
+//!
+//! [home]:
+//!     https://home.example.com/
+
+/// Configuration.
+pub struct Config;
+
+pub mod guide {}
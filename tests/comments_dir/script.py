@@ -0,0 +1,3 @@
+"""Module docs: https://docstring.example.com/"""
+
+URL = "https://string.example.com/"  # https://comment.example.com/
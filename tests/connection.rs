@@ -0,0 +1,68 @@
+extern crate loch;
+
+mod server;
+
+use loch::{Config, Info, Placeholder, Result};
+use server::{Fixture, Response};
+
+// Checks links to the paths `paths` of a site, given with the host the site is reached as.
+fn check(links: &[(&str, &str)]) -> Result<(Info, String)> {
+    let port = server::serve(|_| Response::ok("ok"));
+    let links = links
+        .iter()
+        .map(|(host, path)| format!("+ http://{}:{}{}\n", host, port, path))
+        .collect();
+    let fixture = Fixture::new(&[("links.md", links)]);
+
+    // The server's address is local.
+    let config = Config::default()
+        .check_placeholders(&[Placeholder::Local])
+        .silent();
+    let info = loch::check_paths(&[fixture.path_str()], Some(&config))?;
+
+    Ok((info, format!(":{}", port)))
+}
+
+// Test that the connection to a host is reused for all its URLs.
+#[test]
+fn reuse_connections() -> Result<()> {
+    let (info, _) = check(&[
+        ("127.0.0.1", "/a"),
+        ("127.0.0.1", "/b"),
+        ("127.0.0.1", "/c"),
+        ("localhost", "/d"),
+    ])?;
+
+    assert_eq!(info.num_bad_urls, 0);
+    assert_eq!(info.num_requests, 4);
+    assert_eq!(info.num_connections, 2);
+
+    Ok(())
+}
+
+// Test that URLs are checked in groups by host.
+#[test]
+fn group_urls_by_host() -> Result<()> {
+    let (info, port) = check(&[
+        ("127.0.0.1", "/a"),
+        ("localhost", "/b"),
+        ("user@127.0.0.1", "/c"),
+    ])?;
+
+    let urls: Vec<String> = info
+        .file_urls
+        .iter()
+        .map(|file_url| file_url.url.replace(&port, ""))
+        .collect();
+
+    assert_eq!(
+        urls,
+        vec![
+            "http://127.0.0.1/a",
+            "http://user@127.0.0.1/c",
+            "http://localhost/b"
+        ]
+    );
+
+    Ok(())
+}
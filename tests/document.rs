@@ -0,0 +1,161 @@
+#![cfg(any(feature = "office", feature = "pdf"))]
+
+extern crate loch;
+
+mod util;
+
+use loch::{Config, FileUrl, Placeholder, Result};
+
+static TEST_DIR: &str = "tests/document_dir/";
+
+// Construct an unchecked FileUrl at the given page, paragraph or cell of a document.
+fn fileurl(
+    url: &str,
+    filepath: &str,
+    (page, paragraph, key_path): (Option<usize>, Option<usize>, Option<&str>),
+    (line, column): (usize, usize),
+    span: (usize, usize),
+) -> FileUrl {
+    FileUrl {
+        key_path: key_path.map(|key_path| key_path.into()),
+        page,
+        paragraph,
+        ..util::fileurl(
+            url,
+            &format!("{}{}", TEST_DIR, filepath),
+            (line, column),
+            span,
+        )
+    }
+}
+
+// Returns the URLs found in the documents with the extension `ext`.
+fn document_urls(ext: &str) -> Result<Vec<FileUrl>> {
+    let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
+        .no_check()
+        .silent();
+
+    let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
+
+    Ok(info
+        .file_urls
+        .into_iter()
+        .filter(|file_url| file_url.filepath.extension().unwrap() == ext)
+        .collect())
+}
+
+// Test that the text and link annotations of each page of a PDF are searched.
+#[cfg(feature = "pdf")]
+#[test]
+fn pdf() -> Result<()> {
+    util::assert_list_eq(
+        &document_urls("pdf")?,
+        &[
+            fileurl(
+                "https://paper.example.com/",
+                "paper.pdf",
+                (Some(1), None, None),
+                (1, 10),
+                (9, 35),
+            ),
+            fileurl(
+                "https://annotation.example.com/",
+                "paper.pdf",
+                (Some(1), None, None),
+                (0, 0),
+                (0, 0),
+            ),
+        ],
+    );
+
+    Ok(())
+}
+
+// Test that the text and hyperlinks of each paragraph of a Word document are searched.
+#[cfg(feature = "office")]
+#[test]
+fn word() -> Result<()> {
+    util::assert_list_eq(
+        &document_urls("docx")?,
+        &[
+            fileurl(
+                "https://handbook.example.com/",
+                "report.docx",
+                (None, Some(2), None),
+                (1, 10),
+                (9, 17),
+            ),
+            fileurl(
+                "https://data.example.com/q3",
+                "report.docx",
+                (None, Some(3), None),
+                (1, 12),
+                (11, 38),
+            ),
+        ],
+    );
+
+    Ok(())
+}
+
+// Test that the cells, hyperlinks and HYPERLINK formulas of a spreadsheet are searched.
+#[cfg(feature = "office")]
+#[test]
+fn spreadsheet() -> Result<()> {
+    util::assert_list_eq(
+        &document_urls("xlsx")?,
+        &[
+            fileurl(
+                "https://vendor.example.com/prices",
+                "budget.xlsx",
+                (None, None, Some("Costs!A2")),
+                (1, 5),
+                (4, 37),
+            ),
+            fileurl(
+                "https://invoice.example.com/",
+                "budget.xlsx",
+                (None, None, Some("Costs!B2")),
+                (0, 0),
+                (0, 0),
+            ),
+            fileurl(
+                "https://contract.example.com/",
+                "budget.xlsx",
+                (None, None, Some("Costs!C3")),
+                (0, 0),
+                (0, 0),
+            ),
+        ],
+    );
+
+    Ok(())
+}
+
+// Test that the text and hyperlinks of each slide of a presentation are searched.
+#[cfg(feature = "office")]
+#[test]
+fn presentation() -> Result<()> {
+    util::assert_list_eq(
+        &document_urls("pptx")?,
+        &[
+            fileurl(
+                "https://slides.example.com/",
+                "talk.pptx",
+                (Some(2), Some(1), None),
+                (1, 11),
+                (10, 37),
+            ),
+            fileurl(
+                "https://demo.example.com/",
+                "talk.pptx",
+                (Some(2), Some(2), None),
+                (1, 1),
+                (0, 4),
+            ),
+        ],
+    );
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+extern crate loch;
+
+mod util;
+
+use loch::{Config, Error, FileUrl, Placeholder, Result};
+
+static TEST_DIR: &str = "tests/encoding_dir/";
+
+// Construct an unchecked FileUrl at the given line and column.
+fn fileurl(
+    url: &str,
+    filepath: &str,
+    (line, column): (usize, usize),
+    span: (usize, usize),
+) -> FileUrl {
+    util::fileurl(
+        url,
+        &format!("{}{}", TEST_DIR, filepath),
+        (line, column),
+        span,
+    )
+}
+
+// Test that UTF-16 and Latin-1 files are decoded, and binary and undecodable files are counted.
+#[test]
+fn detect_encodings() -> Result<()> {
+    let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
+        .no_check()
+        .silent();
+
+    let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
+
+    util::assert_list_eq(
+        &info.file_urls,
+        &[
+            fileurl(
+                "https://utf16.example.com/docs",
+                "utf16.txt",
+                (2, 5),
+                (19, 49),
+            ),
+            fileurl(
+                "https://latin1.example.com/menu",
+                "latin1.html",
+                (3, 22),
+                (70, 101),
+            ),
+        ],
+    );
+    assert_eq!(info.num_files, 4);
+    assert_eq!(info.num_binary_files, 1);
+    assert_eq!(info.num_undecodable_files, 1);
+
+    Ok(())
+}
+
+// Test that a given encoding is used instead of detecting it.
+#[test]
+fn forced_encoding() -> Result<()> {
+    let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
+        .encoding("latin1")
+        .exclude_paths(&["*.bin", "*.html"])
+        .no_check()
+        .silent();
+
+    let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
+
+    util::assert_list_eq(
+        &info.file_urls,
+        &[fileurl(
+            "https://broken.example.com/",
+            "broken.txt",
+            (1, 9),
+            (12, 39),
+        )],
+    );
+    assert_eq!(info.num_undecodable_files, 0);
+
+    let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
+        .encoding("no-such-encoding")
+        .silent();
+
+    match loch::check_paths(&[TEST_DIR], Some(&config)) {
+        Err(Error::InvalidEncoding(label)) => assert_eq!(label, "no-such-encoding"),
+        res => panic!("unexpected result: {:?}", res.map(|info| info.file_urls)),
+    }
+
+    Ok(())
+}
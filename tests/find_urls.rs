@@ -2,33 +2,51 @@ extern crate loch;
 
 mod util;
 
-use loch::{Config, FileUrl, Result};
-use std::path::PathBuf;
+use loch::{Config, Exclusion, FileUrl, Placeholder, Result};
 
 static TEST_DIR: &str = "tests/test_dir/";
 
-// Construct a FileUrl.
-fn fileurl(url: &str, filepath: &str, line: usize, bad: bool, excluded: bool) -> FileUrl {
+// Construct a FileUrl at the given line and column.
+fn fileurl(
+    url: &str,
+    filepath: &str,
+    (line, column): (usize, usize),
+    span: (usize, usize),
+    bad: bool,
+    excluded: Option<Exclusion>,
+) -> FileUrl {
     FileUrl {
-        url: url.into(),
-        filepath: PathBuf::from(format!("{}{}", TEST_DIR, filepath)),
-        line,
         bad: Some(bad),
         excluded,
+        ..util::fileurl(
+            url,
+            &format!("{}{}", TEST_DIR, filepath),
+            (line, column),
+            span,
+        )
     }
 }
 
 #[test]
 fn find_urls_http() -> Result<()> {
-    let config = Config::default().silent();
+    let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
+        .silent();
 
     let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
 
     util::assert_list_eq(
         &info.file_urls,
         &[
-            fileurl("http://www.example.co", "example", 1, true, false),
-            fileurl("http:////test", "test.txt", 1, true, false),
+            fileurl(
+                "http://www.example.co",
+                "example",
+                (1, 1),
+                (0, 21),
+                true,
+                None,
+            ),
+            fileurl("http:////test", "test.txt", (1, 1), (0, 13), true, None),
         ],
     );
     assert_eq!(info.num_urls, 2);
@@ -39,18 +57,35 @@ fn find_urls_http() -> Result<()> {
 
 #[test]
 fn find_urls_nohttp() -> Result<()> {
-    let config = Config::default().no_http().silent();
+    let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
+        .no_http()
+        .silent();
 
     let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
 
     util::assert_list_eq(
         &info.file_urls,
         &[
-            fileurl("google.com/", "test.rs", 2, false, false),
-            fileurl("domains.google.com/", "test.rs", 3, false, false),
-            fileurl("testing.test/page", "test", 1, true, false),
-            fileurl("http://www.example.co", "example", 1, true, false),
-            fileurl("example.com/", "example.txt", 1, false, false),
+            fileurl("google.com/", "test.rs", (2, 3), (24, 35), false, None),
+            fileurl(
+                "domains.google.com/",
+                "test.rs",
+                (3, 4),
+                (39, 58),
+                false,
+                None,
+            ),
+            fileurl("testing.test/page", "test", (1, 1), (0, 17), true, None),
+            fileurl(
+                "http://www.example.co",
+                "example",
+                (1, 1),
+                (0, 21),
+                true,
+                None,
+            ),
+            fileurl("example.com/", "example.txt", (1, 1), (0, 12), false, None),
         ],
     );
     assert_eq!(info.num_urls, 5);
@@ -64,6 +99,7 @@ fn find_urls_nohttp() -> Result<()> {
 #[test]
 fn find_urls_nohttp_excluded() -> Result<()> {
     let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
         .no_http()
         .exclude_urls(&["google.com", "http://www.example.co"])
         .silent();
@@ -73,8 +109,8 @@ fn find_urls_nohttp_excluded() -> Result<()> {
     util::assert_list_eq(
         &info.file_urls,
         &[
-            fileurl("testing.test/page", "test", 1, true, false),
-            fileurl("example.com/", "example.txt", 1, false, false),
+            fileurl("testing.test/page", "test", (1, 1), (0, 17), true, None),
+            fileurl("example.com/", "example.txt", (1, 1), (0, 12), false, None),
         ],
     );
     assert_eq!(info.num_urls, 2);
@@ -0,0 +1,147 @@
+extern crate loch;
+
+mod server;
+
+use loch::{Config, Placeholder, Result};
+use server::{Fixture, Response};
+use std::fs;
+
+fn utf16le(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(|c| c.to_le_bytes()).collect()
+}
+
+// Serves a site with a moved page, and returns the base URL.
+fn site() -> String {
+    let port = server::serve(|request| match request.path.as_str() {
+        "/new" => Response::ok("new"),
+        "/old" => Response::redirect(301, "/new"),
+        "/temp" => Response::redirect(302, "/new"),
+        _ => Response::status(404),
+    });
+
+    format!("http://127.0.0.1:{}", port)
+}
+
+fn config() -> Config {
+    // The server's address is local.
+    Config::default()
+        .check_placeholders(&[Placeholder::Local])
+        .silent()
+}
+
+// Returns files linking to `base`: Markdown with CRLF line endings, Latin-1 and UTF-16 text.
+fn files(base: &str) -> Vec<(&'static str, Vec<u8>)> {
+    let markdown = format!(
+        "# Links\r\n\r\n\
+         + [Old]({0}/old) and [usage]({0}/old#usage)\r\n\
+         + <{0}/temp> {0}/new {0}/missing\r\n\
+         + [Wrapped]({0}/\r\nold)\r\n",
+        base
+    );
+    let latin1 = [b"Caf\xe9: ", format!("{}/old\n", base).as_bytes()].concat();
+    let utf16 = [
+        &b"\xff\xfe"[..],
+        &utf16le(&format!("\u{e9} {}/old\r\n", base)),
+    ]
+    .concat();
+
+    vec![
+        ("links.md", markdown.into_bytes()),
+        ("latin1.txt", latin1),
+        ("utf16.txt", utf16),
+    ]
+}
+
+fn fixture(files: &[(&'static str, Vec<u8>)]) -> Fixture {
+    let fixture = Fixture::new(&[]);
+
+    for (name, bytes) in files {
+        fs::write(fixture.path().join(name), bytes).unwrap();
+    }
+
+    fixture
+}
+
+// Test that permanently redirected URLs are rewritten in place, keeping everything else.
+#[test]
+fn fix_urls() -> Result<()> {
+    let base = site();
+    let before = files(&base);
+    let fixture = fixture(&before);
+
+    let info = loch::check_paths(&[fixture.path_str()], Some(&config().fix()))?;
+
+    assert_eq!(info.num_fixed_urls, 4);
+
+    let fixed = |name: &str| fs::read(fixture.path().join(name)).unwrap();
+    let replace = |bytes: &[u8], from: &[u8], to: &[u8]| -> Vec<u8> {
+        let mut bytes = bytes.to_vec();
+        let i = bytes
+            .windows(from.len())
+            .position(|window| window == from)
+            .unwrap();
+        bytes.splice(i..i + from.len(), to.iter().cloned());
+        bytes
+    };
+
+    // The temporary redirect and the wrapped URL are left as they are.
+    let markdown = String::from_utf8(before[0].1.clone()).unwrap();
+    assert_eq!(
+        String::from_utf8(fixed("links.md")).unwrap(),
+        markdown
+            .replacen("/old)", "/new)", 1)
+            .replacen("/old#usage", "/new#usage", 1)
+    );
+    assert_eq!(fixed("latin1.txt"), replace(&before[1].1, b"/old", b"/new"));
+    assert_eq!(
+        fixed("utf16.txt"),
+        replace(&before[2].1, &utf16le("/old"), &utf16le("/new"))
+    );
+
+    Ok(())
+}
+
+// Test that a dry run leaves files unchanged.
+#[test]
+fn dry_run() -> Result<()> {
+    let base = site();
+    let before = files(&base);
+    let fixture = fixture(&before);
+
+    let info = loch::check_paths(&[fixture.path_str()], Some(&config().dry_run()))?;
+
+    assert_eq!(info.num_fixed_urls, 4);
+    for (name, bytes) in before {
+        assert_eq!(fs::read(fixture.path().join(name))?, bytes);
+    }
+
+    Ok(())
+}
+
+// Test that the replacements of URLs are recorded without fixing.
+#[test]
+fn replacements() -> Result<()> {
+    let base = site();
+    let fixture = fixture(&files(&base));
+
+    let info = loch::check_paths(&[fixture.path_str()], Some(&config()))?;
+
+    let mut replacements: Vec<(String, String)> = info
+        .file_urls
+        .into_iter()
+        .filter_map(|file_url| Some((file_url.url, file_url.replacement?)))
+        .collect();
+    replacements.sort();
+    replacements.dedup();
+
+    assert_eq!(
+        replacements,
+        vec![
+            (format!("{}/old", base), format!("{}/new", base)),
+            (format!("{}/old#usage", base), format!("{}/new#usage", base)),
+        ]
+    );
+    assert_eq!(info.num_fixed_urls, 0);
+
+    Ok(())
+}
@@ -0,0 +1,135 @@
+extern crate loch;
+
+use loch::{Config, Placeholder, Result};
+use std::{
+    env, fs,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    process, thread,
+};
+
+// Files served by the test server, by path.
+static FILES: &[(&str, &str)] = &[("pub/loch-0.1.tar.gz", "release")];
+// Directories served by the test server.
+static DIRS: &[&str] = &["", "pub"];
+
+// Starts a minimal FTP server on a local port, and returns the port. It only understands the
+// commands libcurl needs to check that a file or directory exists.
+fn serve_ftp() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = stream.unwrap();
+            thread::spawn(move || session(stream));
+        }
+    });
+
+    port
+}
+
+// Returns the path of `name` relative to the root, without leading or trailing slashes.
+fn resolve(cwd: &str, name: &str) -> String {
+    if name.starts_with('/') || cwd.is_empty() {
+        name.trim_matches('/').to_string()
+    } else {
+        format!("{}/{}", cwd, name.trim_matches('/'))
+    }
+}
+
+fn session(mut stream: TcpStream) {
+    let reader = BufReader::new(stream.try_clone().unwrap());
+    let mut cwd = String::new();
+
+    writeln!(stream, "220 loch test server\r").unwrap();
+
+    for line in reader.lines() {
+        let line = line.unwrap();
+        let (command, arg) = match line.find(' ') {
+            Some(i) => (&line[..i], &line[i + 1..]),
+            None => (line.as_str(), ""),
+        };
+
+        let reply = match command {
+            "USER" => "331 Password required".to_string(),
+            "PASS" => "230 Logged in".to_string(),
+            "PWD" => "257 \"/\"".to_string(),
+            "TYPE" => "200 Type set".to_string(),
+            "REST" => "350 Restarting".to_string(),
+            "CWD" => {
+                let dir = resolve(&cwd, arg);
+                if DIRS.contains(&dir.as_str()) {
+                    cwd = dir;
+                    "250 Directory changed".to_string()
+                } else {
+                    "550 No such directory".to_string()
+                }
+            }
+            "SIZE" => match FILES.iter().find(|(file, _)| *file == resolve(&cwd, arg)) {
+                Some((_, contents)) => format!("213 {}", contents.len()),
+                None => "550 No such file".to_string(),
+            },
+            "QUIT" => {
+                writeln!(stream, "221 Goodbye\r").unwrap();
+                return;
+            }
+            _ => "502 Command not implemented".to_string(),
+        };
+
+        writeln!(stream, "{}\r", reply).unwrap();
+    }
+}
+
+// Test that FTP links are checked by whether their files and directories exist.
+#[test]
+fn check_ftp_urls() -> Result<()> {
+    let port = serve_ftp();
+    let dir = env::temp_dir().join(format!("loch-ftp-{}", process::id()));
+    let url = |path: &str| format!("ftp://127.0.0.1:{}/{}", port, path);
+
+    fs::create_dir_all(&dir)?;
+    fs::write(
+        dir.join("mirrors.md"),
+        format!(
+            "# Mirrors\n\n\
+             + Release: {}\n\
+             + Directory: {}\n\
+             + Missing file: {}\n\
+             + Missing directory: {}\n",
+            url("pub/loch-0.1.tar.gz"),
+            url("pub/"),
+            url("pub/loch-0.0.tar.gz"),
+            url("old/"),
+        ),
+    )?;
+
+    // The server's address is local.
+    let config = Config::default()
+        .check_placeholders(&[Placeholder::Local])
+        .silent();
+
+    let info = loch::check_paths(&[dir.to_str().unwrap()], Some(&config));
+    fs::remove_dir_all(&dir)?;
+    let info = info?;
+
+    let mut results: Vec<(String, Option<bool>)> = info
+        .file_urls
+        .into_iter()
+        .map(|file_url| (file_url.url, file_url.bad))
+        .collect();
+    results.sort();
+
+    assert_eq!(
+        results,
+        vec![
+            (url("old/"), Some(true)),
+            (url("pub/"), Some(false)),
+            (url("pub/loch-0.0.tar.gz"), Some(true)),
+            (url("pub/loch-0.1.tar.gz"), Some(false)),
+        ]
+    );
+    assert_eq!(info.num_bad_urls, 2);
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+extern crate loch;
+
+mod server;
+
+use loch::{Auth, Config, Error, HostConfig, Placeholder, Result};
+use server::{Fixture, Response};
+use std::env;
+
+// Serves pages that require credentials, and returns the port. The same server is reached as
+// `127.0.0.1` and as `localhost`, which count as different hosts.
+fn site() -> u16 {
+    server::serve(|request| {
+        let authorization = request.header("authorization");
+        let cookie = request.header("cookie").unwrap_or_default();
+        let ok = match request.path.as_str() {
+            "/bearer" => {
+                authorization == Some("Bearer t0ken") && request.header("x-team") == Some("docs")
+            }
+            // alice:pa55
+            "/basic" => authorization == Some("Basic YWxpY2U6cGE1NQ=="),
+            "/cookie" => cookie.split("; ").any(|cookie| cookie == "session=abc"),
+            // Sent to `localhost` after a redirect from `127.0.0.1`.
+            "/other-host" => {
+                authorization.is_none() && cookie.is_empty() && request.header("x-team").is_none()
+            }
+            "/redirect" => {
+                let port = request.header("host").unwrap().split(':').nth(1).unwrap();
+                return Response::redirect(302, &format!("http://localhost:{}/other-host", port));
+            }
+            _ => false,
+        };
+
+        Response::status(if ok { 200 } else { 401 })
+    })
+}
+
+// Returns the bad URLs after checking links to `paths` on `host`, with paths relative to the host.
+fn bad_urls(host: &str, paths: &[&str], config: Config) -> Result<Vec<String>> {
+    let port = site();
+    let base = format!("http://{}:{}", host, port);
+    let links = paths
+        .iter()
+        .map(|path| format!("{}{}\n", base, path))
+        .collect();
+    let fixture = Fixture::new(&[
+        ("links.md", links),
+        (
+            "cookies.txt",
+            "127.0.0.1\tFALSE\t/\tFALSE\t0\tsession\tabc\n".to_string(),
+        ),
+        (
+            "netrc",
+            "machine 127.0.0.1 login alice password pa55\n".to_string(),
+        ),
+    ]);
+
+    // The server's address is local. Files are relative to the fixture.
+    let config = config.check_placeholders(&[Placeholder::Local]).silent();
+    let config = Config {
+        netrc: config.netrc.map(|path| fixture.path().join(path)),
+        hosts: config
+            .hosts
+            .into_iter()
+            .map(|host| HostConfig {
+                cookie_jar: host.cookie_jar.map(|path| fixture.path().join(path)),
+                ..host
+            })
+            .collect(),
+        ..config
+    };
+
+    let info = loch::check_paths(&[fixture.path_str()], Some(&config))?;
+    let mut bad_urls: Vec<String> = info
+        .file_urls
+        .into_iter()
+        .filter(|file_url| file_url.bad == Some(true))
+        .map(|file_url| file_url.url.replace(&base, ""))
+        .collect();
+    bad_urls.sort();
+
+    Ok(bad_urls)
+}
+
+fn host_config(host: &str) -> HostConfig {
+    HostConfig::new(host)
+        .header("X-Team", "${LOCH_TEST_TEAM}")
+        .auth(Auth::Bearer {
+            token_env: "LOCH_TEST_TOKEN".to_string(),
+        })
+        .cookie_jar("cookies.txt".as_ref())
+}
+
+// Test that headers, credentials and cookies are sent to their hosts, and not to others.
+#[test]
+fn host_settings() -> Result<()> {
+    env::set_var("LOCH_TEST_TEAM", "docs");
+    env::set_var("LOCH_TEST_TOKEN", "t0ken");
+
+    let paths = ["/bearer", "/cookie", "/redirect"];
+
+    let config = Config::default().host(host_config("127.0.0.1"));
+    assert!(bad_urls("127.0.0.1", &paths, config)?.is_empty());
+
+    // Nothing is sent to other hosts.
+    let config = Config::default().host(host_config("127.0.0.1"));
+    assert_eq!(
+        bad_urls("localhost", &paths, config)?,
+        vec!["/bearer", "/cookie"]
+    );
+
+    Ok(())
+}
+
+// Test basic authentication from an environment variable and from a netrc file.
+#[test]
+fn basic_auth() -> Result<()> {
+    env::set_var("LOCH_TEST_PASSWORD", "pa55");
+
+    let config = || {
+        Config::default().host(HostConfig::new("127.0.0.1").auth(Auth::Basic {
+            username: "alice".to_string(),
+            password_env: "LOCH_TEST_PASSWORD".to_string(),
+        }))
+    };
+    assert!(bad_urls("127.0.0.1", &["/basic"], config())?.is_empty());
+    assert_eq!(
+        bad_urls("localhost", &["/basic"], config())?,
+        vec!["/basic"]
+    );
+
+    // The netrc file has credentials for `127.0.0.1` only.
+    let config = || Config::default().netrc("netrc".as_ref());
+    assert!(bad_urls("127.0.0.1", &["/basic"], config())?.is_empty());
+    assert_eq!(
+        bad_urls("localhost", &["/basic"], config())?,
+        vec!["/basic"]
+    );
+    assert_eq!(
+        bad_urls("127.0.0.1", &["/basic"], Config::default())?,
+        vec!["/basic"]
+    );
+
+    Ok(())
+}
+
+// Test that a missing environment variable is an error.
+#[test]
+fn missing_env_var() {
+    let config = Config::default().host(HostConfig::new("127.0.0.1").auth(Auth::Bearer {
+        token_env: "LOCH_TEST_UNSET".to_string(),
+    }));
+
+    match bad_urls("127.0.0.1", &["/bearer"], config) {
+        Err(Error::MissingEnvVar(name)) => assert_eq!(name, "LOCH_TEST_UNSET"),
+        res => panic!("Unexpected result: {:?}", res),
+    }
+}
@@ -0,0 +1,32 @@
+extern crate loch;
+
+mod util;
+
+use loch::{Config, FileUrl, Result};
+
+static TEST_DIR: &str = "tests/idn_dir/";
+
+// Construct an unchecked FileUrl at the given line and column.
+fn fileurl(url: &str, (line, column): (usize, usize), span: (usize, usize)) -> FileUrl {
+    util::fileurl(url, &format!("{}stadt.md", TEST_DIR), (line, column), span)
+}
+
+// Test that internationalized URLs are found, and counted once along with their ASCII forms.
+#[test]
+fn internationalized_urls() -> Result<()> {
+    let config = Config::default().no_check().silent();
+
+    let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
+
+    util::assert_list_eq(
+        &info.file_urls,
+        &[
+            fileurl("https://münchen.de/straße", (3, 12), (28, 55)),
+            fileurl("https://xn--mnchen-3ya.de/stra%C3%9Fe", (4, 18), (74, 111)),
+            fileurl("https://例え.テスト/パス", (5, 16), (128, 159)),
+        ],
+    );
+    assert_eq!(info.num_urls, 2);
+
+    Ok(())
+}
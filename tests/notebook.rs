@@ -0,0 +1,65 @@
+extern crate loch;
+
+mod util;
+
+use loch::{Config, FileUrl, Placeholder, Result};
+
+static TEST_DIR: &str = "tests/notebook_dir/";
+
+// Construct an unchecked FileUrl at the given line and column of a notebook cell.
+fn fileurl(
+    url: &str,
+    cell: usize,
+    (line, column): (usize, usize),
+    span: (usize, usize),
+) -> FileUrl {
+    FileUrl {
+        cell: Some(cell),
+        ..util::fileurl(
+            url,
+            &format!("{}example.ipynb", TEST_DIR),
+            (line, column),
+            span,
+        )
+    }
+}
+
+// Test that only Markdown cells are searched by default, and outputs are skipped.
+#[test]
+fn notebook_markdown_cells() -> Result<()> {
+    let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
+        .no_check()
+        .silent();
+
+    let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
+
+    util::assert_list_eq(
+        &info.file_urls,
+        &[fileurl("https://docs.example.com/", 1, (3, 16), (129, 154))],
+    );
+
+    Ok(())
+}
+
+// Test that comments in code cells are searched if enabled.
+#[test]
+fn notebook_code_comments() -> Result<()> {
+    let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
+        .notebook_code_comments()
+        .no_check()
+        .silent();
+
+    let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
+
+    util::assert_list_eq(
+        &info.file_urls,
+        &[
+            fileurl("https://docs.example.com/", 1, (3, 16), (129, 154)),
+            fileurl("https://data.example.com/", 2, (1, 13), (474, 499)),
+        ],
+    );
+
+    Ok(())
+}
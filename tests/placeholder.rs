@@ -0,0 +1,125 @@
+extern crate loch;
+
+mod util;
+
+use loch::{Config, Exclusion, FileUrl, Placeholder, Result};
+
+static TEST_DIR: &str = "tests/placeholder_dir/";
+
+// Construct an unchecked FileUrl at the given line and column, skipped as the given placeholder.
+fn fileurl(
+    url: &str,
+    filepath: &str,
+    (line, column): (usize, usize),
+    span: (usize, usize),
+    placeholder: Option<Placeholder>,
+) -> FileUrl {
+    FileUrl {
+        excluded: placeholder.map(Exclusion::Placeholder),
+        ..util::fileurl(
+            url,
+            &format!("{}{}", TEST_DIR, filepath),
+            (line, column),
+            span,
+        )
+    }
+}
+
+// Returns the URLs in the test directory, with the given kinds of placeholders checked.
+fn placeholder_urls(check_placeholders: &[Placeholder]) -> Result<Vec<FileUrl>> {
+    let config = Config::default()
+        .check_placeholders(check_placeholders)
+        .no_check()
+        .silent();
+
+    let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
+
+    Ok(info.file_urls)
+}
+
+// Test that templated, local and example URLs are skipped by default.
+#[test]
+fn skip_placeholders() -> Result<()> {
+    use Placeholder::*;
+
+    util::assert_list_eq(
+        &placeholder_urls(&[])?,
+        &[
+            fileurl(
+                "https://{{ .Values.host }}/api",
+                "values.yaml",
+                (2, 8),
+                (16, 46),
+                Some(Template),
+            ),
+            fileurl(
+                "https://${DOMAIN}/callback",
+                "values.yaml",
+                (3, 14),
+                (60, 86),
+                Some(Template),
+            ),
+            fileurl(
+                "https://charts.helm.sh/stable",
+                "values.yaml",
+                (4, 8),
+                (95, 124),
+                None,
+            ),
+            fileurl(
+                "https://<your-org>.example.com",
+                "setup.md",
+                (3, 12),
+                (20, 50),
+                Some(Template),
+            ),
+            fileurl(
+                "http://localhost:8080",
+                "setup.md",
+                (4, 15),
+                (87, 108),
+                Some(Local),
+            ),
+            fileurl(
+                "http://192.168.1.20/admin",
+                "setup.md",
+                (4, 41),
+                (113, 138),
+                Some(Local),
+            ),
+            fileurl(
+                "https://docs.rs/loch/",
+                "setup.md",
+                (5, 7),
+                (146, 167),
+                None,
+            ),
+        ],
+    );
+
+    Ok(())
+}
+
+// Test that each kind of placeholder can be checked instead.
+#[test]
+fn check_placeholders() -> Result<()> {
+    let urls = placeholder_urls(&[Placeholder::Local])?;
+
+    assert_eq!(
+        urls.iter()
+            .filter(|file_url| file_url.excluded.is_some())
+            .map(|file_url| file_url.url.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "https://${DOMAIN}/callback",
+            "https://<your-org>.example.com",
+            "https://{{ .Values.host }}/api",
+        ]
+    );
+
+    let urls = placeholder_urls(Placeholder::ALL)?;
+
+    assert!(urls.iter().all(|file_url| file_url.excluded.is_none()));
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+extern crate loch;
+
+mod server;
+
+use loch::{Config, Placeholder, Result};
+use server::{Fixture, Response};
+
+// A host that doesn't resolve, so that its links can only be checked through the proxy.
+static HOST: &str = "loch-proxy-test.invalid";
+
+// Serves as a proxy that knows a single page of `HOST`, and returns the port. Requests to a proxy
+// give the whole URL rather than the path.
+fn proxy() -> u16 {
+    server::serve(|request| {
+        if request.path == format!("http://{}/page", HOST) {
+            Response::ok("page")
+        } else {
+            Response::status(404)
+        }
+    })
+}
+
+// Returns whether each of the links to `paths` on `HOST` is bad, in order.
+fn bad(paths: &[&str], config: Config) -> Result<Vec<Option<bool>>> {
+    let links = paths
+        .iter()
+        .map(|path| format!("http://{}{}\n", HOST, path))
+        .collect();
+    let fixture = Fixture::new(&[("links.md", links)]);
+
+    // The `.invalid` domain is reserved.
+    let config = config.check_placeholders(Placeholder::ALL).silent();
+    let mut file_urls = loch::check_paths(&[fixture.path_str()], Some(&config))?.file_urls;
+    file_urls.sort_by_key(|file_url| file_url.line);
+
+    Ok(file_urls.into_iter().map(|file_url| file_url.bad).collect())
+}
+
+// Test that links are checked through the proxy.
+#[test]
+fn check_through_proxy() -> Result<()> {
+    let proxy = format!("http://127.0.0.1:{}", proxy());
+    let config = Config::default().proxy(&proxy);
+
+    assert_eq!(
+        bad(&["/page", "/missing"], config)?,
+        vec![Some(false), Some(true)]
+    );
+
+    Ok(())
+}
+
+// Test that hosts in the no-proxy list are connected to directly.
+#[test]
+fn no_proxy_hosts() -> Result<()> {
+    let proxy = format!("http://127.0.0.1:{}", proxy());
+
+    for no_proxy in &[HOST, ".invalid", "*"] {
+        let config = Config::default().proxy(&proxy).no_proxy(&[no_proxy]);
+        assert_eq!(bad(&["/page"], config)?, vec![Some(true)], "{}", no_proxy);
+    }
+
+    let config = Config::default().proxy(&proxy).no_proxy(&["other.invalid"]);
+    assert_eq!(bad(&["/page"], config)?, vec![Some(false)]);
+
+    Ok(())
+}
@@ -0,0 +1,137 @@
+extern crate loch;
+
+mod server;
+
+use loch::{Config, Info, Placeholder, RedirectPolicy, Result};
+use server::{Fixture, Response};
+
+// A URL checked and the redirects followed, with paths relative to the server.
+type Checked = (String, Option<bool>, Vec<(u32, String)>);
+
+// Serves a site with moved pages, and returns the file linking to them.
+fn site() -> (Fixture, String) {
+    let port = server::serve(|request| match request.path.as_str() {
+        "/new" => Response::ok("new"),
+        "/old" => Response::redirect(301, "/new"),
+        "/moved-twice" => Response::redirect(308, "/old"),
+        "/temp" => Response::redirect(302, "/new"),
+        "/loop" => Response::redirect(301, "/loop"),
+        "/gone" => Response::redirect(301, "/missing"),
+        _ => Response::status(404),
+    });
+    let base = format!("http://127.0.0.1:{}", port);
+
+    let links = ["/new", "/old", "/moved-twice", "/temp", "/loop", "/gone"]
+        .iter()
+        .map(|path| format!("+ {}{}\n", base, path))
+        .collect();
+
+    (Fixture::new(&[("links.md", links)]), base)
+}
+
+fn check(config: Config) -> Result<(Info, Vec<Checked>)> {
+    let (fixture, base) = site();
+    // The server's address is local.
+    let config = config.check_placeholders(&[Placeholder::Local]).silent();
+
+    let info = loch::check_paths(&[fixture.path_str()], Some(&config))?;
+    let mut checked: Vec<Checked> = info
+        .file_urls
+        .iter()
+        .map(|file_url| {
+            (
+                file_url.url.replace(&base, ""),
+                file_url.bad,
+                file_url
+                    .redirects
+                    .iter()
+                    .map(|redirect| (redirect.status, redirect.url.replace(&base, "")))
+                    .collect(),
+            )
+        })
+        .collect();
+    checked.sort();
+
+    Ok((info, checked))
+}
+
+fn checked(path: &str, bad: bool, redirects: &[(u32, &str)]) -> Checked {
+    (
+        path.to_string(),
+        Some(bad),
+        redirects
+            .iter()
+            .map(|(status, path)| (*status, path.to_string()))
+            .collect(),
+    )
+}
+
+// Test that redirects are followed and recorded, and that permanent ones are reported.
+#[test]
+fn follow_redirects() -> Result<()> {
+    let (info, urls) = check(Config::default())?;
+
+    assert_eq!(
+        urls,
+        vec![
+            checked("/gone", true, &[(301, "/missing")]),
+            checked("/loop", true, &[(301, "/loop"); 11]),
+            checked("/moved-twice", false, &[(308, "/old"), (301, "/new")]),
+            checked("/new", false, &[]),
+            checked("/old", false, &[(301, "/new")]),
+            checked("/temp", false, &[(302, "/new")]),
+        ]
+    );
+    assert_eq!(info.num_bad_urls, 2);
+    assert_eq!(info.num_redirected_urls, 2);
+
+    Ok(())
+}
+
+// Test that temporary redirects are also reported with the warn policy.
+#[test]
+fn warn_redirects() -> Result<()> {
+    let (info, _) = check(Config::default().redirects(RedirectPolicy::Warn))?;
+
+    assert_eq!(info.num_bad_urls, 2);
+    assert_eq!(info.num_redirected_urls, 3);
+
+    Ok(())
+}
+
+// Test that URLs that redirect are bad with the forbid policy.
+#[test]
+fn forbid_redirects() -> Result<()> {
+    let (info, urls) = check(Config::default().redirects(RedirectPolicy::Forbid))?;
+
+    assert_eq!(
+        urls,
+        vec![
+            checked("/gone", true, &[(301, "/missing")]),
+            checked("/loop", true, &[(301, "/loop")]),
+            checked("/moved-twice", true, &[(308, "/old")]),
+            checked("/new", false, &[]),
+            checked("/old", true, &[(301, "/new")]),
+            checked("/temp", true, &[(302, "/new")]),
+        ]
+    );
+    assert_eq!(info.num_bad_urls, 5);
+    assert_eq!(info.num_redirected_urls, 0);
+
+    Ok(())
+}
+
+// Test that URLs that redirect too many times are bad.
+#[test]
+fn max_redirects() -> Result<()> {
+    let (info, urls) = check(Config::default().max_redirects(1))?;
+
+    assert_eq!(
+        urls[2],
+        checked("/moved-twice", true, &[(308, "/old"), (301, "/new")])
+    );
+    assert_eq!(info.num_bad_urls, 3);
+    assert_eq!(info.num_redirected_urls, 1);
+
+    Ok(())
+}
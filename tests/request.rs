@@ -0,0 +1,129 @@
+extern crate loch;
+
+mod server;
+
+use loch::{Config, MethodStrategy, Placeholder, Result};
+use server::{Fixture, Response};
+use std::sync::{Arc, Mutex};
+
+// A request received, by method, path and `Range` header.
+type Received = (String, String, Option<String>);
+
+// Checks links to a site, and returns the URLs found bad and the requests received, with paths
+// relative to the site.
+fn check(paths: &[&str], config: Config) -> Result<(Vec<String>, Vec<Received>)> {
+    let received = Arc::new(Mutex::new(vec![]));
+    let log = received.clone();
+
+    let port = server::serve(move |request| {
+        let range = request.header("range").map(|range| range.to_string());
+        log.lock()
+            .unwrap()
+            .push((request.method.clone(), request.path.clone(), range.clone()));
+
+        match (request.method.as_str(), request.path.as_str()) {
+            (_, "/page") => Response::ok("<title>Page</title>"),
+            ("HEAD", "/no-head") => Response::status(405),
+            (_, "/no-head") => Response::ok("<title>No HEAD</title>"),
+            // An empty file, whose first byte can't be served.
+            ("GET", "/empty") if range.is_some() => Response::status(416),
+            (_, "/empty") => Response::ok(""),
+            (_, "/large") => Response::ok(&format!("<title>Large</title>{}", "x".repeat(100_000))),
+            _ => Response::status(404),
+        }
+    });
+
+    let base = format!("http://127.0.0.1:{}", port);
+    let links = paths
+        .iter()
+        .map(|path| format!("+ {}{}\n", base, path))
+        .collect();
+    let fixture = Fixture::new(&[("links.md", links)]);
+
+    // The server's address is local.
+    let config = config.check_placeholders(&[Placeholder::Local]).silent();
+    let info = loch::check_paths(&[fixture.path_str()], Some(&config))?;
+
+    let bad = info
+        .file_urls
+        .into_iter()
+        .filter(|file_url| file_url.bad == Some(true))
+        .map(|file_url| file_url.url.replace(&base, ""))
+        .collect();
+    let received = received.lock().unwrap().clone();
+
+    Ok((bad, received))
+}
+
+fn received(method: &str, path: &str, ranged: bool) -> Received {
+    let range = if ranged {
+        Some("bytes=0-0".to_string())
+    } else {
+        None
+    };
+
+    (method.to_string(), path.to_string(), range)
+}
+
+// Test that GET is only sent when HEAD fails, for the first byte only, by default.
+#[test]
+fn head_then_get() -> Result<()> {
+    let (bad, requests) = check(&["/page", "/no-head"], Config::default())?;
+
+    assert!(bad.is_empty());
+    assert_eq!(
+        requests,
+        vec![
+            received("HEAD", "/no-head", false),
+            received("GET", "/no-head", true),
+            received("HEAD", "/page", false),
+        ]
+    );
+
+    Ok(())
+}
+
+// Test that only HEAD requests are sent with the HEAD strategy.
+#[test]
+fn head_only() -> Result<()> {
+    let config = Config::default().method(MethodStrategy::Head);
+    let (bad, requests) = check(&["/page", "/no-head"], config)?;
+
+    assert_eq!(bad, vec!["/no-head"]);
+    assert!(requests.iter().all(|(method, _, _)| method == "HEAD"));
+
+    Ok(())
+}
+
+// Test that only ranged GET requests are sent with the GET strategy, unless the first byte can't
+// be served.
+#[test]
+fn get_only() -> Result<()> {
+    let config = Config::default().method(MethodStrategy::Get);
+    let (bad, requests) = check(&["/page", "/empty"], config)?;
+
+    assert!(bad.is_empty());
+    assert_eq!(
+        requests,
+        vec![
+            received("GET", "/empty", true),
+            received("GET", "/empty", false),
+            received("GET", "/page", true),
+        ]
+    );
+
+    Ok(())
+}
+
+// Test that whole bodies are requested to detect soft 404s, and that bodies larger than the
+// maximum size are cut short without making the URL bad.
+#[test]
+fn max_body_size() -> Result<()> {
+    let config = Config::default().soft_404().max_body_size(1000);
+    let (bad, requests) = check(&["/large"], config)?;
+
+    assert!(bad.is_empty());
+    assert!(requests.contains(&received("GET", "/large", false)));
+
+    Ok(())
+}
@@ -0,0 +1,129 @@
+extern crate loch;
+
+mod server;
+
+use loch::{Config, IpVersion, Placeholder, Resolve, Result, Timeouts};
+use server::{Fixture, Response};
+use std::sync::{Arc, Mutex};
+
+// A host that doesn't resolve, standing in for a site before its DNS cutover.
+static HOST: &str = "docs.loch-staging.invalid";
+
+// Checks a link to a page of `HOST`, served on a local port, and returns whether it is bad and
+// the `Host` headers received. `config` is given the port and the path of a hosts file containing
+// `hosts`.
+fn check<F>(hosts: &str, config: F) -> Result<(Option<bool>, Vec<String>)>
+where
+    F: FnOnce(Config, u16, &str) -> Config,
+{
+    let received = Arc::new(Mutex::new(vec![]));
+    let log = received.clone();
+
+    let port = server::serve(move |request| {
+        let host = request.header("host").unwrap_or_default().to_string();
+        log.lock().unwrap().push(host);
+        Response::ok("page")
+    });
+
+    let fixture = Fixture::new(&[
+        ("links.md", format!("http://{}:{}/page\n", HOST, port)),
+        ("hosts", hosts.to_string()),
+    ]);
+    let hosts_file = fixture.path().join("hosts");
+
+    // The `.invalid` domain is reserved.
+    let config = config(Config::default(), port, hosts_file.to_str().unwrap())
+        .check_placeholders(Placeholder::ALL)
+        .silent();
+    let info = loch::check_paths(&[fixture.path_str()], Some(&config))?;
+    let received = received.lock().unwrap().clone();
+
+    Ok((info.file_urls[0].bad, received))
+}
+
+fn resolve(port: u16, addr: &str) -> Resolve {
+    format!("{}:{}:{}", HOST, port, addr).parse().unwrap()
+}
+
+// Test that links are checked on the address given for their host and port, with their own host
+// name.
+#[test]
+fn resolve_host() -> Result<()> {
+    let (bad, received) = check("", |config, port, _| {
+        config.resolve(resolve(port, "127.0.0.1"))
+    })?;
+
+    assert_eq!(bad, Some(false));
+    assert!(received.iter().all(|host| host.starts_with(HOST)));
+
+    // The host name isn't resolved for the DNS timeout.
+    let (bad, _) = check("", |config, port, _| {
+        config
+            .resolve(resolve(port, "127.0.0.1"))
+            .timeouts("dns:1".parse::<Timeouts>().unwrap())
+    })?;
+    assert_eq!(bad, Some(false));
+
+    Ok(())
+}
+
+// Test that the addresses of a hosts file are used for all ports.
+#[test]
+fn hosts_file() -> Result<()> {
+    let hosts = format!("# Staging\n127.0.0.1 {} other.invalid\n", HOST);
+    let (bad, received) = check(&hosts, |config, _, hosts_file| {
+        config.hosts_file(hosts_file.as_ref())
+    })?;
+
+    assert_eq!(bad, Some(false));
+    assert!(received.iter().all(|host| host.starts_with(HOST)));
+
+    Ok(())
+}
+
+// Test that the addresses given for a port take precedence over the hosts file.
+#[test]
+fn resolve_over_hosts_file() -> Result<()> {
+    // Nothing listens on 127.0.0.2.
+    let hosts = format!("127.0.0.2 {}\n", HOST);
+    let (bad, _) = check(&hosts, |config, _, hosts_file| {
+        config.hosts_file(hosts_file.as_ref())
+    })?;
+    assert_eq!(bad, Some(true));
+
+    let (bad, _) = check(&hosts, |config, port, hosts_file| {
+        config
+            .hosts_file(hosts_file.as_ref())
+            .resolve(resolve(port, "127.0.0.1"))
+    })?;
+    assert_eq!(bad, Some(false));
+
+    Ok(())
+}
+
+// Test that only addresses of the IP version given are connected to.
+#[test]
+fn ip_version() -> Result<()> {
+    // The server only listens on the IPv4 address.
+    let hosts = format!("::1 {0}\n127.0.0.1 {0}\n", HOST);
+    let with_version = |version| {
+        move |config: Config, _, hosts_file: &str| {
+            config.hosts_file(hosts_file.as_ref()).ip_version(version)
+        }
+    };
+
+    assert_eq!(check(&hosts, with_version(IpVersion::V4))?.0, Some(false));
+    assert_eq!(check(&hosts, with_version(IpVersion::V6))?.0, Some(true));
+
+    Ok(())
+}
+
+// Test that an invalid hosts file is an error.
+#[test]
+fn invalid_hosts_file() {
+    let result = check("staging docs.example.com\n", |config, _, hosts_file| {
+        config.hosts_file(hosts_file.as_ref())
+    });
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,74 @@
+extern crate loch;
+
+mod util;
+
+use loch::{Config, FileUrl, Placeholder, Result, Scheme};
+
+static TEST_DIR: &str = "tests/scheme_dir/";
+
+// Construct a FileUrl at the given line and column.
+fn fileurl(url: &str, (line, column): (usize, usize), span: (usize, usize), bad: bool) -> FileUrl {
+    FileUrl {
+        bad: Some(bad),
+        ..util::fileurl(
+            url,
+            &format!("{}contact.md", TEST_DIR),
+            (line, column),
+            span,
+        )
+    }
+}
+
+// Test that mailto:, tel: and data: URLs are found and validated offline.
+#[test]
+fn validate_schemes() -> Result<()> {
+    let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
+        .schemes(Scheme::ALL)
+        .silent();
+
+    let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
+
+    util::assert_list_eq(
+        &info.file_urls,
+        &[
+            fileurl("mailto:support@example.com", (3, 17), (27, 53), false),
+            fileurl("mailto:sales@exmaple", (3, 48), (58, 78), true),
+            fileurl("tel:+1-201-555-0123", (4, 6), (85, 104), false),
+            fileurl("tel:555-0123", (4, 30), (109, 121), true),
+            fileurl(
+                "data:image/png;base64,iVBORw0KGgo=",
+                (5, 7),
+                (130, 164),
+                false,
+            ),
+            fileurl("data:;base64,SGk", (5, 46), (169, 185), true),
+        ],
+    );
+    assert_eq!(info.num_bad_urls, 3);
+
+    Ok(())
+}
+
+// Test that only the given schemes are found, and none by default.
+#[test]
+fn select_schemes() -> Result<()> {
+    let urls = |schemes: &[Scheme]| -> Result<Vec<String>> {
+        let config = Config::default().schemes(schemes).no_check().silent();
+        let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
+
+        Ok(info
+            .file_urls
+            .into_iter()
+            .map(|file_url| file_url.url)
+            .collect())
+    };
+
+    assert_eq!(
+        urls(&[Scheme::Tel])?,
+        vec!["tel:+1-201-555-0123", "tel:555-0123"]
+    );
+    assert!(urls(&[])?.is_empty());
+
+    Ok(())
+}
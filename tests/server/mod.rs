@@ -0,0 +1,207 @@
+//! A minimal HTTP server for tests that check URLs, serving responses on a local port.
+
+#![allow(dead_code)]
+
+use std::{
+    env, fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+/// A request received by the server.
+#[derive(Clone, Debug)]
+pub struct Request {
+    /// The method, e.g. `HEAD` or `GET`.
+    pub method: String,
+    /// The path, including any query.
+    pub path: String,
+    /// The headers, with lowercase names.
+    pub headers: Vec<(String, String)>,
+}
+
+impl Request {
+    /// Returns the value of the header `name`, which must be in lowercase.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A response to send.
+#[derive(Clone, Debug)]
+pub struct Response {
+    /// The status code.
+    pub status: u32,
+    /// Extra headers.
+    pub headers: Vec<(String, String)>,
+    /// The body, not sent in responses to `HEAD` requests.
+    pub body: String,
+}
+
+impl Response {
+    /// A response with status `status` and no body.
+    pub fn status(status: u32) -> Self {
+        Response {
+            status,
+            headers: vec![],
+            body: String::new(),
+        }
+    }
+
+    /// A 200 response with body `body`.
+    pub fn ok(body: &str) -> Self {
+        Response {
+            body: body.to_string(),
+            ..Response::status(200)
+        }
+    }
+
+    /// A redirect with status `status` to `location`.
+    pub fn redirect(status: u32, location: &str) -> Self {
+        Response::status(status).header("Location", location)
+    }
+
+    /// Adds the header `name`.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// Starts a server on a local port answering requests with `handler`, and returns the port.
+pub fn serve<F>(handler: F) -> u16
+where
+    F: Fn(&Request) -> Response + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handler = Arc::new(handler);
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = stream.unwrap();
+            let handler = handler.clone();
+            thread::spawn(move || connection(stream, &*handler));
+        }
+    });
+
+    port
+}
+
+// Answers the requests sent over a connection until it is closed.
+fn connection(mut stream: TcpStream, handler: &dyn Fn(&Request) -> Response) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    loop {
+        // Close TLS connections, such as when the `https://` equivalent of a URL is tried.
+        match reader.fill_buf() {
+            Ok(buf) if buf.first() != Some(&0x16) => (),
+            _ => return,
+        }
+
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+
+        let mut parts = line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let path = parts.next().unwrap_or_default().to_string();
+
+        let mut headers = vec![];
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(i) = line.find(':') {
+                headers.push((line[..i].to_lowercase(), line[i + 1..].trim().to_string()));
+            }
+        }
+
+        let request = Request {
+            method,
+            path,
+            headers,
+        };
+
+        // Skip any request body.
+        if let Some(len) = request.header("content-length") {
+            let mut body = vec![0; len.parse().unwrap_or(0)];
+            reader.read_exact(&mut body).unwrap();
+        }
+
+        let response = handler(&request);
+        let mut head = format!(
+            "HTTP/1.1 {} Status\r\nContent-Length: {}\r\n",
+            response.status,
+            response.body.len()
+        );
+        for (name, value) in response.headers.iter() {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str("\r\n");
+
+        if stream.write_all(head.as_bytes()).is_err() {
+            return;
+        }
+        if request.method != "HEAD" && stream.write_all(response.body.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+/// A directory of test files, written at runtime so that they can contain the server's port, and
+/// removed when dropped.
+pub struct Fixture {
+    dir: PathBuf,
+}
+
+impl Fixture {
+    /// Writes the files `files`, given by name and contents, to a new directory.
+    pub fn new(files: &[(&str, String)]) -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        let dir = env::temp_dir().join(format!(
+            "loch-test-{}-{}",
+            process::id(),
+            COUNT.fetch_add(1, Ordering::SeqCst)
+        ));
+        fs::create_dir_all(&dir).unwrap();
+
+        for (name, contents) in files.iter() {
+            fs::write(dir.join(name), contents).unwrap();
+        }
+
+        Fixture { dir }
+    }
+
+    /// The path of the directory.
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The path of the directory, as a string.
+    pub fn path_str(&self) -> &str {
+        self.dir.to_str().unwrap()
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
@@ -0,0 +1,79 @@
+extern crate loch;
+
+mod server;
+
+use loch::{Config, Placeholder, Result, Soft404};
+use server::{Fixture, Response};
+
+// A URL checked, with its path relative to the server, and why it is a soft 404 if it is.
+type Checked = (String, Option<Soft404>);
+
+// Checks links to a site that answers every path with 200, and returns the path and soft 404 of
+// each link along with the number of soft 404s.
+fn check(config: Config) -> Result<(u64, Vec<Checked>)> {
+    let port = server::serve(|request| match request.path.as_str() {
+        "/guide" => Response::ok("<title>Guide</title>The guide."),
+        "/moved" => Response::ok("<html><title>\n  Page Not Found\n</title></html>"),
+        "/removed" => Response::ok("<p>Sorry, we couldn't find that page.</p>"),
+        path => Response::ok(&format!("<title>Docs</title>Nothing at {}.", path)),
+    });
+    let base = format!("http://127.0.0.1:{}", port);
+    let links = ["/guide", "/moved", "/removed", "/typo"]
+        .iter()
+        .map(|path| format!("+ {}{}\n", base, path))
+        .collect();
+    let fixture = Fixture::new(&[("links.md", links)]);
+
+    // The server's address is local.
+    let config = config.check_placeholders(&[Placeholder::Local]).silent();
+    let info = loch::check_paths(&[fixture.path_str()], Some(&config))?;
+
+    let mut results: Vec<Checked> = info
+        .file_urls
+        .into_iter()
+        .map(|file_url| {
+            assert_eq!(file_url.bad, Some(false));
+            (file_url.url.replace(&base, ""), file_url.soft_404)
+        })
+        .collect();
+    results.sort();
+
+    Ok((info.num_soft_404_urls, results))
+}
+
+// Test that soft 404s are only detected when asked.
+#[test]
+fn soft_404s_not_detected_by_default() -> Result<()> {
+    let (num_soft_404_urls, results) = check(Config::default())?;
+
+    assert_eq!(num_soft_404_urls, 0);
+    assert!(results.iter().all(|(_, soft_404)| soft_404.is_none()));
+
+    Ok(())
+}
+
+// Test that soft 404s are detected by title, by pattern and by comparing with a missing page.
+#[test]
+fn detect_soft_404s() -> Result<()> {
+    let config = Config::default().soft_404_patterns(&["we couldn.t find"]);
+    let (num_soft_404_urls, results) = check(config)?;
+
+    assert_eq!(
+        results,
+        vec![
+            ("/guide".to_string(), None),
+            (
+                "/moved".to_string(),
+                Some(Soft404::Title("Page Not Found".to_string()))
+            ),
+            (
+                "/removed".to_string(),
+                Some(Soft404::Pattern("we couldn.t find".to_string()))
+            ),
+            ("/typo".to_string(), Some(Soft404::SameAsMissingPage)),
+        ]
+    );
+    assert_eq!(num_soft_404_urls, 3);
+
+    Ok(())
+}
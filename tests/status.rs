@@ -0,0 +1,139 @@
+extern crate loch;
+
+mod server;
+
+use loch::{Config, HostConfig, Placeholder, Result, StatusCodes, StatusRule, Verdict};
+use server::{Fixture, Response};
+use std::str::FromStr;
+
+// A URL checked, with its path relative to the server, whether it is bad, and the verdict and rule
+// of its status code.
+type Checked = (String, Option<bool>, Option<(u32, Verdict, StatusRule)>);
+
+// Checks links to a site answering with various status codes, on `host`.
+fn check(host: &str, config: Config) -> Result<(u64, Vec<Checked>)> {
+    let port = server::serve(|request| match request.path.as_str() {
+        "/ok" => Response::ok("ok"),
+        "/bot" => Response::status(999),
+        "/forbidden" => Response::status(403),
+        "/limited" => Response::status(429),
+        "/login" => Response::redirect(302, "/forbidden"),
+        _ => Response::status(404),
+    });
+    let base = format!("http://{}:{}", host, port);
+    let links = [
+        "/ok",
+        "/bot",
+        "/forbidden",
+        "/limited",
+        "/login",
+        "/missing",
+    ]
+    .iter()
+    .map(|path| format!("+ {}{}\n", base, path))
+    .collect();
+    let fixture = Fixture::new(&[("links.md", links)]);
+
+    // The server's address is local.
+    let config = config.check_placeholders(&[Placeholder::Local]).silent();
+    let info = loch::check_paths(&[fixture.path_str()], Some(&config))?;
+
+    let mut checked: Vec<Checked> = info
+        .file_urls
+        .into_iter()
+        .map(|file_url| {
+            (
+                file_url.url.replace(&base, ""),
+                file_url.bad,
+                file_url
+                    .status
+                    .map(|status| (status.code, status.verdict, status.rule)),
+            )
+        })
+        .collect();
+    checked.sort();
+
+    Ok((info.num_warned_urls, checked))
+}
+
+fn checked(path: &str, bad: bool, code: u32, verdict: Verdict, rule: StatusRule) -> Checked {
+    (path.to_string(), Some(bad), Some((code, verdict, rule)))
+}
+
+fn codes(codes: &str) -> StatusCodes {
+    StatusCodes::from_str(codes).unwrap()
+}
+
+// Test that status codes from 200 to 399 are good by default.
+#[test]
+fn default_rule() -> Result<()> {
+    use StatusRule::Default;
+    use Verdict::*;
+
+    let (num_warned_urls, results) = check("127.0.0.1", Config::default())?;
+
+    assert_eq!(
+        results,
+        vec![
+            checked("/bot", true, 999, Bad, Default),
+            checked("/forbidden", true, 403, Bad, Default),
+            checked("/limited", true, 429, Bad, Default),
+            checked("/login", true, 403, Bad, Default),
+            checked("/missing", true, 404, Bad, Default),
+            checked("/ok", false, 200, Good, Default),
+        ]
+    );
+    assert_eq!(num_warned_urls, 0);
+
+    Ok(())
+}
+
+// Test that global and per-host status codes are accepted or reported as warnings, with the rules
+// of the host taking precedence.
+#[test]
+fn accepted_and_warning_codes() -> Result<()> {
+    use StatusRule::{Default, Global};
+    use Verdict::*;
+
+    let config = || {
+        Config::default()
+            .accept_status(codes("403"))
+            .warn_status(codes("429,500-599"))
+            .host(
+                HostConfig::new("localhost")
+                    .accept_status(codes("999,429"))
+                    .warn_status(codes("403")),
+            )
+    };
+
+    let (num_warned_urls, results) = check("127.0.0.1", config())?;
+    assert_eq!(
+        results,
+        vec![
+            checked("/bot", true, 999, Bad, Default),
+            checked("/forbidden", false, 403, Good, Global),
+            checked("/limited", false, 429, Warning, Global),
+            checked("/login", false, 403, Good, Global),
+            checked("/missing", true, 404, Bad, Default),
+            checked("/ok", false, 200, Good, Default),
+        ]
+    );
+    assert_eq!(num_warned_urls, 1);
+
+    let localhost = || StatusRule::Host("localhost".to_string());
+    let (num_warned_urls, results) = check("localhost", config())?;
+    assert_eq!(
+        results,
+        vec![
+            checked("/bot", false, 999, Good, localhost()),
+            checked("/forbidden", false, 403, Warning, localhost()),
+            checked("/limited", false, 429, Good, localhost()),
+            checked("/login", false, 403, Warning, localhost()),
+            checked("/missing", true, 404, Bad, Default),
+            checked("/ok", false, 200, Good, Default),
+        ]
+    );
+    assert_eq!(num_warned_urls, 2);
+
+    Ok(())
+}
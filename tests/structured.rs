@@ -0,0 +1,100 @@
+extern crate loch;
+
+mod util;
+
+use loch::{Config, FileUrl, Placeholder, Result};
+
+static TEST_DIR: &str = "tests/structured_dir/";
+
+// Construct an unchecked FileUrl at the given line and column.
+fn fileurl(
+    url: &str,
+    filepath: &str,
+    (line, column): (usize, usize),
+    span: (usize, usize),
+    key_path: Option<&str>,
+) -> FileUrl {
+    FileUrl {
+        key_path: key_path.map(|key_path| key_path.into()),
+        ..util::fileurl(
+            url,
+            &format!("{}{}", TEST_DIR, filepath),
+            (line, column),
+            span,
+        )
+    }
+}
+
+// Test that only string values are searched, and that key paths are recorded.
+#[test]
+fn structured_data() -> Result<()> {
+    let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
+        .structured_data()
+        .no_check()
+        .silent();
+
+    let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
+
+    util::assert_list_eq(
+        &info.file_urls,
+        &[
+            fileurl(
+                "https://home.example.com/",
+                "config.toml",
+                (3, 13),
+                (57, 82),
+                Some("package.homepage"),
+            ),
+            fileurl(
+                "https://github.com/m-cat/loch",
+                "config.toml",
+                (4, 15),
+                (98, 127),
+                Some("package.repository"),
+            ),
+            fileurl(
+                "https://home.example.com/",
+                "package.json",
+                (3, 16),
+                (38, 63),
+                Some("homepage"),
+            ),
+            fileurl(
+                "https://github.com/m-cat/loch.git",
+                "package.json",
+                (6, 13),
+                (115, 148),
+                Some("repository.url"),
+            ),
+            fileurl(
+                "https://api.example.com/v1",
+                "ci.yml",
+                (3, 10),
+                (49, 75),
+                Some("servers[0].url"),
+            ),
+        ],
+    );
+
+    Ok(())
+}
+
+// Test that structured data files are searched line by line by default.
+#[test]
+fn structured_data_disabled() -> Result<()> {
+    let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
+        .no_check()
+        .silent();
+
+    let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
+
+    assert_eq!(info.file_urls.len(), 7);
+    assert!(info
+        .file_urls
+        .iter()
+        .all(|file_url| file_url.key_path.is_none()));
+
+    Ok(())
+}
@@ -0,0 +1,159 @@
+extern crate loch;
+
+mod util;
+
+use loch::{Config, Exclusion, FileUrl, Placeholder, Result, Scheme};
+
+static TEST_DIR: &str = "tests/suppress_dir/";
+
+// Construct an unchecked FileUrl at the given line and column, suppressed if `suppressed` is set.
+fn fileurl(
+    url: &str,
+    filepath: &str,
+    (line, column): (usize, usize),
+    span: (usize, usize),
+    suppressed: bool,
+) -> FileUrl {
+    FileUrl {
+        excluded: if suppressed {
+            Some(Exclusion::InlineSuppression)
+        } else {
+            None
+        },
+        ..util::fileurl(
+            url,
+            &format!("{}{}", TEST_DIR, filepath),
+            (line, column),
+            span,
+        )
+    }
+}
+
+// Test that links suppressed by inline directives are reported as excluded.
+#[test]
+fn inline_suppression() -> Result<()> {
+    let config = Config::default()
+        .check_placeholders(Placeholder::ALL)
+        .no_check()
+        .silent();
+
+    let info = loch::check_paths(&[TEST_DIR], Some(&config))?;
+
+    util::assert_list_eq(
+        &info.file_urls,
+        &[
+            fileurl(
+                "https://api.example.com/",
+                "client.py",
+                (1, 13),
+                (12, 36),
+                false,
+            ),
+            fileurl(
+                "https://fake.example.com/",
+                "client.py",
+                (2, 13),
+                (50, 75),
+                true,
+            ),
+            fileurl(
+                "https://broken.example.com/",
+                "client.py",
+                (4, 15),
+                (135, 162),
+                true,
+            ),
+            fileurl(
+                "https://docs.example.com/",
+                "client.py",
+                (5, 13),
+                (176, 201),
+                false,
+            ),
+            fileurl(
+                "https://tool.example.com/",
+                "tutorial.md",
+                (3, 24),
+                (35, 60),
+                false,
+            ),
+            fileurl(
+                "https://your-server.example.com/api",
+                "tutorial.md",
+                (6, 18),
+                (102, 137),
+                true,
+            ),
+            fileurl(
+                "https://staging.example.com/api",
+                "tutorial.md",
+                (7, 4),
+                (141, 172),
+                true,
+            ),
+            fileurl(
+                "https://issues.example.com/",
+                "tutorial.md",
+                (10, 20),
+                (215, 242),
+                false,
+            ),
+        ],
+    );
+
+    Ok(())
+}
+
+// Test that a URL suppressed in one file is still checked where it isn't suppressed, and that the
+// result of checking it isn't reported for the suppressed occurrence.
+#[test]
+fn suppressed_repeat() -> Result<()> {
+    let config = Config::default().schemes(&[Scheme::Tel]).silent();
+
+    let info = loch::check_paths(&["tests/suppress_repeat_dir/"], Some(&config))?;
+
+    let results: Vec<_> = info
+        .file_urls
+        .iter()
+        .map(|file_url| {
+            (
+                file_url.filepath.to_string_lossy().into_owned(),
+                file_url.url.as_str(),
+                file_url.excluded.is_some(),
+                file_url.bad,
+            )
+        })
+        .collect();
+    assert_eq!(
+        results,
+        [
+            (
+                "tests/suppress_repeat_dir/a.md".into(),
+                "tel:555-0123",
+                true,
+                None
+            ),
+            (
+                "tests/suppress_repeat_dir/b.md".into(),
+                "tel:555-0123",
+                false,
+                Some(true)
+            ),
+            (
+                "tests/suppress_repeat_dir/a.md".into(),
+                "tel:555-0199",
+                false,
+                Some(true)
+            ),
+            (
+                "tests/suppress_repeat_dir/b.md".into(),
+                "tel:555-0199",
+                true,
+                None
+            ),
+        ]
+    );
+    assert_eq!(info.num_bad_urls, 2);
+
+    Ok(())
+}
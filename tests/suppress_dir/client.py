@@ -0,0 +1,5 @@
+BASE_URL = "https://api.example.com/"
+FAKE_URL = "https://fake.example.com/"  # loch-ignore-line
+# loch-ignore-next-line
+BROKEN_URL = "https://broken.example.com/"
+DOCS_URL = "https://docs.example.com/"
@@ -0,0 +1,150 @@
+extern crate loch;
+
+mod server;
+
+use loch::{Config, HostConfig, MethodStrategy, Placeholder, Result, TimeoutPhase, Timeouts};
+use server::{Fixture, Response};
+use std::{net::TcpListener, thread, time::Duration};
+
+// Checks links to a site whose `/slow` page takes 1.5 seconds to answer, and returns the timeouts
+// that expired, by path relative to the site.
+fn check(config: Config) -> Result<Vec<(String, Option<TimeoutPhase>)>> {
+    let port = server::serve(|request| {
+        if request.path == "/slow" {
+            thread::sleep(Duration::from_millis(1500));
+        }
+        Response::ok("<title>Page</title>")
+    });
+
+    let base = format!("http://127.0.0.1:{}", port);
+    let fixture = Fixture::new(&[("links.md", format!("+ {0}/page\n+ {0}/slow\n", base))]);
+
+    // The server's address is local.
+    let config = config.check_placeholders(&[Placeholder::Local]).silent();
+    let info = loch::check_paths(&[fixture.path_str()], Some(&config))?;
+
+    Ok(info
+        .file_urls
+        .into_iter()
+        .map(|file_url| {
+            assert_eq!(file_url.bad, Some(file_url.timeout.is_some()));
+            (file_url.url.replace(&base, ""), file_url.timeout)
+        })
+        .collect())
+}
+
+// Starts a server that accepts connections but never sends the greeting of an FTP server, and
+// returns the port.
+fn serve_silent_ftp() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    thread::spawn(move || {
+        // Keep the connections open.
+        let mut streams = vec![];
+        for stream in listener.incoming() {
+            streams.push(stream);
+        }
+    });
+
+    port
+}
+
+fn timeouts(timeouts: &str) -> Timeouts {
+    timeouts.parse().unwrap()
+}
+
+// Test that a page answering too late is bad, with the first byte timeout recorded.
+#[test]
+fn first_byte_timeout() -> Result<()> {
+    let config = Config::default().timeouts(timeouts("first-byte:200,total:10000"));
+
+    assert_eq!(
+        check(config)?,
+        vec![
+            ("/page".to_string(), None),
+            ("/slow".to_string(), Some(TimeoutPhase::FirstByte)),
+        ]
+    );
+
+    Ok(())
+}
+
+// Test that the first byte timeout applies when whole pages are downloaded.
+#[test]
+fn first_byte_timeout_with_body() -> Result<()> {
+    let config = Config::default()
+        .method(MethodStrategy::Get)
+        .soft_404()
+        .timeouts(timeouts("first-byte:200"));
+
+    assert_eq!(
+        check(config)?,
+        vec![
+            ("/page".to_string(), None),
+            ("/slow".to_string(), Some(TimeoutPhase::FirstByte)),
+        ]
+    );
+
+    Ok(())
+}
+
+// Test that the first timeout to expire is recorded.
+#[test]
+fn total_timeout() -> Result<()> {
+    let config = Config::default().timeouts(timeouts("first-byte:10000,total:200"));
+    assert_eq!(
+        check(config)?[1],
+        ("/slow".to_string(), Some(TimeoutPhase::Total))
+    );
+
+    // The timeout in seconds is a total timeout.
+    let config = Config::default().timeout(1);
+    assert_eq!(
+        check(config)?[1],
+        ("/slow".to_string(), Some(TimeoutPhase::Total))
+    );
+
+    Ok(())
+}
+
+// Test that the timeouts of a host override the global ones.
+#[test]
+fn host_timeouts() -> Result<()> {
+    let config = Config::default()
+        .timeouts(timeouts("connect:1000,total:200"))
+        .host(HostConfig::new("127.0.0.1").timeouts(timeouts("total:10000")));
+
+    assert_eq!(
+        check(config)?,
+        vec![("/page".to_string(), None), ("/slow".to_string(), None)]
+    );
+
+    Ok(())
+}
+
+// Test that the connect and total timeouts apply to FTP links.
+#[test]
+fn ftp_timeouts() -> Result<()> {
+    let port = serve_silent_ftp();
+    let fixture = Fixture::new(&[(
+        "links.md",
+        format!("ftp://127.0.0.1:{}/pub/loch-0.1.tar.gz\n", port),
+    )]);
+
+    for (ftp_timeouts, phase) in &[
+        ("connect:200", TimeoutPhase::Connect),
+        ("connect:10000,total:200", TimeoutPhase::Total),
+    ] {
+        let config = Config::default()
+            .check_placeholders(&[Placeholder::Local])
+            .timeouts(timeouts(ftp_timeouts))
+            .silent();
+        let info = loch::check_paths(&[fixture.path_str()], Some(&config))?;
+
+        assert_eq!(info.file_urls[0].bad, Some(true));
+        assert_eq!(info.file_urls[0].timeout, Some(*phase), "{}", ftp_timeouts);
+    }
+
+    Ok(())
+}
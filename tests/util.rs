@@ -1,5 +1,28 @@
+//! Helpers shared by the tests.
+
+#![allow(dead_code)]
+
+use loch::FileUrl;
 use pretty_assertions::assert_eq;
-use std::fmt::Debug;
+use std::{fmt::Debug, path::PathBuf};
+
+/// Returns an unchecked `FileUrl` for `url` at the given line and column and byte span of
+/// `filepath`, with the other fields unset.
+pub fn fileurl(
+    url: &str,
+    filepath: &str,
+    (line, column): (usize, usize),
+    span: (usize, usize),
+) -> FileUrl {
+    FileUrl {
+        url: url.into(),
+        filepath: PathBuf::from(filepath),
+        line,
+        column,
+        span,
+        ..Default::default()
+    }
+}
 
 /// Asserts that the lists contain the same elements, unordered.
 pub fn assert_list_eq<T>(list1: &[T], list2: &[T])